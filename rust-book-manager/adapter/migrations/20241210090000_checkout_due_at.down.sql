@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS due_at;
+ALTER TABLE checkouts DROP COLUMN IF EXISTS due_at;
@@ -0,0 +1,16 @@
+-- 貸出に返却期限(due_at)を追加する
+-- 既存の貸出は貸出日から14日後を返却期限として埋める
+ALTER TABLE checkouts
+  ADD COLUMN IF NOT EXISTS due_at TIMESTAMP(3) WITH TIME ZONE;
+
+UPDATE checkouts SET due_at = checked_out_at + INTERVAL '14 days' WHERE due_at IS NULL;
+
+ALTER TABLE checkouts ALTER COLUMN due_at SET NOT NULL;
+
+-- 返却済みの貸出にも返却期限を保持し、延滞して返却されたかを後から確認できるようにする
+ALTER TABLE returned_checkouts
+  ADD COLUMN IF NOT EXISTS due_at TIMESTAMP(3) WITH TIME ZONE;
+
+UPDATE returned_checkouts SET due_at = checked_out_at + INTERVAL '14 days' WHERE due_at IS NULL;
+
+ALTER TABLE returned_checkouts ALTER COLUMN due_at SET NOT NULL;
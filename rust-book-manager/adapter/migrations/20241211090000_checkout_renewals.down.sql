@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS renewal_count;
+ALTER TABLE checkouts DROP COLUMN IF EXISTS renewal_count;
@@ -0,0 +1,6 @@
+-- 貸出の延長回数を記録する
+ALTER TABLE checkouts
+  ADD COLUMN IF NOT EXISTS renewal_count INTEGER NOT NULL DEFAULT 0;
+
+ALTER TABLE returned_checkouts
+  ADD COLUMN IF NOT EXISTS renewal_count INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,21 @@
+-- 貸出中の蔵書に対する予約（取り置き）の待ち行列
+-- 予約はreserved_atの古い順に処理する
+-- 蔵書が返却されると先頭の予約にpickup_expires_atが設定され、その日時までは予約者のみが借りられる
+CREATE TABLE IF NOT EXISTS reservations (
+    reservation_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    reserved_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    pickup_expires_at TIMESTAMP(3) WITH TIME ZONE,
+
+  UNIQUE (book_id, user_id),
+  FOREIGN KEY (book_id) REFERENCES books(book_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE,
+  FOREIGN KEY (user_id) REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS reservations_book_id_reserved_at_idx
+  ON reservations (book_id, reserved_at);
@@ -0,0 +1,23 @@
+-- 延滞料金などの罰金台帳
+-- amountは請求をプラス、支払い・免除をマイナスで記録し、合計がユーザーの未払い残高となる
+-- checkout_idは貸出中・返却済みのどちらのテーブルにも存在しうるため外部キーは張らない
+-- created_byがNULLの場合はシステムによる自動計上を表す
+CREATE TABLE IF NOT EXISTS fines (
+    fine_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    checkout_id UUID,
+    kind VARCHAR(32) NOT NULL,
+    amount BIGINT NOT NULL,
+    note VARCHAR(1024) NOT NULL DEFAULT '',
+    created_by UUID,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+  FOREIGN KEY (user_id) REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE,
+  FOREIGN KEY (created_by) REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS fines_user_id_idx ON fines (user_id);
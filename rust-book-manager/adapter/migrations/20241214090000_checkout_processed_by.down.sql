@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE returned_checkouts
+  DROP COLUMN IF EXISTS return_processed_by,
+  DROP COLUMN IF EXISTS checkout_processed_by;
+ALTER TABLE checkouts DROP COLUMN IF EXISTS checkout_processed_by;
@@ -0,0 +1,20 @@
+-- 貸出・返却の操作を行ったユーザーを、借りたユーザーとは別に記録する
+-- 管理者が窓口で代理操作した場合に、借りたユーザーと操作したユーザーが異なる
+-- 既存の貸出は借りたユーザー自身が操作したものとして埋める
+ALTER TABLE checkouts
+  ADD COLUMN IF NOT EXISTS checkout_processed_by UUID;
+
+UPDATE checkouts SET checkout_processed_by = user_id WHERE checkout_processed_by IS NULL;
+
+ALTER TABLE checkouts ALTER COLUMN checkout_processed_by SET NOT NULL;
+
+ALTER TABLE returned_checkouts
+  ADD COLUMN IF NOT EXISTS checkout_processed_by UUID,
+  ADD COLUMN IF NOT EXISTS return_processed_by UUID;
+
+UPDATE returned_checkouts SET checkout_processed_by = user_id WHERE checkout_processed_by IS NULL;
+UPDATE returned_checkouts SET return_processed_by = user_id WHERE return_processed_by IS NULL;
+
+ALTER TABLE returned_checkouts
+  ALTER COLUMN checkout_processed_by SET NOT NULL,
+  ALTER COLUMN return_processed_by SET NOT NULL;
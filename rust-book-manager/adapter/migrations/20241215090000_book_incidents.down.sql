@@ -0,0 +1,5 @@
+-- Add down migration script here
+ALTER TABLE returned_checkouts
+  DROP COLUMN IF EXISTS incident_reported_at,
+  DROP COLUMN IF EXISTS incident;
+ALTER TABLE books DROP COLUMN IF EXISTS status;
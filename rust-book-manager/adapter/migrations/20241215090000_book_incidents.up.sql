@@ -0,0 +1,10 @@
+-- 蔵書の状態を追加する
+-- Available: 通常, Lost: 紛失, Damaged: 破損
+ALTER TABLE books
+  ADD COLUMN IF NOT EXISTS status VARCHAR(32) NOT NULL DEFAULT 'Available';
+
+-- 貸出履歴に紛失・破損の報告を記録する
+-- 紛失の場合は報告時点で返却済みとして扱う
+ALTER TABLE returned_checkouts
+  ADD COLUMN IF NOT EXISTS incident VARCHAR(32),
+  ADD COLUMN IF NOT EXISTS incident_reported_at TIMESTAMP(3) WITH TIME ZONE;
@@ -0,0 +1,19 @@
+-- Add down migration script here
+-- 書誌ごとに1冊の貸出しか保持できないため、2冊目以降の貸出は削除される
+ALTER TABLE returned_checkouts DROP COLUMN IF EXISTS copy_id;
+
+DELETE FROM checkouts AS c
+USING checkouts AS other
+WHERE c.book_id = other.book_id AND c.checked_out_at > other.checked_out_at;
+DROP INDEX IF EXISTS checkouts_book_id_idx;
+ALTER TABLE checkouts
+  DROP CONSTRAINT IF EXISTS checkouts_copy_id_fkey,
+  DROP CONSTRAINT IF EXISTS checkouts_copy_id_key,
+  ADD CONSTRAINT checkouts_book_id_key UNIQUE (book_id),
+  DROP COLUMN IF EXISTS copy_id;
+
+ALTER TABLE books ADD COLUMN IF NOT EXISTS status VARCHAR(32) NOT NULL DEFAULT 'Available';
+UPDATE books AS b SET status = c.status FROM book_copies AS c WHERE c.copy_id = b.book_id;
+
+DROP TRIGGER IF EXISTS book_copies_updated_at_trigger ON book_copies;
+DROP TABLE IF EXISTS book_copies;
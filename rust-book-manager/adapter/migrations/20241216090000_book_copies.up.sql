@@ -0,0 +1,46 @@
+-- 書誌情報(books)と物理的な蔵書(book_copies)を分け、同じ書誌に複数の蔵書を持てるようにする
+-- 紛失・破損は物理的な蔵書ごとの状態なので、booksからbook_copiesに移す
+CREATE TABLE IF NOT EXISTS book_copies (
+    copy_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    book_id UUID NOT NULL,
+    barcode VARCHAR(64) NOT NULL UNIQUE,
+    status VARCHAR(32) NOT NULL DEFAULT 'Available',
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+  FOREIGN KEY (book_id) REFERENCES books(book_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS book_copies_book_id_idx ON book_copies (book_id);
+
+CREATE TRIGGER book_copies_updated_at_trigger
+    BEFORE UPDATE ON book_copies FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
+
+-- 既存の書誌はそれぞれ1冊の蔵書を持つものとして移行する
+-- 蔵書IDには書誌IDをそのまま使い、バーコードはハイフンを除いたIDとする
+INSERT INTO book_copies (copy_id, book_id, barcode, status, created_at, updated_at)
+SELECT book_id, book_id, REPLACE(book_id::text, '-', ''), status, created_at, updated_at
+FROM books
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE books DROP COLUMN IF EXISTS status;
+
+-- 貸出は書誌ではなく蔵書に対して行う
+-- 同じ書誌の蔵書は同時に複数貸し出せるため、book_idの一意制約をcopy_idに移す
+ALTER TABLE checkouts ADD COLUMN IF NOT EXISTS copy_id UUID;
+UPDATE checkouts SET copy_id = book_id WHERE copy_id IS NULL;
+ALTER TABLE checkouts
+  ALTER COLUMN copy_id SET NOT NULL,
+  DROP CONSTRAINT IF EXISTS checkouts_book_id_key,
+  ADD CONSTRAINT checkouts_copy_id_key UNIQUE (copy_id),
+  ADD CONSTRAINT checkouts_copy_id_fkey FOREIGN KEY (copy_id) REFERENCES book_copies(copy_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS checkouts_book_id_idx ON checkouts (book_id);
+
+ALTER TABLE returned_checkouts ADD COLUMN IF NOT EXISTS copy_id UUID;
+UPDATE returned_checkouts SET copy_id = book_id WHERE copy_id IS NULL;
+ALTER TABLE returned_checkouts ALTER COLUMN copy_id SET NOT NULL;
@@ -0,0 +1,12 @@
+-- 蔵書検索用に、タイトル・著者・説明文から全文検索用のtsvectorを生成しておく
+-- 日本語の形態素解析は行わないため、言語に依存しない'simple'設定で空白や記号区切りの単語を索引する
+-- 一致した列によって順位を変えられるよう、タイトル > 著者 > 説明文の順に重みを付ける
+ALTER TABLE books
+    ADD COLUMN IF NOT EXISTS search_vector TSVECTOR
+    GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('simple', coalesce(author, '')), 'B') ||
+        setweight(to_tsvector('simple', coalesce(description, '')), 'C')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS books_search_vector_idx ON books USING GIN (search_vector);
@@ -0,0 +1,2 @@
+-- 正規化前の表記(ハイフンの位置やISBN-10であったか)は保持していないため、元に戻す処理はない
+SELECT 1;
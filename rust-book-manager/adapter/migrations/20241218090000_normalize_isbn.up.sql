@@ -0,0 +1,53 @@
+-- 既存の書誌のISBNを、ハイフンを除いたISBN-13の形式に正規化する
+-- チェックディジットが合わないなど正規化できないものは変更せず、書誌IDとISBNを警告として出力する
+CREATE FUNCTION pg_temp.normalize_isbn(raw TEXT) RETURNS TEXT AS $$
+DECLARE
+    digits TEXT := upper(regexp_replace(raw, '[[:space:]-]', '', 'g'));
+    total INT := 0;
+BEGIN
+    IF digits ~ '^[0-9]{9}[0-9X]$' THEN
+        FOR i IN 1..10 LOOP
+            total := total + (11 - i) * CASE
+                WHEN substr(digits, i, 1) = 'X' THEN 10
+                ELSE substr(digits, i, 1)::INT
+            END;
+        END LOOP;
+        IF total % 11 <> 0 THEN
+            RETURN NULL;
+        END IF;
+
+        -- 先頭に978を付け、チェックディジットを計算し直してISBN-13にする
+        digits := '978' || substr(digits, 1, 9);
+        total := 0;
+        FOR i IN 1..12 LOOP
+            total := total + CASE WHEN i % 2 = 1 THEN 1 ELSE 3 END * substr(digits, i, 1)::INT;
+        END LOOP;
+        RETURN digits || ((10 - total % 10) % 10)::TEXT;
+    ELSIF digits ~ '^97[89][0-9]{10}$' THEN
+        FOR i IN 1..13 LOOP
+            total := total + CASE WHEN i % 2 = 1 THEN 1 ELSE 3 END * substr(digits, i, 1)::INT;
+        END LOOP;
+        IF total % 10 <> 0 THEN
+            RETURN NULL;
+        END IF;
+        RETURN digits;
+    END IF;
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+DO $$
+DECLARE
+    r RECORD;
+BEGIN
+    FOR r IN SELECT book_id, isbn FROM books WHERE pg_temp.normalize_isbn(isbn) IS NULL LOOP
+        RAISE WARNING '正規化できないISBNです: book_id=%, isbn=%', r.book_id, r.isbn;
+    END LOOP;
+END;
+$$;
+
+UPDATE books
+SET isbn = pg_temp.normalize_isbn(isbn)
+WHERE pg_temp.normalize_isbn(isbn) IS NOT NULL
+    AND pg_temp.normalize_isbn(isbn) <> isbn;
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS books_normalized_title_author_idx;
+DROP INDEX IF EXISTS books_isbn_idx;
+DROP FUNCTION IF EXISTS normalize_book_text(TEXT);
@@ -0,0 +1,10 @@
+-- 蔵書登録時の重複検出に使う
+-- タイトル・著者は大文字・小文字や空白、記号の違いを無視して比較するため、正規化する関数と式インデックスを用意する
+CREATE OR REPLACE FUNCTION normalize_book_text(value TEXT) RETURNS TEXT AS $$
+    SELECT lower(regexp_replace(value, '[[:space:][:punct:]]', '', 'g'));
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE INDEX IF NOT EXISTS books_isbn_idx ON books (isbn);
+
+CREATE INDEX IF NOT EXISTS books_normalized_title_author_idx
+    ON books (normalize_book_text(title), normalize_book_text(author));
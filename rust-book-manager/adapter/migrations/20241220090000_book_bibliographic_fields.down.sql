@@ -0,0 +1,11 @@
+ALTER TABLE books
+    DROP CONSTRAINT IF EXISTS books_authors_check,
+    DROP CONSTRAINT IF EXISTS books_published_year_check,
+    DROP CONSTRAINT IF EXISTS books_page_count_check,
+    DROP COLUMN IF EXISTS authors,
+    DROP COLUMN IF EXISTS publisher,
+    DROP COLUMN IF EXISTS published_year,
+    DROP COLUMN IF EXISTS language,
+    DROP COLUMN IF EXISTS page_count,
+    DROP COLUMN IF EXISTS edition,
+    DROP COLUMN IF EXISTS subjects;
@@ -0,0 +1,20 @@
+-- 書誌情報に出版社・出版年・言語・ページ数・版・共著者・件名分類を持たせる
+-- 共著者は並び順に意味があるため、配列として順序どおりに保持する
+-- author列は一覧の並び替えや全文検索、重複検出に使う表示用の著者名として残し、authorsを', 'で連結した値を入れる
+ALTER TABLE books
+    ADD COLUMN IF NOT EXISTS authors TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS publisher VARCHAR(255),
+    ADD COLUMN IF NOT EXISTS published_year INTEGER,
+    -- ISO 639-2の3文字の言語コード(例: jpn, eng)
+    ADD COLUMN IF NOT EXISTS language VARCHAR(3),
+    ADD COLUMN IF NOT EXISTS page_count INTEGER,
+    ADD COLUMN IF NOT EXISTS edition VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS subjects TEXT[] NOT NULL DEFAULT '{}';
+
+-- 既存の書誌は、author列の値を唯一の著者とする
+UPDATE books SET authors = ARRAY[author] WHERE cardinality(authors) = 0;
+
+ALTER TABLE books
+    ADD CONSTRAINT books_authors_check CHECK (cardinality(authors) >= 1),
+    ADD CONSTRAINT books_published_year_check CHECK (published_year BETWEEN 1000 AND 9999),
+    ADD CONSTRAINT books_page_count_check CHECK (page_count > 0);
@@ -0,0 +1,25 @@
+-- 書誌をトピックごとに分類するためのタグ
+-- タグ名は大文字・小文字を区別せずに一意とし、最初に登録された表記を使う
+CREATE TABLE IF NOT EXISTS tags (
+    tag_id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tags_lower_name_key ON tags (lower(name));
+
+CREATE TABLE IF NOT EXISTS book_tags (
+    book_id UUID NOT NULL,
+    tag_id UUID NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+    PRIMARY KEY (book_id, tag_id),
+    FOREIGN KEY (book_id) REFERENCES books(book_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE,
+    FOREIGN KEY (tag_id) REFERENCES tags(tag_id)
+        ON UPDATE CASCADE
+        ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS book_tags_tag_id_idx ON book_tags (tag_id);
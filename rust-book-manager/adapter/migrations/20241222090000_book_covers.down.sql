@@ -0,0 +1,2 @@
+DROP TRIGGER IF EXISTS book_covers_updated_at_trigger ON book_covers;
+DROP TABLE IF EXISTS book_covers;
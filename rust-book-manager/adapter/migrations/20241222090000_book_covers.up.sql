@@ -0,0 +1,23 @@
+-- 書誌の表紙画像
+-- 画像そのものはストレージに保存し、ここではアップロードされた画像の形式と大きさを記録する
+-- サムネイルはアップロード時に生成し、常にJPEGで保存する
+CREATE TABLE IF NOT EXISTS book_covers (
+    book_id UUID PRIMARY KEY,
+    content_type VARCHAR(32) NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL,
+    uploaded_by UUID NOT NULL,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+    updated_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+  FOREIGN KEY (book_id) REFERENCES books(book_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE,
+  FOREIGN KEY (uploaded_by) REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE
+);
+
+CREATE TRIGGER book_covers_updated_at_trigger
+    BEFORE UPDATE ON book_covers FOR EACH ROW
+    EXECUTE PROCEDURE set_updated_at();
@@ -0,0 +1,3 @@
+ALTER TABLE books
+  DROP COLUMN IF EXISTS archived_by,
+  DROP COLUMN IF EXISTS archived_at;
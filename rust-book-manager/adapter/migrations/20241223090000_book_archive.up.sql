@@ -0,0 +1,7 @@
+-- 書誌を削除する代わりにアーカイブする
+-- アーカイブした書誌は一覧に表示せず貸出もできないが、貸出履歴などはそのまま残す
+ALTER TABLE books
+  ADD COLUMN IF NOT EXISTS archived_at TIMESTAMP(3) WITH TIME ZONE,
+  ADD COLUMN IF NOT EXISTS archived_by UUID REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE SET NULL;
@@ -0,0 +1,42 @@
+-- 書誌の版(編集履歴)
+-- 書誌の登録時と更新時に、その時点の書誌情報と1つ前の版から変更された項目を記録する
+CREATE TABLE IF NOT EXISTS book_revisions (
+    book_id UUID NOT NULL,
+    revision INTEGER NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    authors TEXT[] NOT NULL,
+    isbn VARCHAR(255) NOT NULL,
+    description VARCHAR(1024) NOT NULL,
+    publisher VARCHAR(255),
+    published_year INTEGER,
+    language VARCHAR(3),
+    page_count INTEGER,
+    edition VARCHAR(64),
+    subjects TEXT[] NOT NULL,
+    changed_fields TEXT[] NOT NULL,
+    edited_by UUID,
+    created_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3),
+
+  PRIMARY KEY (book_id, revision),
+  FOREIGN KEY (book_id) REFERENCES books(book_id)
+      ON UPDATE CASCADE
+      ON DELETE CASCADE,
+  FOREIGN KEY (edited_by) REFERENCES users(user_id)
+      ON UPDATE CASCADE
+      ON DELETE SET NULL
+);
+
+-- 既存の書誌は、現在の内容を所有者が登録した最初の版とする
+INSERT INTO book_revisions (
+    book_id, revision, title, authors, isbn, description, publisher, published_year,
+    language, page_count, edition, subjects, changed_fields, edited_by, created_at
+)
+SELECT
+    book_id, 1, title, authors, isbn, description, publisher, published_year,
+    language, page_count, edition, subjects,
+    ARRAY[
+        'title', 'authors', 'isbn', 'description', 'publisher', 'published_year',
+        'language', 'page_count', 'edition', 'subjects'
+    ],
+    user_id, created_at
+FROM books;
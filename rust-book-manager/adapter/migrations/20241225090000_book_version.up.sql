@@ -0,0 +1,4 @@
+-- 書誌情報を更新するたびに増やす版番号
+-- ETagとして返し、If-Matchで指定された版と一致する場合のみ更新する
+ALTER TABLE books
+  ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS catalog_authors;
+DROP TABLE IF EXISTS catalog_records;
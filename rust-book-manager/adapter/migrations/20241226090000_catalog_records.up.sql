@@ -0,0 +1,22 @@
+-- ISBNから書誌情報を引くために、Open LibraryやMARC21の書誌データを取り込んでおく表
+-- import-catalogコマンドで取り込み、同じISBNを取り込み直した場合は後から取り込んだ内容で置き換える
+CREATE TABLE IF NOT EXISTS catalog_records (
+    isbn VARCHAR(13) PRIMARY KEY,
+    title TEXT,
+    -- Open Libraryの版が参照する著者のキー。名前はcatalog_authorsから引く
+    author_keys TEXT[] NOT NULL DEFAULT '{}',
+    -- 著者キーから名前を引けない場合に使う著者名(MARC21の100$aやOpen Libraryのby_statement)
+    author TEXT,
+    publisher TEXT,
+    published_year INTEGER,
+    page_count INTEGER,
+    language TEXT,
+    imported_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
+
+-- Open Libraryの著者。版とは別のダンプで配布されるため、取り込む順序によらず引けるよう別の表にする
+CREATE TABLE IF NOT EXISTS catalog_authors (
+    author_key TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    imported_at TIMESTAMP(3) WITH TIME ZONE NOT NULL DEFAULT CURRENT_TIMESTAMP(3)
+);
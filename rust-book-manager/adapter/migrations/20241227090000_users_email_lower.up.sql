@@ -0,0 +1,27 @@
+-- ログイン時はメールアドレスの大文字・小文字を区別しないため、大文字・小文字だけが異なるメールアドレスも重複とする
+-- すでに重複しているユーザーがいる場合は一意インデックスを作れないため、該当するユーザーを列挙して移行を中止する
+-- 移行を再実行する前に、どちらかのユーザーのメールアドレスを変更するか、ユーザーを統合しておく
+DO $$
+DECLARE
+    conflicts TEXT;
+BEGIN
+    SELECT string_agg(
+        format('%s (user_id: %s)', email, user_ids),
+        E'\n' ORDER BY email
+    )
+    INTO conflicts
+    FROM (
+        SELECT email, string_agg(user_id::text, ', ' ORDER BY created_at) AS user_ids
+        FROM (SELECT lower(email) AS email, user_id, created_at FROM users) AS u
+        GROUP BY email
+        HAVING COUNT(*) > 1
+    ) AS duplicated;
+
+    IF conflicts IS NOT NULL THEN
+        RAISE EXCEPTION E'大文字・小文字だけが異なるメールアドレスのユーザーがいます:\n%', conflicts
+            USING HINT = 'いずれかのユーザーのメールアドレスを変更してから、移行を再実行してください。';
+    END IF;
+END;
+$$;
+
+CREATE UNIQUE INDEX IF NOT EXISTS users_email_lower_key ON users (lower(email));
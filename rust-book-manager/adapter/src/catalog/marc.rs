@@ -0,0 +1,233 @@
+use kernel::model::book::isbn::Isbn;
+use shared::error::AppResult;
+use std::{io::BufRead, str::FromStr};
+
+use super::{find_year, CatalogEntry, CatalogRecord};
+
+const RECORD_TERMINATOR: u8 = 0x1d;
+const FIELD_TERMINATOR: u8 = 0x1e;
+const SUBFIELD_DELIMITER: u8 = 0x1f;
+
+// MARC21(ISO 2709)形式のファイルを解析する
+// 文字コードはUTF-8であることを前提とし、以下のフィールドから書誌情報を取り出す
+// ISBN: 020$a, タイトル: 245$a$b, 著者: 100$a(なければ110$a, 245$c),
+// 出版者: 264$b(なければ260$b), 出版年: 264$c(なければ260$c, 008), ページ数: 300$a, 言語: 008(なければ041$a)
+// 解析したレコードは読み取った順にemitに渡す
+pub fn parse(
+    mut reader: impl BufRead,
+    emit: &mut impl FnMut(CatalogEntry) -> AppResult<()>,
+) -> AppResult<()> {
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        if reader.read_until(RECORD_TERMINATOR, &mut buf)? == 0 {
+            break;
+        }
+        // 形式が崩れているレコードは読み飛ばす
+        if let Some(fields) = parse_fields(&buf) {
+            for record in into_records(&fields) {
+                emit(CatalogEntry::Record(record))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Field {
+    tag: String,
+    data: Vec<u8>,
+}
+
+impl Field {
+    // 制御フィールド(001〜009)以外は、先頭2バイトの指示子の後にサブフィールドが続く
+    fn subfields(&self) -> impl Iterator<Item = (u8, String)> + '_ {
+        self.data
+            .get(2..)
+            .unwrap_or_default()
+            .split(|b| *b == SUBFIELD_DELIMITER)
+            .filter_map(|s| s.split_first())
+            .map(|(code, value)| (*code, String::from_utf8_lossy(value).trim().to_string()))
+    }
+
+    fn subfield(&self, code: u8) -> Option<String> {
+        self.subfields()
+            .find(|(c, v)| *c == code && !v.is_empty())
+            .map(|(_, v)| v)
+    }
+
+    fn control(&self) -> String {
+        String::from_utf8_lossy(&self.data).to_string()
+    }
+}
+
+// リーダー(24バイト)のベースアドレスとディレクトリから、各フィールドを取り出す
+fn parse_fields(record: &[u8]) -> Option<Vec<Field>> {
+    let base = std::str::from_utf8(record.get(12..17)?)
+        .ok()?
+        .parse::<usize>()
+        .ok()?;
+    let directory = record.get(24..base.checked_sub(1)?)?;
+
+    directory
+        .chunks_exact(12)
+        .map(|entry| {
+            let tag = std::str::from_utf8(&entry[0..3]).ok()?.to_string();
+            let length = std::str::from_utf8(&entry[3..7]).ok()?.parse::<usize>().ok()?;
+            let start = std::str::from_utf8(&entry[7..12]).ok()?.parse::<usize>().ok()?;
+            let data = record.get(base + start..base + start + length)?;
+            let data = data.strip_suffix(&[FIELD_TERMINATOR]).unwrap_or(data);
+            Some(Field {
+                tag,
+                data: data.to_vec(),
+            })
+        })
+        .collect()
+}
+
+fn into_records(fields: &[Field]) -> Vec<CatalogRecord> {
+    let field = |tag: &str| fields.iter().find(|f| f.tag == tag);
+    let subfield = |tag: &str, code: u8| field(tag).and_then(|f| f.subfield(code));
+    let fixed = field("008").map(Field::control);
+
+    let title = field("245").and_then(|f| {
+        let parts = f
+            .subfields()
+            .filter(|(c, v)| (*c == b'a' || *c == b'b') && !v.is_empty())
+            .map(|(_, v)| trim_punctuation(&v).to_string())
+            .collect::<Vec<_>>();
+        (!parts.is_empty()).then(|| parts.join(" "))
+    });
+    let author = subfield("100", b'a')
+        .or_else(|| subfield("110", b'a'))
+        .or_else(|| subfield("245", b'c'))
+        .map(|v| trim_punctuation(&v).to_string());
+    let publisher = subfield("264", b'b')
+        .or_else(|| subfield("260", b'b'))
+        .map(|v| trim_punctuation(&v).to_string());
+    // 008の7〜10桁目は出版年、35〜37桁目は言語コード
+    let published_year = subfield("264", b'c')
+        .or_else(|| subfield("260", b'c'))
+        .as_deref()
+        .and_then(find_year)
+        .or_else(|| fixed.as_deref().and_then(|f| f.get(7..11)).and_then(find_year));
+    let page_count = subfield("300", b'a').and_then(|v| {
+        let digits = v
+            .chars()
+            .skip_while(|c| !c.is_ascii_digit())
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        digits.parse().ok()
+    });
+    let language = fixed
+        .as_deref()
+        .and_then(|f| f.get(35..38))
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(String::from)
+        .or_else(|| subfield("041", b'a'));
+
+    // 020$aは "9784065369579 (pbk.)" のように補足が付くことがあるので、先頭の語だけを使う
+    let mut isbns: Vec<Isbn> = Vec::new();
+    for f in fields.iter().filter(|f| f.tag == "020") {
+        if let Some(isbn) = f
+            .subfield(b'a')
+            .and_then(|v| v.split_whitespace().next().map(String::from))
+            .and_then(|v| Isbn::from_str(&v).ok())
+        {
+            if !isbns.contains(&isbn) {
+                isbns.push(isbn);
+            }
+        }
+    }
+
+    isbns
+        .into_iter()
+        .map(|isbn| CatalogRecord {
+            isbn,
+            title: title.clone(),
+            author_keys: vec![],
+            author: author.clone(),
+            publisher: publisher.clone(),
+            published_year,
+            page_count,
+            language: language.clone(),
+        })
+        .collect()
+}
+
+// 目録規則で項目の末尾に付ける区切り記号を取り除く
+fn trim_punctuation(value: &str) -> &str {
+    value.trim_end_matches([' ', '/', ':', ';', ',', '.', '='])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // タグとフィールドの内容からMARC21形式のレコードを組み立てる
+    fn build_record(fields: &[(&str, String)]) -> Vec<u8> {
+        let mut directory = Vec::new();
+        let mut data = Vec::new();
+        for (tag, content) in fields {
+            let mut field = content.clone().into_bytes();
+            field.push(FIELD_TERMINATOR);
+            directory.extend(format!("{tag}{:04}{:05}", field.len(), data.len()).into_bytes());
+            data.extend(field);
+        }
+        directory.push(FIELD_TERMINATOR);
+        let base = 24 + directory.len();
+        let length = base + data.len() + 1;
+        let mut record = format!("{length:05}nam a22{base:05}   4500").into_bytes();
+        record.extend(directory);
+        record.extend(data);
+        record.push(RECORD_TERMINATOR);
+        record
+    }
+
+    #[test]
+    fn test_parse_marc_records() {
+        let sf = |code: char, value: &str| format!("\u{1f}{code}{value}");
+        let record = build_record(&[
+            ("001", "JP12345".into()),
+            ("008", format!("{:<35}jpn  ", "240901s2024    ja")),
+            ("020", format!("  {}", sf('a', "9784065369579 (pbk.)"))),
+            ("100", format!("1 {}", sf('a', "豊田, 優貴,"))),
+            (
+                "245",
+                format!(
+                    "10{}{}",
+                    sf('a', "RustによるWebアプリケーション開発 :"),
+                    sf('b', "設計からリリース・運用まで /")
+                ),
+            ),
+            ("264", format!(" 1{}{}", sf('b', "講談社,"), sf('c', "2024."))),
+            ("300", format!("  {}", sf('a', "480p ;"))),
+        ]);
+        let mut dump = record.clone();
+        // ISBNのないレコードは読み飛ばす
+        dump.extend(build_record(&[("245", format!("10{}", sf('a', "ISBNなし")))]));
+
+        let mut entries = Vec::new();
+        parse(dump.as_slice(), &mut |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            entries[0],
+            CatalogEntry::Record(CatalogRecord {
+                isbn: Isbn::from_str("9784065369579").unwrap(),
+                title: Some("RustによるWebアプリケーション開発 設計からリリース・運用まで".into()),
+                author_keys: vec![],
+                author: Some("豊田, 優貴".into()),
+                publisher: Some("講談社".into()),
+                published_year: Some(2024),
+                page_count: Some(480),
+                language: Some("jpn".into()),
+            })
+        );
+    }
+}
@@ -0,0 +1,215 @@
+pub mod marc;
+pub mod open_library;
+
+use derive_new::new;
+use kernel::model::book::isbn::Isbn;
+use shared::error::{AppError, AppResult};
+use std::{collections::HashSet, fs::File, io::BufReader, path::Path};
+use tokio::sync::mpsc;
+
+use crate::database::ConnectionPool;
+
+// 一度に書き込む件数。ダンプ全体をメモリに載せないよう、この件数ずつ読み込んでは書き込む
+const IMPORT_BATCH_SIZE: usize = 1000;
+// 読み込みが書き込みより先行しすぎないよう、待たせておくバッチの上限
+const IMPORT_QUEUE_SIZE: usize = 4;
+
+// 書誌データのファイルから読み取った1件分の情報
+#[derive(Debug, PartialEq, Eq)]
+pub enum CatalogEntry {
+    Record(CatalogRecord),
+    // Open Libraryの著者。版からはキーで参照される
+    Author { key: String, name: String },
+}
+
+// ISBNごとの書誌情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatalogRecord {
+    pub isbn: Isbn,
+    pub title: Option<String>,
+    // Open Libraryの著者キー。名前は取り込んだ著者から引く
+    pub author_keys: Vec<String>,
+    // 著者キーから名前を引けない場合に使う著者名
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub page_count: Option<i32>,
+    pub language: Option<String>,
+}
+
+// 取り込んだ件数
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CatalogImportSummary {
+    pub records: u64,
+    pub authors: u64,
+}
+
+// 書誌データのファイルをcatalog_records・catalog_authorsに取り込む
+// Open Libraryのダンプ(TSV)か、拡張子が.mrcまたは.marcのMARC21形式のファイルを受け付ける
+// Open Libraryは版と著者が別のダンプで配布されるため、それぞれを取り込めばよく、順序は問わない
+#[derive(new)]
+pub struct CatalogImporter {
+    db: ConnectionPool,
+}
+
+impl CatalogImporter {
+    pub async fn import(&self, path: &Path) -> AppResult<CatalogImportSummary> {
+        let file = File::open(path)?;
+        let is_marc = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("mrc") || ext.eq_ignore_ascii_case("marc"));
+
+        // ファイルの読み込みと解析はブロッキング処理用のスレッドで行い、バッチごとにチャネルで受け取って書き込む
+        let (tx, mut rx) = mpsc::channel::<Vec<CatalogEntry>>(IMPORT_QUEUE_SIZE);
+        let reader = tokio::task::spawn_blocking(move || {
+            let reader = BufReader::new(file);
+            let mut batch = Vec::with_capacity(IMPORT_BATCH_SIZE);
+            let mut emit = |entry: CatalogEntry| -> AppResult<()> {
+                batch.push(entry);
+                if batch.len() >= IMPORT_BATCH_SIZE {
+                    send(&tx, std::mem::take(&mut batch))?;
+                }
+                Ok(())
+            };
+            if is_marc {
+                marc::parse(reader, &mut emit)?;
+            } else {
+                open_library::parse(reader, &mut emit)?;
+            }
+            send(&tx, batch)
+        });
+
+        let mut summary = CatalogImportSummary::default();
+        while let Some(batch) = rx.recv().await {
+            let (records, authors) = self.write_batch(batch).await?;
+            summary.records += records;
+            summary.authors += authors;
+        }
+        // 書き込みに失敗して受信側を閉じた場合は、読み込み側もそこで止まる
+        reader
+            .await
+            .map_err(|e| AppError::StorageError(std::io::Error::other(e)))??;
+
+        Ok(summary)
+    }
+
+    async fn write_batch(&self, batch: Vec<CatalogEntry>) -> AppResult<(u64, u64)> {
+        // 1つの文で同じキーを2回更新できないため、バッチ内で重複するものは最初のものだけを使う
+        let mut isbns = HashSet::new();
+        let mut author_keys = HashSet::new();
+        let mut records = Vec::new();
+        let mut authors = Vec::new();
+        for entry in batch {
+            match entry {
+                CatalogEntry::Record(record) => {
+                    if isbns.insert(record.isbn.to_string()) {
+                        records.push(record);
+                    }
+                }
+                CatalogEntry::Author { key, name } => {
+                    if author_keys.insert(key.clone()) {
+                        authors.push((key, name));
+                    }
+                }
+            }
+        }
+
+        let mut tx = self.db.begin().await?;
+        let mut imported_records = 0;
+        if !records.is_empty() {
+            imported_records = sqlx::query!(
+                r#"
+                    INSERT INTO catalog_records (
+                        isbn, title, author_keys, author, publisher, published_year, page_count, language
+                    )
+                    SELECT
+                        r.isbn,
+                        r.title,
+                        ARRAY(SELECT jsonb_array_elements_text(r.author_keys::jsonb)),
+                        r.author,
+                        r.publisher,
+                        r.published_year,
+                        r.page_count,
+                        r.language
+                    FROM UNNEST(
+                        $1::text[], $2::text[], $3::text[], $4::text[],
+                        $5::text[], $6::int[], $7::int[], $8::text[]
+                    ) AS r(isbn, title, author_keys, author, publisher, published_year, page_count, language)
+                    ON CONFLICT (isbn) DO UPDATE SET
+                        title = EXCLUDED.title,
+                        author_keys = EXCLUDED.author_keys,
+                        author = EXCLUDED.author,
+                        publisher = EXCLUDED.publisher,
+                        published_year = EXCLUDED.published_year,
+                        page_count = EXCLUDED.page_count,
+                        language = EXCLUDED.language,
+                        imported_at = CURRENT_TIMESTAMP(3)
+                "#,
+                &records.iter().map(|r| r.isbn.to_string()).collect::<Vec<_>>(),
+                &records.iter().map(|r| r.title.clone()).collect::<Vec<_>>() as _,
+                &records
+                    .iter()
+                    .map(|r| serde_json::to_string(&r.author_keys))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+                &records.iter().map(|r| r.author.clone()).collect::<Vec<_>>() as _,
+                &records.iter().map(|r| r.publisher.clone()).collect::<Vec<_>>() as _,
+                &records.iter().map(|r| r.published_year).collect::<Vec<_>>() as _,
+                &records.iter().map(|r| r.page_count).collect::<Vec<_>>() as _,
+                &records.iter().map(|r| r.language.clone()).collect::<Vec<_>>() as _,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?
+            .rows_affected();
+        }
+
+        let mut imported_authors = 0;
+        if !authors.is_empty() {
+            let (keys, names): (Vec<_>, Vec<_>) = authors.into_iter().unzip();
+            imported_authors = sqlx::query!(
+                r#"
+                    INSERT INTO catalog_authors (author_key, name)
+                    SELECT * FROM UNNEST($1::text[], $2::text[])
+                    ON CONFLICT (author_key) DO UPDATE SET
+                        name = EXCLUDED.name,
+                        imported_at = CURRENT_TIMESTAMP(3)
+                "#,
+                &keys,
+                &names,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?
+            .rows_affected();
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok((imported_records, imported_authors))
+    }
+}
+
+// 受信側が書き込みに失敗して閉じている場合は、読み込みを打ち切る
+fn send(tx: &mpsc::Sender<Vec<CatalogEntry>>, batch: Vec<CatalogEntry>) -> AppResult<()> {
+    if batch.is_empty() {
+        return Ok(());
+    }
+    tx.blocking_send(batch).map_err(|_| {
+        AppError::StorageError(std::io::Error::new(
+            std::io::ErrorKind::BrokenPipe,
+            "書誌データの書き込みが中断されました",
+        ))
+    })
+}
+
+// 出版年として、文字列中に最初に現れる4桁の数字を取り出す
+fn find_year(value: &str) -> Option<i32> {
+    value
+        .as_bytes()
+        .windows(4)
+        .find(|w| w.iter().all(u8::is_ascii_digit))
+        .and_then(|w| std::str::from_utf8(w).ok())
+        .and_then(|w| w.parse().ok())
+}
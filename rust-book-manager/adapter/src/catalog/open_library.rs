@@ -0,0 +1,164 @@
+use kernel::model::book::isbn::Isbn;
+use serde::Deserialize;
+use shared::error::AppResult;
+use std::{io::BufRead, str::FromStr};
+
+use super::{find_year, CatalogEntry, CatalogRecord};
+
+// Open Libraryのダンプファイルを解析し、読み取った順にemitに渡す
+// 各行は「種別 キー リビジョン 更新日時 JSON」のタブ区切りで、
+// 版(/type/edition)の行から書誌情報を、著者(/type/author)の行から著者名を取り出す
+// 版の行には著者のキーしか含まれないため、著者名は取り込んだ後に著者のキーで引く
+pub fn parse(
+    reader: impl BufRead,
+    emit: &mut impl FnMut(CatalogEntry) -> AppResult<()>,
+) -> AppResult<()> {
+    for line in reader.lines() {
+        let line = line?;
+        let mut columns = line.splitn(5, '\t');
+        let (Some(kind), Some(key), Some(_), Some(_), Some(json)) = (
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+            columns.next(),
+        ) else {
+            continue;
+        };
+
+        // 形式が崩れている行は読み飛ばす
+        match kind {
+            "/type/edition" => {
+                if let Ok(edition) = serde_json::from_str::<EditionRecord>(json) {
+                    for record in edition.into_records() {
+                        emit(CatalogEntry::Record(record))?;
+                    }
+                }
+            }
+            "/type/author" => {
+                if let Ok(AuthorRecord { name: Some(name) }) = serde_json::from_str(json) {
+                    emit(CatalogEntry::Author {
+                        key: key.to_string(),
+                        name,
+                    })?;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct EditionRecord {
+    title: Option<String>,
+    subtitle: Option<String>,
+    #[serde(default)]
+    publishers: Vec<String>,
+    publish_date: Option<String>,
+    number_of_pages: Option<i32>,
+    #[serde(default)]
+    languages: Vec<KeyRef>,
+    #[serde(default)]
+    authors: Vec<KeyRef>,
+    by_statement: Option<String>,
+    #[serde(default)]
+    isbn_13: Vec<String>,
+    #[serde(default)]
+    isbn_10: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct KeyRef {
+    key: String,
+}
+
+#[derive(Deserialize)]
+struct AuthorRecord {
+    name: Option<String>,
+}
+
+impl EditionRecord {
+    // 1つの版に複数のISBNがある場合は、それぞれのISBNで引けるようにする
+    fn into_records(self) -> Vec<CatalogRecord> {
+        let title = match (self.title, self.subtitle) {
+            (Some(title), Some(subtitle)) => Some(format!("{title} {subtitle}")),
+            (title, _) => title,
+        };
+        let author_keys = self.authors.into_iter().map(|a| a.key).collect::<Vec<_>>();
+        let publisher = self.publishers.into_iter().next();
+        let published_year = self.publish_date.as_deref().and_then(find_year);
+        // 言語は "/languages/jpn" のようなキーで表されるので、末尾のコードを取り出す
+        let language = self
+            .languages
+            .first()
+            .and_then(|l| l.key.rsplit('/').next())
+            .map(String::from);
+
+        let mut isbns = Vec::new();
+        for isbn in self.isbn_13.iter().chain(self.isbn_10.iter()) {
+            if let Ok(isbn) = Isbn::from_str(isbn) {
+                if !isbns.contains(&isbn) {
+                    isbns.push(isbn);
+                }
+            }
+        }
+
+        isbns
+            .into_iter()
+            .map(|isbn| CatalogRecord {
+                isbn,
+                title: title.clone(),
+                author_keys: author_keys.clone(),
+                author: self.by_statement.clone(),
+                publisher: publisher.clone(),
+                published_year,
+                page_count: self.number_of_pages,
+                language: language.clone(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_open_library_dump() {
+        let dump = [
+            r#"/type/edition	/books/OL1M	3	2024-01-01T00:00:00	{"title": "RustによるWebアプリケーション開発", "subtitle": "設計からリリース・運用まで", "publishers": ["講談社"], "publish_date": "2024年9月", "number_of_pages": 480, "languages": [{"key": "/languages/jpn"}], "authors": [{"key": "/authors/OL1A"}], "isbn_10": ["4065369576"], "isbn_13": ["9784065369579"]}"#,
+            r#"/type/author	/authors/OL1A	1	2024-01-01T00:00:00	{"name": "豊田優貴"}"#,
+            r#"/type/edition	/books/OL2M	1	2024-01-01T00:00:00	{"title": "ISBNのない版"}"#,
+            "壊れた行",
+        ]
+        .join("\n");
+
+        let mut entries = Vec::new();
+        parse(dump.as_bytes(), &mut |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                CatalogEntry::Record(CatalogRecord {
+                    isbn: Isbn::from_str("9784065369579").unwrap(),
+                    title: Some("RustによるWebアプリケーション開発 設計からリリース・運用まで".into()),
+                    author_keys: vec!["/authors/OL1A".into()],
+                    author: None,
+                    publisher: Some("講談社".into()),
+                    published_year: Some(2024),
+                    page_count: Some(480),
+                    language: Some("jpn".into()),
+                }),
+                CatalogEntry::Author {
+                    key: "/authors/OL1A".into(),
+                    name: "豊田優貴".into(),
+                },
+            ]
+        );
+    }
+}
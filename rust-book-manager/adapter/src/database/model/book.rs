@@ -1,42 +1,74 @@
 use chrono::{DateTime, Utc};
 use kernel::model::{
-    book::{Book, Checkout},
-    id::{BookId, CheckoutId, UserId},
+    book::{isbn::Isbn, Book, BookCopy, Checkout, CopyStatus},
+    id::{BookCopyId, BookId, CheckoutId, UserId},
     user::{BookOwner, CheckoutUser},
 };
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
 
 pub struct BookRow {
     pub book_id: BookId,
     pub title: String,
-    pub author: String,
+    pub authors: Vec<String>,
     pub isbn: String,
     pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    pub tags: Vec<String>,
+    pub cover_updated_at: Option<DateTime<Utc>>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub version: i32,
     pub owned_by: UserId,
     pub owner_name: String,
 }
 
 impl BookRow {
-    pub fn into_book(self, checkout: Option<Checkout>) -> Book {
+    pub fn into_book(self, copies: Vec<BookCopy>) -> Book {
         let BookRow {
             book_id,
             title,
-            author,
+            authors,
             isbn,
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            tags,
+            cover_updated_at,
+            archived_at,
+            version,
             owned_by,
             owner_name,
         } = self;
         Book {
             id: book_id,
             title,
-            author,
-            isbn,
+            authors,
+            isbn: Isbn::from_stored(isbn),
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            tags,
+            cover_updated_at,
+            archived_at,
+            version,
             owner: BookOwner {
                 id: owned_by,
                 name: owner_name,
             },
-            checkout,
+            copies,
         }
     }
 }
@@ -46,30 +78,63 @@ pub struct PaginatedBookRow {
     pub id: BookId,
 }
 
-pub struct BookCheckoutRow {
-    pub checkout_id: CheckoutId,
+// カーソル方式の一覧で、ページの境界となる行の並び替えキーを取得する際に使う型
+pub struct BookKeyRow {
+    pub id: BookId,
+    pub title: String,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+// 書誌に属する蔵書を、貸出中であればその貸出とあわせて取得する際に使う型
+// 貸出中でない場合はcheckout_id以降の列がNoneになる
+pub struct BookCopyRow {
+    pub copy_id: BookCopyId,
     pub book_id: BookId,
-    pub user_id: UserId,
-    pub user_name: String,
-    pub checked_out_at: DateTime<Utc>,
+    pub barcode: String,
+    pub status: String,
+    pub checkout_id: Option<CheckoutId>,
+    pub user_id: Option<UserId>,
+    pub user_name: Option<String>,
+    pub checked_out_at: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
 }
 
-impl From<BookCheckoutRow> for Checkout {
-    fn from(value: BookCheckoutRow) -> Self {
-        let BookCheckoutRow {
-            checkout_id,
+impl TryFrom<BookCopyRow> for BookCopy {
+    type Error = AppError;
+
+    fn try_from(value: BookCopyRow) -> AppResult<Self> {
+        let BookCopyRow {
+            copy_id,
             book_id: _,
+            barcode,
+            status,
+            checkout_id,
             user_id,
             user_name,
             checked_out_at,
+            due_at,
         } = value;
-        Checkout {
-            checkout_id,
-            checked_out_by: CheckoutUser {
-                id: user_id,
-                name: user_name,
-            },
-            checked_out_at,
-        }
+        let checkout = match (checkout_id, user_id, user_name, checked_out_at, due_at) {
+            (Some(checkout_id), Some(user_id), Some(user_name), Some(checked_out_at), Some(due_at)) => {
+                Some(Checkout {
+                    checkout_id,
+                    checked_out_by: CheckoutUser {
+                        id: user_id,
+                        name: user_name,
+                    },
+                    checked_out_at,
+                    due_at,
+                })
+            }
+            _ => None,
+        };
+        Ok(BookCopy {
+            id: copy_id,
+            barcode,
+            status: CopyStatus::from_str(status.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            checkout,
+        })
     }
-}
\ No newline at end of file
+}
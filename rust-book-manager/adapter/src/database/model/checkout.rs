@@ -1,8 +1,11 @@
 use kernel::model::{
-    checkout::{Checkout, CheckoutBook},
-    id::{BookId, CheckoutId, UserId},
+    book::CopyStatus,
+    checkout::{Checkout, CheckoutBook, CheckoutIncident, IncidentKind, OverdueCheckout},
+    id::{BookCopyId, BookId, CheckoutId, UserId},
 };
+use shared::error::{AppError, AppResult};
 use sqlx::types::chrono::{DateTime, Utc};
+use std::str::FromStr;
 
 // 貸出状態を確認するための型
 // 藏書が存在する場合はこの型にはまるレコードが存在し、その蔵書が貸し出しの場合は、checkout_idおよびuser_idがNoneではない値になる
@@ -13,12 +16,38 @@ pub struct CheckoutStateRow {
     pub user_id: Option<UserId>,
 }
 
+// 貸出操作の事前チェックで、書誌に属する蔵書の状態を確認するための型
+pub struct CopyStateRow {
+    pub copy_id: BookCopyId,
+    pub status: String,
+    pub checked_out: bool,
+}
+
+impl CopyStateRow {
+    // 紛失・破損しておらず、貸出中でもない蔵書は貸出可能
+    pub fn is_available(&self) -> bool {
+        !self.checked_out && self.status == CopyStatus::Available.as_ref()
+    }
+}
+
+// 延長操作の事前チェックに使う型
+pub struct RenewalStateRow {
+    pub user_id: UserId,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+}
+
 // 貸出中の一覧を取得する際に使う方
 pub struct CheckoutRow {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checkout_processed_by: UserId,
     pub title: String,
     pub author: String,
     pub isbn: String,
@@ -29,8 +58,13 @@ impl From<CheckoutRow> for Checkout {
         let CheckoutRow{
             checkout_id,
             book_id,
+            copy_id,
+            barcode,
             user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
+            checkout_processed_by,
             title,
             author,
             isbn,
@@ -39,10 +73,17 @@ impl From<CheckoutRow> for Checkout {
             id: checkout_id,
             checked_out_by: user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
             // 未返却なので、returuned_atはNoneを入れる
             returned_at: None,
+            checkout_processed_by,
+            return_processed_by: None,
+            incident: None,
             book: CheckoutBook {
                 book_id,
+                copy_id,
+                barcode,
                 title,
                 author,
                 isbn,
@@ -55,38 +96,207 @@ impl From<CheckoutRow> for Checkout {
 pub struct ReturnedCheckoutRow {
     pub checkout_id: CheckoutId,
     pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
     pub user_id: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
     pub returned_at: DateTime<Utc>,
+    pub checkout_processed_by: UserId,
+    pub return_processed_by: UserId,
+    pub incident: Option<String>,
+    pub incident_reported_at: Option<DateTime<Utc>>,
     pub title: String,
     pub author: String,
     pub isbn: String,
 }
 
-impl From<ReturnedCheckoutRow> for Checkout {
-    fn from(value: ReturnedCheckoutRow) -> Self {
+impl TryFrom<ReturnedCheckoutRow> for Checkout {
+    type Error = AppError;
+
+    fn try_from(value: ReturnedCheckoutRow) -> Result<Self, Self::Error> {
         let ReturnedCheckoutRow {
             checkout_id,
             book_id,
+            copy_id,
+            barcode,
             user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
             returned_at,
+            checkout_processed_by,
+            return_processed_by,
+            incident,
+            incident_reported_at,
             title,
             author,
             isbn,
         } = value;
-        Checkout {
+        Ok(Checkout {
             id: checkout_id,
             checked_out_by: user_id,
             checked_out_at,
+            due_at,
+            renewal_count,
             // 返却済みなので、returned_atには日時データが入る
             returned_at: Some(returned_at),
+            checkout_processed_by,
+            return_processed_by: Some(return_processed_by),
+            incident: into_incident(incident, incident_reported_at)?,
             book: CheckoutBook {
                 book_id,
+                copy_id,
+                barcode,
                 title,
                 author,
                 isbn,
             },
+        })
+    }
+}
+
+// 延滞中の貸出一覧を取得する際に使う型
+pub struct OverdueCheckoutRow {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
+    pub user_id: UserId,
+    pub user_name: String,
+    pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub checkout_processed_by: UserId,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    pub days_overdue: i64,
+}
+
+impl From<OverdueCheckoutRow> for OverdueCheckout {
+    fn from(value: OverdueCheckoutRow) -> Self {
+        let OverdueCheckoutRow {
+            checkout_id,
+            book_id,
+            copy_id,
+            barcode,
+            user_id,
+            user_name: _,
+            checked_out_at,
+            due_at,
+            renewal_count,
+            checkout_processed_by,
+            title,
+            author,
+            isbn,
+            days_overdue,
+        } = value;
+        OverdueCheckout {
+            checkout: Checkout {
+                id: checkout_id,
+                checked_out_by: user_id,
+                checked_out_at,
+                due_at,
+                renewal_count,
+                // 延滞中の貸出は未返却なので、returned_atはNoneを入れる
+                returned_at: None,
+                checkout_processed_by,
+                return_processed_by: None,
+                incident: None,
+                book: CheckoutBook {
+                    book_id,
+                    copy_id,
+                    barcode,
+                    title,
+                    author,
+                    isbn,
+                },
+            },
+            days_overdue,
         }
     }
-}
\ No newline at end of file
+}
+
+// ユーザーの貸出履歴を、貸出中・返却済みをあわせてページネーションして取得する際に使う型
+// 貸出中のレコードはreturned_at, return_processed_byがNoneになる
+pub struct CheckoutHistoryRow {
+    pub total: i64,
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
+    pub user_id: UserId,
+    pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
+    pub returned_at: Option<DateTime<Utc>>,
+    pub checkout_processed_by: UserId,
+    pub return_processed_by: Option<UserId>,
+    pub incident: Option<String>,
+    pub incident_reported_at: Option<DateTime<Utc>>,
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+}
+
+impl TryFrom<CheckoutHistoryRow> for Checkout {
+    type Error = AppError;
+
+    fn try_from(value: CheckoutHistoryRow) -> Result<Self, Self::Error> {
+        let CheckoutHistoryRow {
+            total: _,
+            checkout_id,
+            book_id,
+            copy_id,
+            barcode,
+            user_id,
+            checked_out_at,
+            due_at,
+            renewal_count,
+            returned_at,
+            checkout_processed_by,
+            return_processed_by,
+            incident,
+            incident_reported_at,
+            title,
+            author,
+            isbn,
+        } = value;
+        Ok(Checkout {
+            id: checkout_id,
+            checked_out_by: user_id,
+            checked_out_at,
+            due_at,
+            renewal_count,
+            returned_at,
+            checkout_processed_by,
+            return_processed_by,
+            incident: into_incident(incident, incident_reported_at)?,
+            book: CheckoutBook {
+                book_id,
+                copy_id,
+                barcode,
+                title,
+                author,
+                isbn,
+            },
+        })
+    }
+}
+
+// 貸出履歴に記録された紛失・破損の報告をカーネルの型に変換する
+fn into_incident(
+    kind: Option<String>,
+    reported_at: Option<DateTime<Utc>>,
+) -> AppResult<Option<CheckoutIncident>> {
+    match (kind, reported_at) {
+        (Some(kind), Some(reported_at)) => Ok(Some(CheckoutIncident {
+            kind: IncidentKind::from_str(kind.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            reported_at,
+        })),
+        _ => Ok(None),
+    }
+}
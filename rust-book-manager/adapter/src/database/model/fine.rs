@@ -0,0 +1,67 @@
+use kernel::model::{
+    fine::{Fine, FineKind, UserFineBalance},
+    id::{CheckoutId, FineId, UserId},
+};
+use shared::error::AppError;
+use sqlx::types::chrono::{DateTime, Utc};
+use std::str::FromStr;
+
+pub struct FineRow {
+    pub fine_id: FineId,
+    pub user_id: UserId,
+    pub checkout_id: Option<CheckoutId>,
+    pub kind: String,
+    pub amount: i64,
+    pub note: String,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<FineRow> for Fine {
+    type Error = AppError;
+
+    fn try_from(value: FineRow) -> Result<Self, Self::Error> {
+        let FineRow {
+            fine_id,
+            user_id,
+            checkout_id,
+            kind,
+            amount,
+            note,
+            created_by,
+            created_at,
+        } = value;
+        Ok(Fine {
+            id: fine_id,
+            user_id,
+            checkout_id,
+            kind: FineKind::from_str(kind.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+            amount,
+            note,
+            created_by,
+            created_at,
+        })
+    }
+}
+
+pub struct UserFineBalanceRow {
+    pub user_id: UserId,
+    pub user_name: String,
+    pub balance: i64,
+}
+
+impl From<UserFineBalanceRow> for UserFineBalance {
+    fn from(value: UserFineBalanceRow) -> Self {
+        let UserFineBalanceRow {
+            user_id,
+            user_name,
+            balance,
+        } = value;
+        Self {
+            user_id,
+            user_name,
+            balance,
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use kernel::model::list::{PageCursor, SortDirection};
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
+
+// カーソル方式のページネーションで、カーソルが指す境界の行を表す型
+// 並び替えの項目・順序もあわせて保持し、並び順が変わった後に古いカーソルで誤ったページを返さないようにする
+// クライアントには "項目|順序|向き|ID|値" をURLセーフなBase64にした文字列として渡す
+#[derive(Debug, PartialEq, Eq)]
+pub struct KeysetCursor {
+    pub sort_key: String,
+    pub direction: SortDirection,
+    // trueなら境界の行より前のページ、falseなら後ろのページを指す
+    pub backward: bool,
+    pub id: String,
+    pub value: String,
+}
+
+impl KeysetCursor {
+    pub fn encode(&self) -> PageCursor {
+        let raw = format!(
+            "{}|{}|{}|{}|{}",
+            self.sort_key,
+            self.direction.as_ref(),
+            if self.backward { "b" } else { "a" },
+            self.id,
+            self.value
+        );
+        PageCursor::new(URL_SAFE_NO_PAD.encode(raw))
+    }
+
+    pub fn decode(cursor: &PageCursor) -> AppResult<Self> {
+        let invalid = || AppError::UnprocessableEntity("カーソルが不正です。".into());
+
+        let raw = URL_SAFE_NO_PAD
+            .decode(cursor.as_str())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+            .ok_or_else(invalid)?;
+        // 値の部分には区切り文字が含まれうるので、最後の要素として残りをすべて受け取る
+        let mut parts = raw.splitn(5, '|');
+        let (Some(sort_key), Some(direction), Some(backward), Some(id), Some(value)) = (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) else {
+            return Err(invalid());
+        };
+        let backward = match backward {
+            "a" => false,
+            "b" => true,
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self {
+            sort_key: sort_key.into(),
+            direction: SortDirection::from_str(direction).map_err(|_| invalid())?,
+            backward,
+            id: id.into(),
+            value: value.into(),
+        })
+    }
+
+    // カーソルが現在の並び順のために作られたものかを確認する
+    pub fn ensure_sorted_by(&self, sort_key: &str, direction: SortDirection) -> AppResult<()> {
+        if self.sort_key != sort_key || self.direction != direction {
+            return Err(AppError::UnprocessableEntity(
+                "並び順が変わったため、このカーソルは使えません。".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+// カーソル方式で limit + 1 件まで取得した行から、ページに含める行と前後のページのカーソルを求める
+// backwardの場合、rowsは並び順と逆向きに取得されている前提で、並び順に戻してから返す
+// to_cursorには、行と「その行より前のページを指すか」を受け取ってカーソルを作る関数を渡す
+pub fn paginate_by_keyset<R>(
+    mut rows: Vec<R>,
+    limit: i64,
+    from_first: bool,
+    backward: bool,
+    to_cursor: impl Fn(&R, bool) -> PageCursor,
+) -> (Vec<R>, Option<PageCursor>, Option<PageCursor>) {
+    let has_more = rows.len() as i64 > limit;
+    rows.truncate(limit.max(0) as usize);
+    if backward {
+        rows.reverse();
+    }
+
+    let first = rows.first().map(|row| to_cursor(row, true));
+    let last = rows.last().map(|row| to_cursor(row, false));
+    let (next_cursor, prev_cursor) = if backward {
+        (last, first.filter(|_| has_more))
+    } else {
+        (last.filter(|_| has_more), first.filter(|_| !from_first))
+    };
+
+    (rows, next_cursor, prev_cursor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyset_cursor_roundtrip() {
+        let cursor = KeysetCursor {
+            sort_key: "Title".into(),
+            direction: SortDirection::Desc,
+            backward: true,
+            id: "9890736ea4e4461aa77deac3517ef11b".into(),
+            value: "A|B".into(),
+        };
+        assert_eq!(KeysetCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(KeysetCursor::decode(&PageCursor::new("!!!".into())).is_err());
+    }
+}
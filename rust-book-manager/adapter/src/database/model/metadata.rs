@@ -0,0 +1,34 @@
+use kernel::model::{book::isbn::Isbn, metadata::BookMetadata};
+
+pub struct CatalogRecordRow {
+    pub isbn: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub page_count: Option<i32>,
+    pub language: Option<String>,
+}
+
+impl From<CatalogRecordRow> for BookMetadata {
+    fn from(value: CatalogRecordRow) -> Self {
+        let CatalogRecordRow {
+            isbn,
+            title,
+            author,
+            publisher,
+            published_year,
+            page_count,
+            language,
+        } = value;
+        BookMetadata {
+            isbn: Isbn::from_stored(isbn),
+            title,
+            author,
+            publisher,
+            published_year,
+            page_count,
+            language,
+        }
+    }
+}
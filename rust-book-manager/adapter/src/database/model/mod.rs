@@ -2,3 +2,9 @@ pub mod book;
 pub mod auth;
 pub mod user;
 pub mod checkout;
+pub mod reservation;
+pub mod fine;
+pub mod list;
+pub mod tag;
+pub mod revision;
+pub mod metadata;
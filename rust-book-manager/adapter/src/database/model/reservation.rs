@@ -0,0 +1,36 @@
+use kernel::model::{
+    id::{BookId, ReservationId, UserId},
+    reservation::Reservation,
+};
+use sqlx::types::chrono::{DateTime, Utc};
+
+// 予約の一覧を取得する際に使う型
+pub struct ReservationRow {
+    pub reservation_id: ReservationId,
+    pub book_id: BookId,
+    pub user_id: UserId,
+    pub reserved_at: DateTime<Utc>,
+    pub position: i64,
+    pub pickup_expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<ReservationRow> for Reservation {
+    fn from(value: ReservationRow) -> Self {
+        let ReservationRow {
+            reservation_id,
+            book_id,
+            user_id,
+            reserved_at,
+            position,
+            pickup_expires_at,
+        } = value;
+        Self {
+            id: reservation_id,
+            book_id,
+            reserved_by: user_id,
+            reserved_at,
+            position,
+            pickup_expires_at,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    book::isbn::Isbn,
+    id::{BookId, UserId},
+    revision::{BookEditor, BookField, BookRevision, BookSnapshot},
+};
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
+
+// 書誌または版から、版として記録する書誌情報を取得する際に使う型
+pub struct BookSnapshotRow {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub isbn: String,
+    pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+}
+
+impl From<BookSnapshotRow> for BookSnapshot {
+    fn from(value: BookSnapshotRow) -> Self {
+        let BookSnapshotRow {
+            title,
+            authors,
+            isbn,
+            description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+        } = value;
+        BookSnapshot {
+            title,
+            authors,
+            isbn: Isbn::from_stored(isbn),
+            description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+        }
+    }
+}
+
+pub struct BookRevisionRow {
+    pub book_id: BookId,
+    pub revision: i32,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub isbn: String,
+    pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    pub changed_fields: Vec<String>,
+    pub edited_by: Option<UserId>,
+    pub editor_name: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<BookRevisionRow> for BookRevision {
+    type Error = AppError;
+
+    fn try_from(value: BookRevisionRow) -> AppResult<Self> {
+        let BookRevisionRow {
+            book_id,
+            revision,
+            title,
+            authors,
+            isbn,
+            description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            changed_fields,
+            edited_by,
+            editor_name,
+            created_at,
+        } = value;
+        let changed_fields = changed_fields
+            .iter()
+            .map(|field| BookField::from_str(field))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+        Ok(BookRevision {
+            book_id,
+            revision,
+            snapshot: BookSnapshot {
+                title,
+                authors,
+                isbn: Isbn::from_stored(isbn),
+                description,
+                publisher,
+                published_year,
+                language,
+                page_count,
+                edition,
+                subjects,
+            },
+            changed_fields,
+            edited_by: edited_by
+                .zip(editor_name)
+                .map(|(id, name)| BookEditor { id, name }),
+            created_at,
+        })
+    }
+}
@@ -0,0 +1,22 @@
+use kernel::model::{id::TagId, tag::Tag};
+
+pub struct TagRow {
+    pub tag_id: TagId,
+    pub name: String,
+    pub book_count: i64,
+}
+
+impl From<TagRow> for Tag {
+    fn from(value: TagRow) -> Self {
+        let TagRow {
+            tag_id,
+            name,
+            book_count,
+        } = value;
+        Tag {
+            id: tag_id,
+            name,
+            book_count,
+        }
+    }
+}
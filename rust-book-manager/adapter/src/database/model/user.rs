@@ -31,4 +31,34 @@ impl TryFrom<UserRow> for User {
                 .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
         })
     }
-}
\ No newline at end of file
+}
+// ユーザー一覧をオフセット方式で取得する際に、条件に一致する件数もあわせて取得するための型
+pub struct PaginatedUserRow {
+    pub total: i64,
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    pub role_name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl TryFrom<PaginatedUserRow> for User {
+    type Error = AppError;
+
+    fn try_from(value: PaginatedUserRow) -> Result<Self, Self::Error> {
+        let PaginatedUserRow {
+            user_id,
+            name,
+            email,
+            role_name,
+            ..
+        } = value;
+        Ok(User {
+            id: user_id,
+            name,
+            email,
+            role: Role::from_str(role_name.as_str())
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?,
+        })
+    }
+}
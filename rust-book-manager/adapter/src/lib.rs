@@ -1,3 +1,5 @@
+pub mod catalog;
 pub mod database;
 pub mod redis;
 pub mod repository;
+pub mod storage;
@@ -37,7 +37,7 @@ impl AuthRepository for AuthRepositoryImpl {
         let user_item = sqlx::query_as!(
             UserItem,
             r#"
-                SELECT user_id, password_hash FROM users WHERE email = $1;
+                SELECT user_id, password_hash FROM users WHERE lower(email) = lower($1);
             "#,
             email
         )
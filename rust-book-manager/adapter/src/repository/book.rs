@@ -2,21 +2,34 @@ use async_trait::async_trait;
 use derive_new::new;
 use kernel::{
     model::{
-        id::{BookId, UserId},
+        id::{BookCopyId, BookId, CheckoutId, UserId},
         book::{
-            event::{CreateBook, UpdateBook, DeleteBook},
-            Book, BookListOptions, Checkout,
+            event::{
+                ArchiveBook, CreateBook, CreateBookCopy, DeleteBookCopy, RestoreBook, UpdateBook,
+            },
+            display_author, Book, BookCopy, BookListFilter, BookListOptions, BookSort, BookSortKey, CopyStatus,
         },
-        list::PaginatedList,
+        list::{CursorPosition, PageCursor, PaginatedList, SortDirection},
+        revision::{event::RevertBook, BookRevision, BookSnapshot},
     },
     repository::book::BookRepository,
 };
+use chrono::DateTime;
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt, TryStreamExt,
+};
 use shared::error::{AppError, AppResult};
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::database::model::book::{BookRow, PaginatedBookRow, BookCheckoutRow};
+use crate::database::model::book::{BookKeyRow, BookRow, PaginatedBookRow, BookCopyRow};
+use crate::database::model::list::{paginate_by_keyset, KeysetCursor};
+use crate::database::model::revision::{BookRevisionRow, BookSnapshotRow};
 use crate::database::ConnectionPool;
 
+// stream_allで1回のクエリで取得する書誌の件数
+const STREAM_BATCH_SIZE: i64 = 200;
+
 #[derive(new)]
 pub struct BookRepositoryImpl {
     db: ConnectionPool,
@@ -25,94 +38,112 @@ pub struct BookRepositoryImpl {
 #[async_trait]
 impl BookRepository for BookRepositoryImpl {
     async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()> {
-        sqlx::query!(
-            r#"
-                INSERT INTO books (title, author, isbn, description, user_id) VALUES($1, $2, $3, $4, $5)
-            "#,
-            event.title,
-            event.author,
-            event.isbn,
-            event.description,
-            user_id as _
-        )
-        .execute(self.db.inner_ref())
-        .await
-        // sqlx::Error型をAppError型に変換
-        .map_err(AppError::SpecificOperationError)?;
+        // 書誌と最初の1冊の蔵書を同じトランザクションで登録する
+        let mut tx = self.db.begin().await?;
+
+        self.insert_book(&mut tx, event, user_id).await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
 
         Ok(())
     }
 
-    async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>> {
-        let BookListOptions { limit, offset } = options;
+    async fn create_many(&self, events: Vec<(CreateBook, UserId)>) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
 
-        let rows: Vec<PaginatedBookRow> = sqlx::query_as!(
-            PaginatedBookRow,
-            r#"
-                SELECT
-                    COUNT(*) OVER() AS "total!",
-                    b.book_id AS id
-                FROM
-                    books AS b
-                ORDER BY b.created_at DESC
-                LIMIT $1
-                OFFSET $2
-            "#,
-            limit,
-            offset
-        )
-        .fetch_all(self.db.inner_ref())
-        .await
-        .map_err(AppError::SpecificOperationError)?;
+        for (event, user_id) in events {
+            self.insert_book(&mut tx, event, user_id).await?;
+        }
 
-        let total = rows.first().map(|r| r.total).unwrap_or_default();
-        let book_ids = rows.into_iter().map(|r| r.id).collect::<Vec<BookId>>();
+        tx.commit().await.map_err(AppError::TransactionError)?;
 
-        let rows: Vec<BookRow> = sqlx::query_as!(
-            BookRow,
+        Ok(())
+    }
+
+    async fn find_duplicates(&self, event: &CreateBook) -> AppResult<Vec<BookId>> {
+        // タイトル・著者は大文字・小文字や空白、記号の違いを無視して比較する
+        let book_ids = sqlx::query_scalar!(
             r#"
-                SELECT
-                    b.book_id AS book_id,
-                    b.title AS title,
-                    b.author AS author,
-                    b.isbn AS isbn,
-                    b.description AS description,
-                    u.user_id AS owned_by,
-                    u.name AS owner_name
-                FROM
-                    books AS b
-                INNER JOIN
-                    users AS u
-                USING(user_id)
+                SELECT b.book_id AS "book_id: BookId"
+                FROM books AS b
                 WHERE
-                    b.book_id IN (SELECT * FROM UNNEST($1::uuid[]))
-                ORDER BY b.created_at DESC
+                    b.isbn = $1
+                    OR (
+                        normalize_book_text(b.title) = normalize_book_text($2)
+                        AND normalize_book_text(b.author) = normalize_book_text($3)
+                    )
+                ORDER BY b.created_at
             "#,
-            &book_ids as _
+            event.isbn.as_str(),
+            event.title,
+            display_author(&event.authors)
         )
         .fetch_all(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?;
 
-        let book_ids = rows.iter().map(|book| book.book_id).collect::<Vec<_>>();
-        let mut checkouts = self.find_checkouts(&book_ids).await?;
+        Ok(book_ids)
+    }
 
-        let items = rows
-            .into_iter()
-            .map(|row| {
-                let checkout = checkouts.remove(&row.book_id);
-                row.into_book(checkout)
-            })
-            .collect();
+    async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>> {
+        let BookListOptions {
+            limit,
+            offset,
+            cursor,
+            search,
+            filter,
+            sort,
+        } = options;
+
+        // カーソル方式では件数を数えず、offsetも使わない
+        let (book_ids, total, offset, next_cursor, prev_cursor) = match cursor {
+            None => {
+                let (book_ids, total) = self
+                    .find_ids_by_offset(limit, offset, search, filter, sort)
+                    .await?;
+                (book_ids, Some(total), offset, None, None)
+            }
+            Some(position) => {
+                let (book_ids, next_cursor, prev_cursor) = self
+                    .find_ids_by_cursor(limit, position, search, filter, sort)
+                    .await?;
+                (book_ids, None, 0, next_cursor, prev_cursor)
+            }
+        };
+
+        let items = self.find_by_ids(&book_ids).await?;
 
         Ok(PaginatedList {
             total,
             limit,
             offset,
             items,
+            next_cursor,
+            prev_cursor,
         })
     }
 
+    fn stream_all(&self) -> BoxStream<'static, AppResult<Book>> {
+        // 書誌IDの順にSTREAM_BATCH_SIZE件ずつ取得し、前回の最後の書誌IDより後ろから続きを取得する
+        let repository = BookRepositoryImpl::new(self.db.clone());
+        stream::try_unfold(
+            (repository, None::<BookId>),
+            |(repository, last_book_id)| async move {
+                let book_ids = repository.find_ids_after(last_book_id).await?;
+                let Some(&last_book_id) = book_ids.last() else {
+                    return Ok(None);
+                };
+                let books = repository.find_by_ids(&book_ids).await?;
+                Ok::<_, AppError>(Some((
+                    stream::iter(books.into_iter().map(Ok)),
+                    (repository, Some(last_book_id)),
+                )))
+            },
+        )
+        .try_flatten()
+        .boxed()
+    }
+
     async fn find_by_id(&self, book_id: BookId) -> AppResult<Option<Book>> {
         let row: Option<BookRow> = sqlx::query_as!(
             BookRow,
@@ -120,16 +151,35 @@ impl BookRepository for BookRepositoryImpl {
                 SELECT
                     b.book_id AS book_id,
                     b.title AS title,
-                    b.author AS author,
-                    b.isbn AS isbn,
+                    b.authors AS authors,
+                    b.isbn,
                     b.description AS description,
+                    b.publisher AS publisher,
+                    b.published_year AS published_year,
+                    b.language AS language,
+                    b.page_count AS page_count,
+                    b.edition AS edition,
+                    b.subjects AS subjects,
+                    ARRAY(
+                        SELECT t.name
+                        FROM book_tags AS bt
+                        INNER JOIN tags AS t USING(tag_id)
+                        WHERE bt.book_id = b.book_id
+                        ORDER BY lower(t.name)
+                    ) AS "tags!",
+                    cv.updated_at AS "cover_updated_at?",
+                    b.archived_at AS archived_at,
+                    b.version AS version,
                     u.user_id AS owned_by,
                     u.name AS owner_name
                 FROM
                     books AS b
                 INNER JOIN
                     users AS u
-                USING (user_id)
+                ON u.user_id = b.user_id
+                LEFT OUTER JOIN
+                    book_covers AS cv
+                ON cv.book_id = b.book_id
                 WHERE b.book_id = $1
             "#,
             book_id as _ // query_as!マクロによるコンパイル時の型チェックを無効化
@@ -140,99 +190,1002 @@ impl BookRepository for BookRepositoryImpl {
 
         match row {
             Some(r) => {
-                let checkout = self
-                    .find_checkouts(&[r.book_id])
+                let copies = self
+                    .find_copies(&[r.book_id])
                     .await?
-                    .remove(&r.book_id);
-                Ok(Some(r.into_book(checkout)))
+                    .remove(&r.book_id)
+                    .unwrap_or_default();
+                Ok(Some(r.into_book(copies)))
             }
             None => Ok(None)
         }
     }
 
     async fn update(&self, event: UpdateBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
         let res = sqlx::query!(
             r#"
                 UPDATE books
                 SET
                     title = $1,
                     author = $2,
-                    isbn = $3,
-                    description = $4
-                WHERE book_id = $5
-                AND user_id = $6
+                    authors = $3,
+                    isbn = $4,
+                    description = $5,
+                    publisher = $6,
+                    published_year = $7,
+                    language = $8,
+                    page_count = $9,
+                    edition = $10,
+                    subjects = $11,
+                    version = version + 1
+                WHERE book_id = $12
+                AND user_id = $13
+                AND version = $14
+                AND archived_at IS NULL
             "#,
             event.title,
-            event.author,
-            event.isbn,
+            display_author(&event.authors),
+            &event.authors,
+            event.isbn.as_str(),
             event.description,
+            event.publisher,
+            event.published_year,
+            event.language,
+            event.page_count,
+            event.edition,
+            &event.subjects,
             event.book_id as _,
             event.requested_user as _,
+            event.expected_version,
         )
-        .execute(self.db.inner_ref())
+        .execute(&mut *tx)
         .await
         .map_err(AppError::SpecificOperationError)?;
 
+        // 更新できなかった場合は、書誌がないのか、アーカイブされているのか、ほかの更新で版番号が変わったのかを調べる
         if res.rows_affected() < 1 {
+            let book = sqlx::query!(
+                r#"
+                    SELECT version, archived_at IS NOT NULL AS "archived!"
+                    FROM books WHERE book_id = $1 AND user_id = $2
+                "#,
+                event.book_id as _,
+                event.requested_user as _,
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            return Err(match book {
+                Some(book) if book.archived => AppError::UnprocessableEntity(format!(
+                    "書籍({})はアーカイブされているため更新できません。",
+                    event.book_id
+                )),
+                Some(book) => AppError::PreconditionFailed(format!(
+                    "書籍({})は更新されています(版番号: {})。",
+                    event.book_id, book.version
+                )),
+                None => AppError::EntityNotFound("specified book not found".into()),
+            });
+        }
+
+        self.record_revision(&mut tx, event.book_id, event.requested_user)
+            .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn find_revisions(&self, book_id: BookId) -> AppResult<Vec<BookRevision>> {
+        let rows: Vec<BookRevisionRow> = sqlx::query_as!(
+            BookRevisionRow,
+            r#"
+                SELECT
+                    r.book_id AS "book_id: BookId",
+                    r.revision,
+                    r.title,
+                    r.authors,
+                    r.isbn,
+                    r.description,
+                    r.publisher,
+                    r.published_year,
+                    r.language,
+                    r.page_count,
+                    r.edition,
+                    r.subjects,
+                    r.changed_fields,
+                    u.user_id AS "edited_by?: UserId",
+                    u.name AS "editor_name?",
+                    r.created_at
+                FROM
+                    book_revisions AS r
+                LEFT OUTER JOIN
+                    users AS u
+                ON u.user_id = r.edited_by
+                WHERE r.book_id = $1
+                ORDER BY r.revision DESC
+            "#,
+            book_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        // 書誌には登録時の版が必ずあるため、版がない場合は書誌が存在しない
+        if rows.is_empty() {
             return Err(AppError::EntityNotFound(
                 "specified book not found".into(),
             ));
         }
 
-        Ok(())
+        rows.into_iter().map(BookRevision::try_from).collect()
     }
 
-    async fn delete(&self, event: DeleteBook) -> AppResult<()> {
+    async fn revert(&self, event: RevertBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let snapshot: BookSnapshot = sqlx::query_as!(
+            BookSnapshotRow,
+            r#"
+                SELECT
+                    title,
+                    authors,
+                    isbn,
+                    description,
+                    publisher,
+                    published_year,
+                    language,
+                    page_count,
+                    edition,
+                    subjects
+                FROM book_revisions
+                WHERE book_id = $1 AND revision = $2
+            "#,
+            event.book_id as _,
+            event.revision
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound("specified revision not found".into()))?
+        .into();
+
         let res = sqlx::query!(
             r#"
-                DELETE FROM books WHERE book_id = $1 AND user_id = $2
+                UPDATE books
+                SET
+                    title = $1,
+                    author = $2,
+                    authors = $3,
+                    isbn = $4,
+                    description = $5,
+                    publisher = $6,
+                    published_year = $7,
+                    language = $8,
+                    page_count = $9,
+                    edition = $10,
+                    subjects = $11,
+                    version = version + 1
+                WHERE book_id = $12
+                AND version = $13
+                AND archived_at IS NULL
+            "#,
+            snapshot.title,
+            display_author(&snapshot.authors),
+            &snapshot.authors,
+            snapshot.isbn.as_str(),
+            snapshot.description,
+            snapshot.publisher,
+            snapshot.published_year,
+            snapshot.language,
+            snapshot.page_count,
+            snapshot.edition,
+            &snapshot.subjects,
+            event.book_id as _,
+            event.expected_version,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        // 戻せなかった場合は、アーカイブされているのか、ほかの更新で版番号が変わったのかを調べる
+        if res.rows_affected() < 1 {
+            let book = sqlx::query!(
+                r#"
+                    SELECT version, archived_at IS NOT NULL AS "archived!"
+                    FROM books WHERE book_id = $1
+                "#,
+                event.book_id as _,
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            return Err(match book {
+                Some(book) if book.archived => AppError::UnprocessableEntity(format!(
+                    "書籍({})はアーカイブされているため、以前の版に戻せません。",
+                    event.book_id
+                )),
+                Some(book) => AppError::PreconditionFailed(format!(
+                    "書籍({})は更新されています(版番号: {})。",
+                    event.book_id, book.version
+                )),
+                None => AppError::EntityNotFound("specified book not found".into()),
+            });
+        }
+
+        self.record_revision(&mut tx, event.book_id, event.requested_user)
+            .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn archive(&self, event: ArchiveBook) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 貸出の処理と同時に行われても、貸出中の蔵書がある書誌をアーカイブしないようにする
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+        let checked_out = sqlx::query_scalar!(
+            r#"
+                SELECT
+                    EXISTS (SELECT 1 FROM checkouts AS c WHERE c.book_id = b.book_id) AS "checked_out!"
+                FROM
+                    books AS b
+                WHERE
+                    b.book_id = $1
+                    AND b.user_id = $2
+                    AND b.archived_at IS NULL
             "#,
             event.book_id as _,
             event.requested_user as _
         )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound("specified book not found".into()))?;
+
+        if checked_out {
+            return Err(AppError::UnprocessableEntity(format!(
+                "書籍({})は貸出中の蔵書があるためアーカイブできません。",
+                event.book_id
+            )));
+        }
+
+        // アーカイブした書誌は貸出できず、予約を待っても借りられないため予約を取り消す
+        sqlx::query!(
+            r#"
+                DELETE FROM reservations WHERE book_id = $1
+            "#,
+            event.book_id as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        sqlx::query!(
+            r#"
+                UPDATE books
+                SET archived_at = CURRENT_TIMESTAMP(3), archived_by = $2
+                WHERE book_id = $1
+            "#,
+            event.book_id as _,
+            event.requested_user as _
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn restore(&self, event: RestoreBook) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE books
+                SET archived_at = NULL, archived_by = NULL
+                WHERE book_id = $1 AND archived_at IS NOT NULL
+            "#,
+            event.book_id as _
+        )
         .execute(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?;
 
         if res.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(
+                "specified archived book not found".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn create_copy(&self, event: CreateBookCopy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 蔵書を追加できるのは書誌を登録したユーザーのみ
+        let exists = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM books WHERE book_id = $1 AND user_id = $2
+                ) AS "exists!"
+            "#,
+            event.book_id as _,
+            event.requested_user as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if !exists {
             return Err(AppError::EntityNotFound(
                 "specified book not found".into(),
             ));
         }
 
+        self.insert_copy(&mut tx, event.book_id, event.barcode).await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn delete_copy(&self, event: DeleteBookCopy) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // 貸出や予約の処理と同時に行われても、貸出中や取り置き中の蔵書を削除しないようにする
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+        let copy = sqlx::query!(
+            r#"
+                SELECT
+                    bc.status,
+                    EXISTS (
+                        SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id
+                    ) AS "checked_out!",
+                    EXISTS (
+                        SELECT 1 FROM returned_checkouts AS rc WHERE rc.copy_id = bc.copy_id
+                    ) AS "returned!"
+                FROM book_copies AS bc
+                INNER JOIN books AS b USING(book_id)
+                WHERE
+                    bc.copy_id = $1
+                    AND bc.book_id = $2
+                    AND b.user_id = $3
+            "#,
+            event.copy_id as _,
+            event.book_id as _,
+            event.requested_user as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound("specified book copy not found".into()))?;
+
+        // 貸出中の蔵書は削除できない
+        if copy.checked_out {
+            return Err(AppError::UnprocessableEntity(format!(
+                "蔵書({})は貸出中のため削除できません。",
+                event.copy_id
+            )));
+        }
+
+        // 貸出履歴は蔵書を参照して表示するため、履歴のある蔵書は削除せず、紛失・破損として扱う
+        if copy.returned {
+            return Err(AppError::UnprocessableEntity(format!(
+                "蔵書({})には貸出履歴があるため削除できません。",
+                event.copy_id
+            )));
+        }
+
+        // 取り置き中の予約の冊数だけ、貸出可能な蔵書を残しておく
+        if copy.status == CopyStatus::Available.as_ref() {
+            let held = sqlx::query!(
+                r#"
+                    SELECT
+                        (
+                            SELECT COUNT(*) FROM reservations
+                            WHERE book_id = $1 AND pickup_expires_at IS NOT NULL
+                        ) AS "ready!",
+                        (
+                            SELECT COUNT(*) FROM book_copies AS bc
+                            WHERE
+                                bc.book_id = $1
+                                AND bc.copy_id <> $2
+                                AND bc.status = $3
+                                AND NOT EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id)
+                        ) AS "remaining!"
+                "#,
+                event.book_id as _,
+                event.copy_id as _,
+                CopyStatus::Available.as_ref(),
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            if held.remaining < held.ready {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})には取り置き中の予約があるため、蔵書({})を削除できません。",
+                    event.book_id, event.copy_id
+                )));
+            }
+        }
+
+        sqlx::query!(
+            r#"
+                DELETE FROM book_copies WHERE copy_id = $1
+            "#,
+            event.copy_id as _,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
         Ok(())
     }
 }
 
 impl BookRepositoryImpl {
-    async fn find_checkouts(&self, book_ids: &[BookId]) -> AppResult<HashMap<BookId, Checkout>> {
-        let res = sqlx::query_as!(
-            BookCheckoutRow,
+    // 書誌と最初の1冊の蔵書を登録する
+    async fn insert_book(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        event: CreateBook,
+        user_id: UserId,
+    ) -> AppResult<()> {
+        let book_id = sqlx::query_scalar!(
+            r#"
+                INSERT INTO books (
+                    title, author, authors, isbn, description,
+                    publisher, published_year, language, page_count, edition, subjects,
+                    user_id
+                )
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                RETURNING book_id AS "book_id: BookId"
+            "#,
+            event.title,
+            display_author(&event.authors),
+            &event.authors,
+            event.isbn.as_str(),
+            event.description,
+            event.publisher,
+            event.published_year,
+            event.language,
+            event.page_count,
+            event.edition,
+            &event.subjects,
+            user_id as _
+        )
+        .fetch_one(&mut **tx)
+        .await
+        // sqlx::Error型をAppError型に変換
+        .map_err(AppError::SpecificOperationError)?;
+
+        self.record_revision(tx, book_id, user_id).await?;
+        self.insert_copy(tx, book_id, event.barcode).await
+    }
+
+    // 書誌の現在の内容を、直前の版から変更があれば新しい版として記録する
+    // 書誌の行を更新した後に呼び出すことで、同じ書誌の版の記録が同時に行われないようにする
+    async fn record_revision(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        book_id: BookId,
+        edited_by: UserId,
+    ) -> AppResult<()> {
+        let current: BookSnapshot = sqlx::query_as!(
+            BookSnapshotRow,
+            r#"
+                SELECT
+                    title,
+                    authors,
+                    isbn,
+                    description,
+                    publisher,
+                    published_year,
+                    language,
+                    page_count,
+                    edition,
+                    subjects
+                FROM books
+                WHERE book_id = $1
+            "#,
+            book_id as _
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into();
+
+        let previous: Option<BookRevision> = sqlx::query_as!(
+            BookRevisionRow,
             r#"
                 SELECT
-                    c.checkout_id,
-                    c.book_id,
-                    u.user_id,
-                    u.name AS user_name,
-                    c.checked_out_at
+                    r.book_id AS "book_id: BookId",
+                    r.revision,
+                    r.title,
+                    r.authors,
+                    r.isbn,
+                    r.description,
+                    r.publisher,
+                    r.published_year,
+                    r.language,
+                    r.page_count,
+                    r.edition,
+                    r.subjects,
+                    r.changed_fields,
+                    u.user_id AS "edited_by?: UserId",
+                    u.name AS "editor_name?",
+                    r.created_at
                 FROM
-                    checkouts AS c
+                    book_revisions AS r
+                LEFT OUTER JOIN
+                    users AS u
+                ON u.user_id = r.edited_by
+                WHERE r.book_id = $1
+                ORDER BY r.revision DESC
+                LIMIT 1
+            "#,
+            book_id as _
+        )
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .map(BookRevision::try_from)
+        .transpose()?;
+
+        let changed_fields = current.changed_fields(previous.as_ref().map(|r| &r.snapshot));
+        if previous.is_some() && changed_fields.is_empty() {
+            return Ok(());
+        }
+        let revision = previous.map_or(1, |r| r.revision + 1);
+        let changed_fields = changed_fields
+            .iter()
+            .map(|field| field.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        sqlx::query!(
+            r#"
+                INSERT INTO book_revisions (
+                    book_id, revision, title, authors, isbn, description, publisher,
+                    published_year, language, page_count, edition, subjects,
+                    changed_fields, edited_by
+                )
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#,
+            book_id as _,
+            revision,
+            current.title,
+            &current.authors,
+            current.isbn.as_str(),
+            current.description,
+            current.publisher,
+            current.published_year,
+            current.language,
+            current.page_count,
+            current.edition,
+            &current.subjects,
+            &changed_fields,
+            edited_by as _
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(())
+    }
+
+    // オフセット方式で、一覧のページに含まれる書誌IDと条件に一致する件数を取得する
+    async fn find_ids_by_offset(
+        &self,
+        limit: i64,
+        offset: i64,
+        search: Option<String>,
+        filter: BookListFilter,
+        sort: Option<BookSort>,
+    ) -> AppResult<(Vec<BookId>, i64)> {
+        let BookListFilter {
+            author,
+            owner,
+            tag,
+            available,
+            created_from,
+            created_to,
+        } = filter;
+        let sort_key = sort.map(|s| s.key.as_ref().to_string());
+        let sort_direction = sort.map(|s| s.direction.as_ref().to_string());
+
+        // 並び替えの指定がない場合、検索語があれば関連度(ts_rank)の高い順に、
+        // なければ登録日時の新しい順に返す
+        // 並び替えの項目と順序は列挙型で受け取るため、ここで任意の値が渡ることはない
+        let rows: Vec<PaginatedBookRow> = sqlx::query_as!(
+            PaginatedBookRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() AS "total!",
+                    b.book_id AS id
+                FROM
+                    books AS b
+                WHERE
+                    b.archived_at IS NULL
+                    AND ($3::text IS NULL OR b.search_vector @@ websearch_to_tsquery('simple', $3))
+                    AND ($4::text IS NULL OR strpos(lower(b.author), lower($4)) > 0)
+                    AND ($5::uuid IS NULL OR b.user_id = $5)
+                    AND ($6::bool IS NULL OR $6 = EXISTS (
+                        SELECT 1
+                        FROM book_copies AS bc
+                        LEFT OUTER JOIN checkouts AS c ON c.copy_id = bc.copy_id
+                        WHERE bc.book_id = b.book_id
+                            AND bc.status = $9
+                            AND c.checkout_id IS NULL
+                    ))
+                    AND ($7::timestamptz IS NULL OR b.created_at >= $7)
+                    AND ($8::timestamptz IS NULL OR b.created_at <= $8)
+                    AND ($12::text IS NULL OR EXISTS (
+                        SELECT 1
+                        FROM book_tags AS bt
+                        INNER JOIN tags AS t USING(tag_id)
+                        WHERE bt.book_id = b.book_id AND lower(t.name) = lower($12)
+                    ))
+                ORDER BY
+                    CASE WHEN $10::text = 'Title' AND $11::text = 'Asc' THEN b.title END ASC,
+                    CASE WHEN $10::text = 'Title' AND $11::text = 'Desc' THEN b.title END DESC,
+                    CASE WHEN $10::text = 'Author' AND $11::text = 'Asc' THEN b.author END ASC,
+                    CASE WHEN $10::text = 'Author' AND $11::text = 'Desc' THEN b.author END DESC,
+                    CASE WHEN $10::text = 'CreatedAt' AND $11::text = 'Asc' THEN b.created_at END ASC,
+                    CASE
+                        WHEN $10::text IS NULL AND $3::text IS NOT NULL
+                        THEN ts_rank(b.search_vector, websearch_to_tsquery('simple', $3))
+                    END DESC,
+                    b.created_at DESC,
+                    b.book_id
+                LIMIT $1
+                OFFSET $2
+            "#,
+            limit,
+            offset,
+            search,
+            author,
+            owner as _,
+            available,
+            created_from,
+            created_to,
+            CopyStatus::Available.as_ref(),
+            sort_key,
+            sort_direction,
+            tag
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let book_ids = rows.into_iter().map(|r| r.id).collect();
+
+        Ok((book_ids, total))
+    }
+
+    // カーソル方式で、一覧のページに含まれる書誌IDと前後のページのカーソルを取得する
+    // OFFSETで読み飛ばさず、カーソルが指す行の並び替えキーと書誌IDの組より後ろ(前)を直接取得する
+    async fn find_ids_by_cursor(
+        &self,
+        limit: i64,
+        position: CursorPosition,
+        search: Option<String>,
+        filter: BookListFilter,
+        sort: Option<BookSort>,
+    ) -> AppResult<(Vec<BookId>, Option<PageCursor>, Option<PageCursor>)> {
+        let BookListFilter {
+            author,
+            owner,
+            tag,
+            available,
+            created_from,
+            created_to,
+        } = filter;
+        let BookSort { key, direction } = sort.unwrap_or(BookSort {
+            key: BookSortKey::CreatedAt,
+            direction: SortDirection::Desc,
+        });
+
+        let cursor = match &position {
+            CursorPosition::First => None,
+            CursorPosition::At(cursor) => {
+                let cursor = KeysetCursor::decode(cursor)?;
+                cursor.ensure_sorted_by(key.as_ref(), direction)?;
+                Some(cursor)
+            }
+        };
+        let backward = cursor.as_ref().is_some_and(|c| c.backward);
+        // 前のページを取得する場合は並び順と逆向きに読み、後で並び順に戻す
+        let ascending = (direction == SortDirection::Asc) != backward;
+        let cursor_id = cursor
+            .as_ref()
+            .map(|c| BookId::from_str(&c.id))
+            .transpose()?;
+        let cursor_text = cursor.as_ref().map(|c| c.value.clone());
+        let cursor_created_at = match (&cursor, key) {
+            (Some(c), BookSortKey::CreatedAt) => Some(
+                c.value
+                    .parse::<i64>()
+                    .ok()
+                    .and_then(DateTime::from_timestamp_micros)
+                    .ok_or_else(|| AppError::UnprocessableEntity("カーソルが不正です。".into()))?,
+            ),
+            _ => None,
+        };
+
+        let rows: Vec<BookKeyRow> = sqlx::query_as!(
+            BookKeyRow,
+            r#"
+                SELECT
+                    b.book_id AS id,
+                    b.title,
+                    b.author,
+                    b.created_at
+                FROM
+                    books AS b
+                WHERE
+                    b.archived_at IS NULL
+                    AND ($2::text IS NULL OR b.search_vector @@ websearch_to_tsquery('simple', $2))
+                    AND ($3::text IS NULL OR strpos(lower(b.author), lower($3)) > 0)
+                    AND ($4::uuid IS NULL OR b.user_id = $4)
+                    AND ($5::bool IS NULL OR $5 = EXISTS (
+                        SELECT 1
+                        FROM book_copies AS bc
+                        LEFT OUTER JOIN checkouts AS c ON c.copy_id = bc.copy_id
+                        WHERE bc.book_id = b.book_id
+                            AND bc.status = $8
+                            AND c.checkout_id IS NULL
+                    ))
+                    AND ($6::timestamptz IS NULL OR b.created_at >= $6)
+                    AND ($7::timestamptz IS NULL OR b.created_at <= $7)
+                    AND ($14::text IS NULL OR EXISTS (
+                        SELECT 1
+                        FROM book_tags AS bt
+                        INNER JOIN tags AS t USING(tag_id)
+                        WHERE bt.book_id = b.book_id AND lower(t.name) = lower($14)
+                    ))
+                    AND ($11::uuid IS NULL OR CASE $9::text
+                        WHEN 'Title' THEN CASE WHEN $10::bool
+                            THEN (b.title, b.book_id) > ($12::text, $11)
+                            ELSE (b.title, b.book_id) < ($12::text, $11) END
+                        WHEN 'Author' THEN CASE WHEN $10::bool
+                            THEN (b.author, b.book_id) > ($12::text, $11)
+                            ELSE (b.author, b.book_id) < ($12::text, $11) END
+                        ELSE CASE WHEN $10::bool
+                            THEN (b.created_at, b.book_id) > ($13::timestamptz, $11)
+                            ELSE (b.created_at, b.book_id) < ($13::timestamptz, $11) END
+                    END)
+                ORDER BY
+                    CASE WHEN $9::text = 'Title' AND $10::bool THEN b.title END ASC,
+                    CASE WHEN $9::text = 'Title' AND NOT $10::bool THEN b.title END DESC,
+                    CASE WHEN $9::text = 'Author' AND $10::bool THEN b.author END ASC,
+                    CASE WHEN $9::text = 'Author' AND NOT $10::bool THEN b.author END DESC,
+                    CASE WHEN $9::text = 'CreatedAt' AND $10::bool THEN b.created_at END ASC,
+                    CASE WHEN $9::text = 'CreatedAt' AND NOT $10::bool THEN b.created_at END DESC,
+                    CASE WHEN $10::bool THEN b.book_id END ASC,
+                    CASE WHEN NOT $10::bool THEN b.book_id END DESC
+                LIMIT $1
+            "#,
+            limit + 1,
+            search,
+            author,
+            owner as _,
+            available,
+            created_from,
+            created_to,
+            CopyStatus::Available.as_ref(),
+            key.as_ref(),
+            ascending,
+            cursor_id as _,
+            cursor_text,
+            cursor_created_at,
+            tag
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let (rows, next_cursor, prev_cursor) = paginate_by_keyset(
+            rows,
+            limit,
+            position == CursorPosition::First,
+            backward,
+            |row, backward| {
+                KeysetCursor {
+                    sort_key: key.as_ref().into(),
+                    direction,
+                    backward,
+                    id: row.id.to_string(),
+                    value: match key {
+                        BookSortKey::Title => row.title.clone(),
+                        BookSortKey::Author => row.author.clone(),
+                        BookSortKey::CreatedAt => row.created_at.timestamp_micros().to_string(),
+                    },
+                }
+                .encode()
+            },
+        );
+
+        Ok((
+            rows.into_iter().map(|row| row.id).collect(),
+            next_cursor,
+            prev_cursor,
+        ))
+    }
+
+    // stream_allで使う、指定した書誌IDより後ろの書誌IDを書誌IDの順に取得する
+    async fn find_ids_after(&self, last_book_id: Option<BookId>) -> AppResult<Vec<BookId>> {
+        let rows = sqlx::query_scalar!(
+            r#"
+                SELECT b.book_id AS "book_id: BookId"
+                FROM books AS b
+                WHERE b.archived_at IS NULL AND ($1::uuid IS NULL OR b.book_id > $1)
+                ORDER BY b.book_id ASC
+                LIMIT $2
+            "#,
+            last_book_id as _,
+            STREAM_BATCH_SIZE
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(rows)
+    }
+
+    // 指定したIDの書誌を、指定した順に蔵書とあわせて取得する
+    async fn find_by_ids(&self, book_ids: &[BookId]) -> AppResult<Vec<Book>> {
+        let rows: Vec<BookRow> = sqlx::query_as!(
+            BookRow,
+            r#"
+                SELECT
+                    b.book_id AS book_id,
+                    b.title AS title,
+                    b.authors AS authors,
+                    b.isbn,
+                    b.description AS description,
+                    b.publisher AS publisher,
+                    b.published_year AS published_year,
+                    b.language AS language,
+                    b.page_count AS page_count,
+                    b.edition AS edition,
+                    b.subjects AS subjects,
+                    ARRAY(
+                        SELECT t.name
+                        FROM book_tags AS bt
+                        INNER JOIN tags AS t USING(tag_id)
+                        WHERE bt.book_id = b.book_id
+                        ORDER BY lower(t.name)
+                    ) AS "tags!",
+                    cv.updated_at AS "cover_updated_at?",
+                    b.archived_at AS archived_at,
+                    b.version AS version,
+                    u.user_id AS owned_by,
+                    u.name AS owner_name
+                FROM
+                    books AS b
                 INNER JOIN
                     users AS u
-                USING (user_id)
+                ON u.user_id = b.user_id
+                LEFT OUTER JOIN
+                    book_covers AS cv
+                ON cv.book_id = b.book_id
                 WHERE
-                    book_id = ANY($1);
+                    b.book_id IN (SELECT * FROM UNNEST($1::uuid[]))
+                ORDER BY array_position($1::uuid[], b.book_id)
             "#,
             book_ids as _
         )
         .fetch_all(self.db.inner_ref())
         .await
-        .map_err(AppError::SpecificOperationError)?
-        .into_iter()
-        .map(|checkout| (checkout.book_id, Checkout::from(checkout)))
-        .collect();
+        .map_err(AppError::SpecificOperationError)?;
+
+        let book_ids = rows.iter().map(|book| book.book_id).collect::<Vec<_>>();
+        let mut copies = self.find_copies(&book_ids).await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let copies = copies.remove(&row.book_id).unwrap_or_default();
+                row.into_book(copies)
+            })
+            .collect())
+    }
+
+    // 書誌ごとの蔵書を、貸出中であればその貸出とあわせて取得する
+    async fn find_copies(&self, book_ids: &[BookId]) -> AppResult<HashMap<BookId, Vec<BookCopy>>> {
+        let rows = sqlx::query_as!(
+            BookCopyRow,
+            r#"
+                SELECT
+                    bc.copy_id,
+                    bc.book_id,
+                    bc.barcode,
+                    bc.status,
+                    c.checkout_id AS "checkout_id?: CheckoutId",
+                    u.user_id AS "user_id?: UserId",
+                    u.name AS "user_name?",
+                    c.checked_out_at AS "checked_out_at?",
+                    c.due_at AS "due_at?"
+                FROM
+                    book_copies AS bc
+                LEFT OUTER JOIN
+                    checkouts AS c
+                ON c.copy_id = bc.copy_id
+                LEFT OUTER JOIN
+                    users AS u
+                ON u.user_id = c.user_id
+                WHERE
+                    bc.book_id = ANY($1)
+                ORDER BY bc.created_at ASC, bc.barcode ASC;
+            "#,
+            book_ids as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let mut copies: HashMap<BookId, Vec<BookCopy>> = HashMap::new();
+        for row in rows {
+            let book_id = row.book_id;
+            copies
+                .entry(book_id)
+                .or_default()
+                .push(BookCopy::try_from(row)?);
+        }
+
+        Ok(copies)
+    }
+
+    // create, create_copyで蔵書を登録するために内部的に使うメソッド
+    // バーコードの指定がない場合は、蔵書IDをそのままバーコードとする
+    async fn insert_copy(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        book_id: BookId,
+        barcode: Option<String>,
+    ) -> AppResult<()> {
+        let copy_id = BookCopyId::new();
+        let barcode = barcode.unwrap_or_else(|| copy_id.to_string());
+
+        sqlx::query!(
+            r#"
+                INSERT INTO book_copies (copy_id, book_id, barcode) VALUES ($1, $2, $3)
+            "#,
+            copy_id as _,
+            book_id as _,
+            barcode,
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            // バーコードの重複はクライアントのリクエストの誤りとして扱う
+            Some(db_error) if db_error.is_unique_violation() => AppError::UnprocessableEntity(
+                format!("バーコード({})はすでに使われています。", barcode),
+            ),
+            _ => AppError::SpecificOperationError(e),
+        })?;
 
-        Ok(res)
+        Ok(())
     }
 }
 
@@ -247,8 +1200,14 @@ mod tests {
     use chrono::Utc;
     use kernel::{
         model::{
-            checkout::event::{CreateCheckout, UpdateReturned},
+            book::isbn::Isbn,
+            checkout::{
+                event::{CreateCheckout, UpdateReturned},
+                LoanPolicy,
+            },
+            fine::FinePolicy,
             id::UserId,
+            revision::BookField,
             user::event::CreateUser,
         },
         repository::{checkout::CheckoutRepository, user::UserRepository},
@@ -273,9 +1232,16 @@ mod tests {
         // 投入するための蔵書データを作成
         let book = CreateBook {
             title: "Test Title".into(),
-            author: "Test Author".into(),
-            isbn: "Test ISBN".into(),
+            authors: vec!["Test Author".into(), "Second Author".into()],
+            isbn: Isbn::from_str("978-4-06-536957-9")?,
             description: "Test Description".into(),
+            publisher: Some("Test Publisher".into()),
+            published_year: Some(2024),
+            language: Some("jpn".into()),
+            page_count: Some(512),
+            edition: Some("第2版".into()),
+            subjects: vec!["Rust".into(), "Web".into()],
+            barcode: None,
         };
 
         // 蔵書データを投入すると正常終了することを確認
@@ -285,6 +1251,10 @@ mod tests {
         let options = BookListOptions {
             limit: 20,
             offset: 0,
+            cursor: None,
+            search: None,
+            filter: BookListFilter::default(),
+            sort: None,
         };
 
         // 蔵書の一覧を取得すると投入した1件だけ取得できることを確認
@@ -297,25 +1267,84 @@ mod tests {
         assert!(res.is_some());
 
         // 取得した蔵書データがCreateBookで投入した蔵書データと一致することを確認
+        let book = res.unwrap();
+        assert_eq!(book.author(), "Test Author, Second Author");
         let Book {
             id,
             title,
-            author,
+            authors,
             isbn,
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
             owner,
             ..
-        } = res.unwrap();
+        } = book;
         assert_eq!(id, book_id);
         assert_eq!(title, "Test Title");
-        assert_eq!(author, "Test Author");
-        assert_eq!(isbn, "Test ISBN");
+        assert_eq!(authors, vec!["Test Author", "Second Author"]);
+        assert_eq!(isbn.as_str(), "9784065369579");
         assert_eq!(description, "Test Description");
+        assert_eq!(publisher.as_deref(), Some("Test Publisher"));
+        assert_eq!(published_year, Some(2024));
+        assert_eq!(language.as_deref(), Some("jpn"));
+        assert_eq!(page_count, Some(512));
+        assert_eq!(edition.as_deref(), Some("第2版"));
+        assert_eq!(subjects, vec!["Rust", "Web"]);
         assert_eq!(owner.name, "Test User");
 
         Ok(())
     }
 
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_duplicates(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book = |title: &str, authors: &[&str], isbn: &str| -> anyhow::Result<CreateBook> {
+            Ok(CreateBook {
+                title: title.into(),
+                authors: authors.iter().map(|&author| author.into()).collect(),
+                isbn: Isbn::from_str(isbn)?,
+                description: "".into(),
+                publisher: None,
+                published_year: None,
+                language: None,
+                page_count: None,
+                edition: None,
+                subjects: vec![],
+                barcode: None,
+            })
+        };
+
+        // ISBNが同じ書誌を重複として検出することを確認
+        let res = repo
+            .find_duplicates(&book("別のタイトル", &["別の著者"], "978-4-7980-6170-2")?)
+            .await?;
+        assert_eq!(res, vec![book_id]);
+
+        // 大文字・小文字や空白の違いを無視して、タイトルと著者が同じ書誌を検出することを確認
+        let res = repo
+            .find_duplicates(&book(
+                "実践 rust プログラミング入門",
+                &["初田直也", "山田良明", "姫野大輔"],
+                "9780804429573",
+            )?)
+            .await?;
+        assert_eq!(res, vec![book_id]);
+
+        // どちらにも当てはまらなければ重複はないことを確認
+        let res = repo
+            .find_duplicates(&book("実践Rustプログラミング入門", &["別の著者"], "9780804429573")?)
+            .await?;
+        assert!(res.is_empty());
+
+        Ok(())
+    }
+
     #[sqlx::test(fixtures("common", "book"))]
     async fn test_update_book(pool: sqlx::PgPool) -> anyhow::Result<()> {
         let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
@@ -323,20 +1352,666 @@ mod tests {
         let book = repo.find_by_id(book_id).await?.unwrap();
 
         const NEW_AUTHOR: &str = "更新後の著者名";
-        assert_ne!(book.author, NEW_AUTHOR);
+        assert_ne!(book.author(), NEW_AUTHOR);
 
         let update_book = UpdateBook {
             book_id: book.id,
             title: book.title,
-            author: NEW_AUTHOR.into(),
+            authors: vec![NEW_AUTHOR.into()],
             isbn: book.isbn,
             description: book.description,
+            publisher: Some("技術評論社".into()),
+            published_year: Some(2019),
+            language: Some("jpn".into()),
+            page_count: Some(576),
+            edition: None,
+            subjects: vec!["Rust".into()],
+            expected_version: book.version,
             requested_user: UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c").unwrap(),
         };
+        let stale_update_book = UpdateBook {
+            book_id: update_book.book_id,
+            title: update_book.title.clone(),
+            authors: update_book.authors.clone(),
+            isbn: update_book.isbn.clone(),
+            description: update_book.description.clone(),
+            publisher: None,
+            published_year: None,
+            language: None,
+            page_count: None,
+            edition: None,
+            subjects: vec![],
+            expected_version: update_book.expected_version,
+            requested_user: update_book.requested_user,
+        };
         repo.update(update_book).await.unwrap();
 
         let book = repo.find_by_id(book_id).await?.unwrap();
-        assert_eq!(book.author, NEW_AUTHOR);
+        assert_eq!(book.authors, vec![NEW_AUTHOR]);
+        assert_eq!(book.author(), NEW_AUTHOR);
+        assert_eq!(book.published_year, Some(2019));
+        assert_eq!(book.page_count, Some(576));
+        assert_eq!(book.subjects, vec!["Rust"]);
+        assert_eq!(book.version, 2);
+
+        // 更新前の版番号を前提とした更新は、ほかの更新を上書きしないことを確認
+        let res = repo.update(stale_update_book).await;
+        assert!(matches!(res, Err(AppError::PreconditionFailed(_))));
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.page_count, Some(576));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_revisions(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let original = repo.find_by_id(book_id).await?.unwrap();
+        let update_book = |description: &str, expected_version: i32| UpdateBook {
+            book_id,
+            title: original.title.clone(),
+            authors: original.authors.clone(),
+            isbn: original.isbn.clone(),
+            description: description.into(),
+            publisher: original.publisher.clone(),
+            published_year: original.published_year,
+            language: original.language.clone(),
+            page_count: original.page_count,
+            edition: original.edition.clone(),
+            subjects: original.subjects.clone(),
+            expected_version,
+            requested_user: user_id,
+        };
+
+        // 更新すると、変更した項目と編集者つきの新しい版が記録されることを確認
+        repo.update(update_book("更新後の説明", 1)).await?;
+        let revisions = repo.find_revisions(book_id).await?;
+        assert_eq!(
+            revisions.iter().map(|r| r.revision).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+        assert_eq!(revisions[0].changed_fields, vec![BookField::Description]);
+        assert_eq!(revisions[0].snapshot.description, "更新後の説明");
+        assert_eq!(
+            revisions[0].edited_by.as_ref().map(|e| e.name.as_str()),
+            Some("Eleazar Fig")
+        );
+
+        // 内容が変わらない更新では版が増えないことを確認
+        repo.update(update_book("更新後の説明", 2)).await?;
+        assert_eq!(repo.find_revisions(book_id).await?.len(), 2);
+
+        // 以前の版に戻すと、その内容が新しい版として記録されることを確認
+        let revert_book = |revision, expected_version| RevertBook {
+            book_id,
+            revision,
+            expected_version,
+            requested_user: user_id,
+        };
+        let version = repo.find_by_id(book_id).await?.unwrap().version;
+        repo.revert(revert_book(1, version)).await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.description, original.description);
+        assert_eq!(book.version, version + 1);
+        let revisions = repo.find_revisions(book_id).await?;
+        assert_eq!(revisions[0].revision, 3);
+        assert_eq!(revisions[0].changed_fields, vec![BookField::Description]);
+
+        // 版番号が変わっている場合は戻さないことを確認
+        let res = repo.revert(revert_book(2, version)).await;
+        assert!(matches!(res, Err(AppError::PreconditionFailed(_))));
+        assert_eq!(repo.find_revisions(book_id).await?.len(), 3);
+
+        // 存在しない版には戻せず、存在しない書誌の版は取得できないことを確認
+        let res = repo.revert(revert_book(9, book.version)).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+        let res = repo.find_revisions(BookId::new()).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        // アーカイブした書誌は戻せないことを確認
+        repo.archive(ArchiveBook {
+            book_id,
+            requested_user: user_id,
+        })
+        .await?;
+        let res = repo.revert(revert_book(2, book.version)).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_all_with_search(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let search = |q: &str| BookListOptions {
+            limit: 20,
+            offset: 0,
+            cursor: None,
+            search: Some(q.into()),
+            filter: BookListFilter::default(),
+            sort: None,
+        };
+
+        // タイトルに含まれる単語で検索でき、大文字・小文字は区別しない
+        let res = repo.find_all(search("web")).await?;
+        assert_eq!(res.total, Some(1));
+        assert_eq!(
+            res.items[0].id,
+            BookId::from_str("17afb850-c786-49c5-a303-a3a443a2212c")?
+        );
+
+        // タイトルと説明文の両方に一致する蔵書が、タイトルのみに一致する蔵書より先に並ぶ
+        let res = repo.find_all(search("Rust")).await?;
+        assert_eq!(res.total, Some(3));
+        assert_eq!(
+            res.items.last().map(|b| b.id),
+            Some(BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?)
+        );
+
+        // 一致するものがなければ空の一覧を返す
+        let res = repo.find_all(search("Python")).await?;
+        assert_eq!(res.total, Some(0));
+        assert!(res.items.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_all_with_filter_and_sort(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 500),
+        );
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book1 = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book2 = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let book3 = BookId::from_str("17afb850-c786-49c5-a303-a3a443a2212c")?;
+        let options = |filter: BookListFilter, sort: Option<BookSort>| BookListOptions {
+            limit: 20,
+            offset: 0,
+            cursor: None,
+            search: None,
+            filter,
+            sort,
+        };
+        let ids = |list: PaginatedList<Book>| list.items.into_iter().map(|b| b.id).collect::<Vec<_>>();
+
+        // 著者名の部分一致で絞り込めることを確認
+        let res = repo
+            .find_all(options(
+                BookListFilter {
+                    author: Some("高野".into()),
+                    ..Default::default()
+                },
+                None,
+            ))
+            .await?;
+        assert_eq!(ids(res), vec![book2]);
+
+        // 所有者で絞り込めることを確認
+        // 3冊目だけを別のユーザーの所有に変え、所有者ごとに該当する書誌だけが返ることを確認する
+        let other = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()))
+            .create(CreateUser {
+                name: "Other User".into(),
+                email: "other@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?;
+        sqlx::query!(
+            "UPDATE books SET user_id = $1 WHERE book_id = $2",
+            other.id as _,
+            book3 as _
+        )
+        .execute(&pool)
+        .await?;
+        let owned_by = |owner: UserId| {
+            options(
+                BookListFilter {
+                    owner: Some(owner),
+                    ..Default::default()
+                },
+                None,
+            )
+        };
+        let mut res = ids(repo.find_all(owned_by(user_id)).await?);
+        res.sort_by_key(|id| id.to_string());
+        let mut expected = vec![book1, book2];
+        expected.sort_by_key(|id| id.to_string());
+        assert_eq!(res, expected);
+        assert_eq!(ids(repo.find_all(owned_by(other.id)).await?), vec![book3]);
+        let res = repo.find_all(owned_by(UserId::new())).await?;
+        assert_eq!(res.total, Some(0));
+
+        // 貸出中かどうかで絞り込めることを確認
+        checkout_repo
+            .create(CreateCheckout::new(book1, None, user_id, Utc::now(), user_id))
+            .await?;
+        let res = repo
+            .find_all(options(
+                BookListFilter {
+                    available: Some(false),
+                    ..Default::default()
+                },
+                None,
+            ))
+            .await?;
+        assert_eq!(ids(res), vec![book1]);
+        let res = repo
+            .find_all(options(
+                BookListFilter {
+                    available: Some(true),
+                    ..Default::default()
+                },
+                None,
+            ))
+            .await?;
+        assert_eq!(res.total, Some(2));
+
+        // 登録日時の範囲で絞り込めることを確認
+        let res = repo
+            .find_all(options(
+                BookListFilter {
+                    created_from: Some(Utc::now() + chrono::Duration::hours(1)),
+                    ..Default::default()
+                },
+                None,
+            ))
+            .await?;
+        assert_eq!(res.total, Some(0));
+
+        // タイトルで昇順・降順に並び替えられることを確認
+        let res = repo
+            .find_all(options(
+                BookListFilter::default(),
+                Some(BookSort {
+                    key: BookSortKey::Title,
+                    direction: SortDirection::Asc,
+                }),
+            ))
+            .await?;
+        assert_eq!(ids(res), vec![book3, book2, book1]);
+        let res = repo
+            .find_all(options(
+                BookListFilter::default(),
+                Some(BookSort {
+                    key: BookSortKey::Title,
+                    direction: SortDirection::Desc,
+                }),
+            ))
+            .await?;
+        assert_eq!(ids(res), vec![book1, book2, book3]);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_all_with_cursor(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let options = |cursor: CursorPosition, sort: Option<BookSort>| BookListOptions {
+            limit: 2,
+            offset: 0,
+            cursor: Some(cursor),
+            search: None,
+            filter: BookListFilter::default(),
+            sort,
+        };
+        let ids = |list: &PaginatedList<Book>| list.items.iter().map(|b| b.id).collect::<Vec<_>>();
+        // フィクスチャの蔵書は登録日時が同じなので、書誌IDの降順に並ぶ
+        let book1 = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let book2 = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book3 = BookId::from_str("17afb850-c786-49c5-a303-a3a443a2212c")?;
+
+        // 先頭のページでは件数を数えず、次のページのカーソルだけを返すことを確認
+        let first = repo.find_all(options(CursorPosition::First, None)).await?;
+        assert_eq!(ids(&first), vec![book1, book2]);
+        assert_eq!(first.total, None);
+        assert!(first.prev_cursor.is_none());
+
+        // 次のページのカーソルで残りを取得でき、その先のページはないことを確認
+        let next = first.next_cursor.clone().unwrap();
+        let second = repo.find_all(options(CursorPosition::At(next.clone()), None)).await?;
+        assert_eq!(ids(&second), vec![book3]);
+        assert!(second.next_cursor.is_none());
+
+        // 前のページのカーソルで最初のページに戻れることを確認
+        let prev = second.prev_cursor.unwrap();
+        let back = repo.find_all(options(CursorPosition::At(prev), None)).await?;
+        assert_eq!(ids(&back), vec![book1, book2]);
+        assert!(back.prev_cursor.is_none());
+        assert!(back.next_cursor.is_some());
+
+        // 並び順が異なるカーソルは受け付けないことを確認
+        let res = repo
+            .find_all(options(
+                CursorPosition::At(next),
+                Some(BookSort {
+                    key: BookSortKey::Title,
+                    direction: SortDirection::Asc,
+                }),
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_stream_all(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 500),
+        );
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await?;
+
+        // 全書誌が書誌IDの順に、所有者と貸出状況つきで返ることを確認
+        let books: Vec<Book> = repo.stream_all().try_collect().await?;
+        let ids = books.iter().map(|b| b.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                BookId::from_str("17afb850-c786-49c5-a303-a3a443a2212c")?,
+                book_id,
+                BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?,
+            ]
+        );
+        let book = &books[1];
+        assert_eq!(book.owner.name, "Eleazar Fig");
+        assert_eq!(book.available_copies(), 0);
+        assert_eq!(
+            book.copies[0].checkout.as_ref().map(|c| c.checked_out_by.id),
+            Some(user_id)
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_with_legacy_isbn(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+
+        // 移行時に正規化できなかったISBNが残っていても、書誌や一覧はそのまま読み出せることを確認
+        sqlx::query!(
+            "UPDATE books SET isbn = '9784798061703' WHERE book_id = $1",
+            book_id as _
+        )
+        .execute(&pool)
+        .await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.isbn.as_str(), "9784798061703");
+        let books = repo.find_by_ids(&[book_id]).await?;
+        assert_eq!(books[0].isbn.as_str(), "9784798061703");
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_archive_book(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 500),
+        );
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let list_options = || BookListOptions {
+            limit: 10,
+            offset: 0,
+            cursor: None,
+            search: None,
+            filter: BookListFilter::default(),
+            sort: None,
+        };
+        let archive = || ArchiveBook {
+            book_id,
+            requested_user: user_id,
+        };
+
+        // 貸出中の蔵書がある書誌はアーカイブできないことを確認
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await?;
+        let res = repo.archive(archive()).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却後はアーカイブでき、一覧には表示されないが書誌と貸出履歴は残ることを確認
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        let checkout_id = book.copies[0].checkout.as_ref().unwrap().checkout_id;
+        checkout_repo
+            .update_returned(UpdateReturned::new(checkout_id, book_id, user_id, Utc::now()))
+            .await?;
+        repo.archive(archive()).await?;
+        let list = repo.find_all(list_options()).await?;
+        assert_eq!(list.total, Some(2));
+        assert!(list.items.iter().all(|b| b.id != book_id));
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert!(book.archived_at.is_some());
+        let history = checkout_repo.find_history_by_book_id(book_id).await?;
+        assert_eq!(history.len(), 1);
+
+        // アーカイブした書誌は借りられず、もう一度アーカイブすることもできないことを確認
+        let res = checkout_repo
+            .create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let res = repo.archive(archive()).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        // アーカイブした書誌は、版番号が一致していても更新できないことを確認
+        let res = repo
+            .update(UpdateBook {
+                book_id,
+                title: book.title.clone(),
+                authors: book.authors.clone(),
+                isbn: book.isbn.clone(),
+                description: "アーカイブ後の説明".into(),
+                publisher: book.publisher.clone(),
+                published_year: book.published_year,
+                language: book.language.clone(),
+                page_count: book.page_count,
+                edition: book.edition.clone(),
+                subjects: book.subjects.clone(),
+                expected_version: book.version,
+                requested_user: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let archived = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(archived.version, book.version);
+        assert_eq!(archived.description, book.description);
+
+        // 元に戻すと一覧に表示され、アーカイブしていない書誌は元に戻せないことを確認
+        repo.restore(RestoreBook { book_id }).await?;
+        let list = repo.find_all(list_options()).await?;
+        assert_eq!(list.total, Some(3));
+        let res = repo.restore(RestoreBook { book_id }).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_create_many(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let owner = user_repo
+            .find_by_email("Eleazar.Fig@example.com")
+            .await?
+            .unwrap()
+            .id;
+        let book = |title: &str, barcode: Option<&str>| -> anyhow::Result<CreateBook> {
+            Ok(CreateBook {
+                title: title.into(),
+                authors: vec!["Test Author".into()],
+                isbn: Isbn::from_str("9784065369579")?,
+                description: "".into(),
+                publisher: None,
+                published_year: None,
+                language: None,
+                page_count: None,
+                edition: None,
+                subjects: vec![],
+                barcode: barcode.map(Into::into),
+            })
+        };
+        let count = || async {
+            repo.find_all(BookListOptions {
+                limit: 20,
+                offset: 0,
+                cursor: None,
+                search: None,
+                filter: BookListFilter::default(),
+                sort: None,
+            })
+            .await
+            .map(|res| res.total)
+        };
+
+        // 1件でも登録できなければ、すべて登録しないことを確認
+        let res = repo
+            .create_many(vec![
+                (book("Book A", Some("DUP-0001"))?, owner),
+                (book("Book B", Some("DUP-0001"))?, owner),
+            ])
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        assert_eq!(count().await?, Some(3));
+
+        repo.create_many(vec![
+            (book("Book A", None)?, owner),
+            (book("Book B", None)?, owner),
+        ])
+        .await?;
+        assert_eq!(count().await?, Some(5));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_copies(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 500),
+        );
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // 書誌に蔵書を追加すると、合計の冊数が増えることを確認
+        repo.create_copy(CreateBookCopy {
+            book_id,
+            barcode: Some("0000000001".into()),
+            requested_user: user_id,
+        })
+        .await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.total_copies(), 2);
+        assert_eq!(book.available_copies(), 2);
+
+        // 同じバーコードの蔵書は追加できないことを確認
+        let res = repo
+            .create_copy(CreateBookCopy {
+                book_id,
+                barcode: Some("0000000001".into()),
+                requested_user: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 蔵書の冊数だけ同時に貸し出せ、それ以上は貸し出せないことを確認
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await?;
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.available_copies(), 0);
+        let res = checkout_repo
+            .create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 貸出中の蔵書は削除できないことを確認
+        let copy_id = book.copies[0].id;
+        let res = repo
+            .delete_copy(DeleteBookCopy {
+                book_id,
+                copy_id,
+                requested_user: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却すると貸出可能な冊数が戻るが、貸出履歴のある蔵書は削除できないことを確認
+        let checkout_id = book.copies[0].checkout.as_ref().unwrap().checkout_id;
+        checkout_repo
+            .update_returned(UpdateReturned::new(checkout_id, book_id, user_id, Utc::now()))
+            .await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.available_copies(), 1);
+        let res = repo
+            .delete_copy(DeleteBookCopy {
+                book_id,
+                copy_id,
+                requested_user: user_id,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let history = checkout_repo.find_history_by_book_id(book_id).await?;
+        assert!(history.iter().any(|h| h.id == checkout_id));
+
+        // 取り置き中の予約がある場合は、予約者のために残しておく蔵書は削除できないことを確認
+        repo.create_copy(CreateBookCopy {
+            book_id,
+            barcode: Some("0000000002".into()),
+            requested_user: user_id,
+        })
+        .await?;
+        let new_copy_id = repo
+            .find_by_id(book_id)
+            .await?
+            .unwrap()
+            .copies
+            .iter()
+            .find(|c| c.barcode == "0000000002")
+            .unwrap()
+            .id;
+        sqlx::query!(
+            "UPDATE book_copies SET status = 'Lost' WHERE copy_id = $1",
+            copy_id as _
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query!(
+            r#"
+                INSERT INTO reservations (book_id, user_id, pickup_expires_at)
+                VALUES ($1, $2, CURRENT_TIMESTAMP(3) + INTERVAL '3 days')
+            "#,
+            book_id as _,
+            user_id as _
+        )
+        .execute(&pool)
+        .await?;
+        let delete_new_copy = || DeleteBookCopy {
+            book_id,
+            copy_id: new_copy_id,
+            requested_user: user_id,
+        };
+        let res = repo.delete_copy(delete_new_copy()).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 取り置きがなくなれば、貸出履歴のない蔵書は削除できることを確認
+        sqlx::query!("DELETE FROM reservations WHERE book_id = $1", book_id as _)
+            .execute(&pool)
+            .await?;
+        repo.delete_copy(delete_new_copy()).await?;
+        let book = repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.total_copies(), 2);
 
         Ok(())
     }
@@ -1,25 +1,43 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use derive_new::new;
+use itertools::Itertools;
 use kernel::{
     model::{
-        id::{BookId, CheckoutId, UserId},
+        book::CopyStatus,
         checkout::{
-            event::{CreateCheckout, UpdateReturned},
-            Checkout,
+            event::{
+                CreateCheckout, ForceReturned, RenewCheckout, ReportDamaged, ReportLost,
+                ReportRepaired, UpdateReturned,
+            },
+            Checkout, CheckoutHistoryOptions, IncidentKind, LoanPolicy, OverdueCheckout,
+            UserOverdueCheckouts,
         },
+        fine::{event::CreateFine, FineKind, FinePolicy},
+        id::{BookCopyId, BookId, CheckoutId, FineId, ReservationId, UserId},
+        list::PaginatedList,
+        role::Role,
+        user::CheckoutUser,
     },
     repository::checkout::CheckoutRepository,
 };
 use shared::error::{AppError, AppResult};
+use std::str::FromStr;
 
 use crate::database::{
-    model::checkout::{CheckoutRow, CheckoutStateRow, ReturnedCheckoutRow},
+    model::checkout::{
+        CheckoutHistoryRow, CheckoutRow, CheckoutStateRow, CopyStateRow, OverdueCheckoutRow,
+        RenewalStateRow, ReturnedCheckoutRow,
+    },
     ConnectionPool,
 };
+use crate::repository::reservation::settle_reservations;
 
 #[derive(new)]
 pub struct CheckoutRepositoryImpl {
     db: ConnectionPool,
+    loan_policy: LoanPolicy,
+    fine_policy: FinePolicy,
 }
 
 #[async_trait]
@@ -32,64 +50,201 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         self.set_transaction_serializable(&mut tx).await?;
 
         // 事前のチェックとして、以下を調べる
-        // - 指定の蔵書IDを蔵書が存在するか
-        // - 存在した場合、この蔵書は貸出中ではないか
+        // - 指定の書誌IDの書誌が存在し、アーカイブされていないか
+        // - 存在した場合、貸出可能な蔵書があるか（蔵書の指定がある場合は、その蔵書が貸出可能か）
         //
-        // 上記の両方がYesだった場合、このブロック以降の処理に進む
+        // 上記の両方がYesだった場合、貸し出す蔵書を決めてこのブロック以降の処理に進む
+        // 貸出可能な蔵書の冊数は、後続の予約の確認で使う
+        let (copy_id, available_copies) = {
+            let archived = sqlx::query_scalar!(
+                r#"
+                    SELECT archived_at IS NOT NULL AS "archived!" FROM books WHERE book_id = $1;
+                "#,
+                event.book_id as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?
+            // 指定した書籍が存在しない場合
+            .ok_or_else(|| {
+                AppError::EntityNotFound(format!("書籍({})が見つかりませんでした。", event.book_id))
+            })?;
+
+            // 指定した書籍がアーカイブされている場合
+            if archived {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})はアーカイブされているため借りられません。",
+                    event.book_id
+                )));
+            }
+
+            let copies = self.find_copy_states(&mut tx, event.book_id).await?;
+            let available = copies
+                .iter()
+                .filter(|c| c.is_available())
+                .collect::<Vec<_>>();
+
+            let copy_id = match event.copy_id {
+                // 蔵書の指定がある場合は、その蔵書が書誌に属しており貸出可能であること
+                Some(copy_id) => match copies.iter().find(|c| c.copy_id == copy_id) {
+                    None => {
+                        return Err(AppError::EntityNotFound(format!(
+                            "蔵書({})が見つかりませんでした。",
+                            copy_id
+                        )))
+                    }
+                    Some(c) if !c.is_available() => {
+                        return Err(AppError::UnprocessableEntity(format!(
+                            "蔵書({})は貸出中、または紛失・破損しているため借りられません。",
+                            copy_id
+                        )))
+                    }
+                    Some(c) => c.copy_id,
+                },
+                // 蔵書の指定がない場合は、貸出可能な蔵書のうち最初の1冊を選ぶ
+                None => match available.first() {
+                    Some(c) => c.copy_id,
+                    None => {
+                        return Err(AppError::UnprocessableEntity(format!(
+                            "書籍({})には貸出可能な蔵書がありません。",
+                            event.book_id
+                        )))
+                    }
+                },
+            };
+
+            (copy_id, available.len() as i64)
+        };
+
+        // ロールごとに定められた同時に借りられる冊数の上限に達しているユーザーは借りられない
         {
-            let res = sqlx::query_as!(
-                CheckoutStateRow,
+            let res = sqlx::query!(
                 r#"
                     SELECT
-                        b.book_id,
-                        c.checkout_id AS "checkout_id?: CheckoutId",
-                        NULL AS "user_id?: UserId"
+                        r.name AS role_name,
+                        (SELECT COUNT(*) FROM checkouts AS c WHERE c.user_id = u.user_id) AS "checked_out!"
                     FROM
-                        books AS b
-                    LEFT OUTER JOIN
-                        checkouts AS c
-                    USING (book_id)
+                        users AS u
+                    INNER JOIN
+                        roles AS r
+                    USING (role_id)
                     WHERE
-                        book_id = $1;
+                        u.user_id = $1;
                 "#,
-                event.book_id as _
+                event.checked_out_by as _
             )
             .fetch_optional(&mut *tx)
             .await
-            .map_err(AppError::SpecificOperationError)?;
+            .map_err(AppError::SpecificOperationError)?
+            .ok_or_else(|| {
+                AppError::EntityNotFound(format!(
+                    "ユーザー({})が見つかりませんでした。",
+                    event.checked_out_by
+                ))
+            })?;
 
-            match res {
-                // 指定した書籍が存在しない場合
-                None => {
-                    return Err(AppError::EntityNotFound(format!(
-                        "書籍({})が見つかりませんでした。",
-                        event.book_id
-                    )))
-                }
-                // 指定した書籍が存在するが貸出中の場合
-                Some(CheckoutStateRow {
-                    checkout_id: Some(_),
-                    ..
-                }) => {
+            let role = Role::from_str(&res.role_name)
+                .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+            if let Some(limit) = self.loan_policy.checkout_limit(&role) {
+                if res.checked_out >= limit {
                     return Err(AppError::UnprocessableEntity(format!(
-                        "書籍({})に対する貸出がすでに存在します。",
-                        event.book_id
-                    )))
+                        "ユーザー({})は同時に借りられる上限({}冊)に達しています。",
+                        event.checked_out_by, limit
+                    )));
                 }
-                _ => {} // それ以外は処理続行
+            }
+        }
+
+        // 罰金の未払い残高が上限を超えているユーザーは借りられない
+        {
+            let balance = sqlx::query_scalar!(
+                r#"
+                    SELECT COALESCE(SUM(amount), 0)::BIGINT AS "balance!" FROM fines WHERE user_id = $1;
+                "#,
+                event.checked_out_by as _
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            if self.fine_policy.blocks_checkout(balance) {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "ユーザー({})は未払いの罰金({})があるため借りられません。",
+                    event.checked_out_by, balance
+                )));
+            }
+        }
+
+        // 予約待ち行列を整理したうえで、予約がある場合は順番が回ってきた予約者のみが借りられる
+        // 予約者が借りた場合、順番が回ってきていなくても、その予約は完了したものとして削除する
+        settle_reservations(
+            &mut tx,
+            Some(event.book_id),
+            event.checked_out_at,
+            &self.loan_policy,
+        )
+        .await?;
+        {
+            let reservations = sqlx::query!(
+                r#"
+                    SELECT
+                        reservation_id AS "reservation_id: ReservationId",
+                        user_id AS "user_id: UserId",
+                        pickup_expires_at IS NOT NULL AS "ready!"
+                    FROM reservations
+                    WHERE book_id = $1
+                    ORDER BY reserved_at ASC;
+                "#,
+                event.book_id as _
+            )
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            // 取り置き中の予約の冊数だけ、貸出可能な蔵書が予約者のために確保されている
+            let ready = reservations.iter().filter(|r| r.ready).count() as i64;
+            let own = reservations
+                .iter()
+                .find(|r| r.user_id == event.checked_out_by);
+
+            // 自分の予約が取り置き中でない場合は、取り置き中の予約の分を除いて貸出可能な蔵書がなければ借りられない
+            if !own.is_some_and(|r| r.ready) && available_copies <= ready {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})は他のユーザーが予約しています。",
+                    event.book_id
+                )));
+            }
+
+            if let Some(r) = own {
+                sqlx::query!(
+                    r#"
+                        DELETE FROM reservations WHERE reservation_id = $1;
+                    "#,
+                    r.reservation_id as _
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::SpecificOperationError)?;
             }
         }
 
         // 貸出処理を行う、すなわちcheckoutsテーブルにレコードを行う
+        // 返却期限は貸出ポリシーに従って貸出日時から算出する
+        // 貸出操作を行ったユーザーも、借りたユーザーとは別に記録する
         let checkout_id = CheckoutId::new();
+        let due_at = self.loan_policy.due_at(event.checked_out_at);
         let res = sqlx::query!(
             r#"
-                INSERT INTO checkouts (checkout_id, book_id, user_id, checked_out_at) VALUES ($1, $2, $3, $4);
+                INSERT INTO checkouts (checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, checkout_processed_by)
+                VALUES ($1, $2, $3, $4, $5, $6, $7);
             "#,
             checkout_id as _,
             event.book_id as _,
+            copy_id as _,
             event.checked_out_by as _,
             event.checked_out_at,
+            due_at,
+            event.processed_by as _,
         )
         .execute(&mut *tx)
         .await
@@ -114,9 +269,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         self.set_transaction_serializable(&mut tx).await?;
 
         // 返却操作は事前のチェックとして、以下を調べる
-        // - 指定の蔵書IDを蔵書が存在するか
+        // - 指定の書誌IDの書誌が存在するか
         // - 存在した場合
-        //   - この蔵書は貸出中であり
+        //   - この書誌に指定の貸出が存在し
         //   - かつ、借りたユーザーが指定のユーザーと同じか
         //
         // 上記の両方がYesだった場合、このブロック以降の処理に進む
@@ -134,11 +289,12 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         books AS b
                     LEFT OUTER JOIN
                         checkouts AS c
-                    USING (book_id)
+                    ON c.book_id = b.book_id AND c.checkout_id = $2
                     WHERE
-                        book_id = $1;
+                        b.book_id = $1;
                 "#,
-                event.book_id as _
+                event.book_id as _,
+                event.checkout_id as _
             )
             .fetch_optional(&mut *tx)
             .await
@@ -152,36 +308,144 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                         event.book_id
                     )))
                 }
-                // 指定した書籍が貸出中であり、貸出IDまたは借りたユーザーが異なる場合
+                // 指定した貸出が存在し、借りたユーザーも同じ場合は処理続行
                 Some(CheckoutStateRow {
-                    checkout_id: Some(c),
-                    user_id: Some(u),
-                    ..
-                }) if (c, u) != (event.checkout_id, event.returned_by) => {
+                    user_id: Some(u), ..
+                }) if u == event.returned_by => {}
+                // 指定した貸出が存在しない、または借りたユーザーが異なる場合
+                _ => {
                     return Err(AppError::UnprocessableEntity(format!(
                         "指定の貸出(ID({}), ユーザー({})、書籍({})は返却できません。",
-                        event.checkout_id,
-                        event.returned_by,
+                        event.checkout_id, event.returned_by, event.book_id
+                    )))
+                }
+            }
+        }
+
+        self.move_to_returned(
+            &mut tx,
+            event.checkout_id,
+            event.book_id,
+            event.returned_by,
+            event.returned_at,
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 借りたユーザーに関わらず、管理者が返却操作を行う
+    async fn force_returned(&self, event: ForceReturned) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 借りたユーザーの確認は行わず、指定の蔵書IDに対する指定の貸出が存在するかのみを調べる
+        {
+            let res = sqlx::query_as!(
+                CheckoutStateRow,
+                r#"
+                    SELECT
+                        b.book_id,
+                        c.checkout_id AS "checkout_id?: CheckoutId",
+                        c.user_id AS "user_id?: UserId"
+                    FROM
+                        books AS b
+                    LEFT OUTER JOIN
+                        checkouts AS c
+                    ON c.book_id = b.book_id AND c.checkout_id = $2
+                    WHERE
+                        b.book_id = $1;
+                "#,
+                event.book_id as _,
+                event.checkout_id as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            match res {
+                None => {
+                    return Err(AppError::EntityNotFound(format!(
+                        "書籍({})が見つかりませんでした。",
                         event.book_id
                     )))
                 }
-                _ => {} // それ以外は処理続行
+                Some(CheckoutStateRow {
+                    checkout_id: Some(c),
+                    ..
+                }) if c == event.checkout_id => {}
+                _ => {
+                    return Err(AppError::UnprocessableEntity(format!(
+                        "指定の貸出(ID({})、書籍({})は返却できません。",
+                        event.checkout_id, event.book_id
+                    )))
+                }
             }
         }
 
-        // データベース上の返却操作として、
-        // checkoutsテーブルにある該当貸出IDのレコードを、
-        // returned_atを追加して、returned_checkoutsテーブルにINSERTする。
+        self.move_to_returned(
+            &mut tx,
+            event.checkout_id,
+            event.book_id,
+            event.processed_by,
+            event.returned_at,
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 貸出中の蔵書の紛失を報告する
+    async fn report_lost(&self, event: ReportLost) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 指定の書誌ID・貸出IDの貸出が存在するかを調べ、借りたユーザーと貸し出した蔵書を取得する
+        let lost = sqlx::query!(
+            r#"
+                SELECT
+                    user_id AS "user_id: UserId",
+                    copy_id AS "copy_id: BookCopyId"
+                FROM checkouts
+                WHERE checkout_id = $1 AND book_id = $2;
+            "#,
+            event.checkout_id as _,
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!(
+                "貸出({})が見つかりませんでした。",
+                event.checkout_id
+            ))
+        })?;
+
+        // 紛失を報告した時点で返却済みとし、貸出履歴に紛失を記録する
+        // 弁償金を請求するため、延滞料金は計上しない
         let res = sqlx::query!(
             r#"
                 INSERT INTO returned_checkouts
-                (checkout_id, book_id, user_id, checked_out_at, returned_at)
-                SELECT checkout_id, book_id, user_id, checked_out_at, $2
+                (checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count, returned_at,
+                 checkout_processed_by, return_processed_by, incident, incident_reported_at)
+                SELECT checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count, $2,
+                       checkout_processed_by, $3, $4, $2
                 FROM checkouts
                 WHERE checkout_id = $1;
             "#,
             event.checkout_id as _,
-            event.returned_at,
+            event.reported_at,
+            event.reported_by as _,
+            IncidentKind::Lost.as_ref(),
         )
         .execute(&mut *tx)
         .await
@@ -193,8 +457,7 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
             ));
         }
 
-        // 上記処理が成功したら、checkoutsテーブルから該当貸出IDのレコードを削除する
-        let res = sqlx::query!(
+        sqlx::query!(
             r#"
                 DELETE FROM checkouts WHERE checkout_id = $1;
             "#,
@@ -204,9 +467,331 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         .await
         .map_err(AppError::SpecificOperationError)?;
 
+        self.update_copy_status(&mut tx, lost.copy_id, CopyStatus::Lost)
+            .await?;
+
+        // 書誌のすべての蔵書が紛失した場合は貸出できないため、予約はすべて取り消す
+        sqlx::query!(
+            r#"
+                DELETE FROM reservations
+                WHERE
+                    book_id = $1
+                    AND NOT EXISTS (
+                        SELECT 1 FROM book_copies WHERE book_id = $1 AND status <> $2
+                    );
+            "#,
+            event.book_id as _,
+            CopyStatus::Lost.as_ref(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if let Some(fee) = event.replacement_fee {
+            let fine = CreateFine {
+                user_id: lost.user_id,
+                checkout_id: Some(event.checkout_id),
+                kind: FineKind::Replacement,
+                amount: fee,
+                note: "紛失による弁償金".into(),
+                created_by: event.reported_by,
+                created_at: event.reported_at,
+            };
+            self.create_fine(&mut tx, fine).await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 返却済みの蔵書の破損を報告する
+    async fn report_damaged(&self, event: ReportDamaged) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 事前のチェックとして、以下を調べる
+        // - 指定の書誌ID・蔵書IDの蔵書が存在するか
+        // - 存在した場合、貸出中ではなく、紛失もしていないか
+        {
+            let res = sqlx::query!(
+                r#"
+                    SELECT
+                        bc.status,
+                        EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id) AS "checked_out!"
+                    FROM book_copies AS bc
+                    WHERE bc.copy_id = $1 AND bc.book_id = $2;
+                "#,
+                event.copy_id as _,
+                event.book_id as _
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?
+            .ok_or_else(|| {
+                AppError::EntityNotFound(format!(
+                    "蔵書({})が見つかりませんでした。",
+                    event.copy_id
+                ))
+            })?;
+
+            if res.checked_out || res.status == CopyStatus::Lost.as_ref() {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "蔵書({})は貸出中または紛失しているため、破損を報告できません。",
+                    event.copy_id
+                )));
+            }
+        }
+
+        // 破損は直近の貸出で生じたものとして、その貸出履歴に記録する
+        let last = sqlx::query!(
+            r#"
+                SELECT
+                    checkout_id AS "checkout_id: CheckoutId",
+                    user_id AS "user_id: UserId"
+                FROM returned_checkouts
+                WHERE copy_id = $1
+                ORDER BY returned_at DESC
+                LIMIT 1;
+            "#,
+            event.copy_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::UnprocessableEntity(format!(
+                "蔵書({})には返却済みの貸出がありません。",
+                event.copy_id
+            ))
+        })?;
+
+        sqlx::query!(
+            r#"
+                UPDATE returned_checkouts
+                SET
+                    incident = $2,
+                    incident_reported_at = $3
+                WHERE checkout_id = $1;
+            "#,
+            last.checkout_id as _,
+            IncidentKind::Damaged.as_ref(),
+            event.reported_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        self.update_copy_status(&mut tx, event.copy_id, CopyStatus::Damaged)
+            .await?;
+
+        // 破損した蔵書を取り置いていた予約は、貸出可能な蔵書の冊数を超えた分だけ、後から予約したものを待ち行列に戻す
+        sqlx::query!(
+            r#"
+                UPDATE reservations
+                SET pickup_expires_at = NULL
+                WHERE reservation_id IN (
+                    SELECT q.reservation_id
+                    FROM (
+                        SELECT
+                            r.reservation_id,
+                            ROW_NUMBER() OVER (ORDER BY r.reserved_at) AS position
+                        FROM
+                            reservations AS r
+                        WHERE
+                            r.book_id = $1
+                            AND r.pickup_expires_at IS NOT NULL
+                    ) AS q
+                    WHERE q.position > (
+                        SELECT COUNT(*)
+                        FROM book_copies AS bc
+                        WHERE
+                            bc.book_id = $1
+                            AND bc.status = $2
+                            AND NOT EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id)
+                    )
+                );
+            "#,
+            event.book_id as _,
+            CopyStatus::Available.as_ref(),
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if let Some(fee) = event.replacement_fee {
+            let fine = CreateFine {
+                user_id: last.user_id,
+                checkout_id: Some(last.checkout_id),
+                kind: FineKind::Replacement,
+                amount: fee,
+                note: "破損による弁償金".into(),
+                created_by: event.reported_by,
+                created_at: event.reported_at,
+            };
+            self.create_fine(&mut tx, fine).await?;
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 破損した蔵書の修理を報告し、貸出可能な状態に戻す
+    async fn report_repaired(&self, event: ReportRepaired) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 指定の書誌ID・蔵書IDの蔵書が存在し、破損しているかを調べる
+        let status = sqlx::query_scalar!(
+            r#"
+                SELECT status FROM book_copies WHERE copy_id = $1 AND book_id = $2;
+            "#,
+            event.copy_id as _,
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!("蔵書({})が見つかりませんでした。", event.copy_id))
+        })?;
+
+        if status != CopyStatus::Damaged.as_ref() {
+            return Err(AppError::UnprocessableEntity(format!(
+                "蔵書({})は破損していないため、修理を報告できません。",
+                event.copy_id
+            )));
+        }
+
+        self.update_copy_status(&mut tx, event.copy_id, CopyStatus::Available)
+            .await?;
+
+        // 貸出可能になった蔵書を、待ち行列の先頭の予約者のために取り置く
+        settle_reservations(
+            &mut tx,
+            Some(event.book_id),
+            event.repaired_at,
+            &self.loan_policy,
+        )
+        .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 貸出の延長操作を行う
+    async fn renew(&self, event: RenewCheckout) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 延長操作は事前のチェックとして、以下を調べる
+        // - 指定の蔵書ID・貸出IDの貸出が存在するか
+        // - 存在した場合
+        //   - 借りたユーザーが指定のユーザーと同じか
+        //   - 延長回数が上限に達していないか
+        //   - 返却期限を過ぎていないか
+        //   - 他のユーザーが予約していないか
+        let RenewalStateRow {
+            user_id,
+            due_at,
+            renewal_count,
+        } = sqlx::query_as!(
+            RenewalStateRow,
+            r#"
+                SELECT
+                    user_id,
+                    due_at,
+                    renewal_count
+                FROM
+                    checkouts
+                WHERE
+                    checkout_id = $1
+                    AND book_id = $2;
+            "#,
+            event.checkout_id as _,
+            event.book_id as _
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| {
+            AppError::EntityNotFound(format!(
+                "貸出({})が見つかりませんでした。",
+                event.checkout_id
+            ))
+        })?;
+
+        if user_id != event.renewed_by {
+            return Err(AppError::UnprocessableEntity(format!(
+                "指定の貸出(ID({}), ユーザー({})、書籍({})は延長できません。",
+                event.checkout_id, event.renewed_by, event.book_id
+            )));
+        }
+
+        if !self.loan_policy.can_renew(renewal_count) {
+            return Err(AppError::UnprocessableEntity(format!(
+                "貸出({})は延長回数の上限({}回)に達しています。",
+                event.checkout_id, self.loan_policy.max_renewals
+            )));
+        }
+
+        // 延滞料金は返却時の返却期限から計算するため、延滞中に延長すると延滞した日数が消えてしまう
+        if due_at < event.renewed_at {
+            return Err(AppError::UnprocessableEntity(format!(
+                "貸出({})は返却期限を過ぎているため延長できません。",
+                event.checkout_id
+            )));
+        }
+
+        // 他のユーザーが予約している蔵書は延長できない
+        let reserved_by_others = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM reservations WHERE book_id = $1 AND user_id <> $2
+                ) AS "exists!";
+            "#,
+            event.book_id as _,
+            event.renewed_by as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if reserved_by_others {
+            return Err(AppError::UnprocessableEntity(format!(
+                "書籍({})は他のユーザーが予約しているため延長できません。",
+                event.book_id
+            )));
+        }
+
+        // 返却期限を延ばし、延長回数を加算する
+        let new_due_at = self.loan_policy.renewed_due_at(due_at);
+        let res = sqlx::query!(
+            r#"
+                UPDATE checkouts
+                SET
+                    due_at = $2,
+                    renewal_count = renewal_count + 1
+                WHERE checkout_id = $1;
+            "#,
+            event.checkout_id as _,
+            new_due_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
         if res.rows_affected() < 1 {
             return Err(AppError::NoRowsAffectedError(
-                "No returning record has been updated".into(),
+                "No checkout record has been renewed".into(),
             ));
         }
 
@@ -226,8 +811,13 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 SELECT
                     c.checkout_id,
                     c.book_id,
+                    c.copy_id,
+                    bc.barcode,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checkout_processed_by,
                     b.title,
                     b.author,
                     b.isbn
@@ -236,6 +826,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 INNER JOIN
                     books AS b
                 USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = c.copy_id
                 ORDER BY c.checked_out_at ASC;
             "#,
         )
@@ -245,6 +838,63 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         .map_err(AppError::SpecificOperationError)
     }
 
+    // 指定日時の時点で返却期限を過ぎている貸出情報を、ユーザーごとにまとめて取得する。
+    async fn find_overdue_all(&self, now: DateTime<Utc>) -> AppResult<Vec<UserOverdueCheckouts>> {
+        // 延滞日数はタイムゾーンに依存しないよう、UTCの経過時間から24時間単位で算出する。
+        // 1日未満の超過も1日として切り上げる。
+        // ユーザーごとにまとめるため、ユーザー名・ユーザーID・返却期限の順に並べる。
+        let rows = sqlx::query_as!(
+            OverdueCheckoutRow,
+            r#"
+                SELECT
+                    c.checkout_id,
+                    c.book_id,
+                    c.copy_id,
+                    bc.barcode,
+                    c.user_id,
+                    u.name AS user_name,
+                    c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checkout_processed_by,
+                    b.title,
+                    b.author,
+                    b.isbn,
+                    CEIL(EXTRACT(EPOCH FROM ($1 - c.due_at)) / 86400)::BIGINT AS "days_overdue!"
+                FROM
+                    checkouts AS c
+                INNER JOIN
+                    books AS b
+                USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = c.copy_id
+                INNER JOIN
+                    users AS u
+                ON c.user_id = u.user_id
+                WHERE
+                    c.due_at < $1
+                ORDER BY u.name ASC, c.user_id ASC, c.due_at ASC;
+            "#,
+            now
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let overdues = rows
+            .into_iter()
+            .chunk_by(|row| (row.user_id, row.user_name.clone()))
+            .into_iter()
+            .map(|((id, name), rows)| UserOverdueCheckouts {
+                user: CheckoutUser { id, name },
+                checkouts: rows.map(OverdueCheckout::from).collect(),
+            })
+            .collect();
+
+        Ok(overdues)
+    }
+
     // ユーザーIDに紐づく未返却の貸出情報を取得する。
     async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>> {
         // find_unreturned_allのSQLにユーザーIDで絞り込むWHERE句を追加したものである。
@@ -254,8 +904,13 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 SELECT
                     c.checkout_id,
                     c.book_id,
+                    c.copy_id,
+                    bc.barcode,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checkout_processed_by,
                     b.title,
                     b.author,
                     b.isbn
@@ -264,6 +919,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 INNER JOIN
                     books AS b
                 USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = c.copy_id
                 WHERE
                     c.user_id = $1
                 ORDER BY c.checked_out_at ASC;
@@ -276,6 +934,97 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         .map_err(AppError::SpecificOperationError)
     }
 
+    // ユーザーIDに紐づく貸出履歴（返却済みも含む）を取得する。
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutHistoryOptions,
+    ) -> AppResult<PaginatedList<Checkout>> {
+        let CheckoutHistoryOptions {
+            limit,
+            offset,
+            from,
+            to,
+        } = options;
+
+        // 貸出中のcheckoutsテーブルと返却済みのreturned_checkoutsテーブルをUNION ALLでまとめ、
+        // 貸出日時で絞り込んだうえで新しい順に並べてページネーションする。
+        // 件数はページネーション前の全体の件数をCOUNT(*) OVER()で取得する。
+        let rows = sqlx::query_as!(
+            CheckoutHistoryRow,
+            r#"
+                SELECT
+                    COUNT(*) OVER() AS "total!",
+                    h.checkout_id AS "checkout_id!: CheckoutId",
+                    h.book_id AS "book_id!: BookId",
+                    h.copy_id AS "copy_id!: BookCopyId",
+                    bc.barcode,
+                    h.user_id AS "user_id!: UserId",
+                    h.checked_out_at AS "checked_out_at!",
+                    h.due_at AS "due_at!",
+                    h.renewal_count AS "renewal_count!",
+                    h.returned_at,
+                    h.checkout_processed_by AS "checkout_processed_by!: UserId",
+                    h.return_processed_by AS "return_processed_by: UserId",
+                    h.incident,
+                    h.incident_reported_at,
+                    b.title,
+                    b.author,
+                    b.isbn
+                FROM (
+                    SELECT
+                        checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count,
+                        NULL::timestamptz AS returned_at, checkout_processed_by, NULL::uuid AS return_processed_by,
+                        NULL::varchar AS incident, NULL::timestamptz AS incident_reported_at
+                    FROM checkouts
+                    WHERE user_id = $1
+                    UNION ALL
+                    SELECT
+                        checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count,
+                        returned_at, checkout_processed_by, return_processed_by,
+                        incident, incident_reported_at
+                    FROM returned_checkouts
+                    WHERE user_id = $1
+                ) AS h
+                INNER JOIN
+                    books AS b
+                USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = h.copy_id
+                WHERE
+                    ($2::timestamptz IS NULL OR h.checked_out_at >= $2)
+                    AND ($3::timestamptz IS NULL OR h.checked_out_at <= $3)
+                ORDER BY h.checked_out_at DESC
+                LIMIT $4
+                OFFSET $5;
+            "#,
+            user_id as _,
+            from,
+            to,
+            limit,
+            offset,
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let total = rows.first().map(|r| r.total).unwrap_or_default();
+        let items = rows
+            .into_iter()
+            .map(Checkout::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(PaginatedList {
+            total: Some(total),
+            limit,
+            offset,
+            items,
+            next_cursor: None,
+            prev_cursor: None,
+        })
+    }
+
     // 蔵書の貸出履歴（返却済みも含む）を取得する。
     async fn find_history_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>> {
         // このメソッドでは、貸出中・返却済みの両方を取得して、蔵書に対する貸出履歴の一覧を返す必要がある
@@ -283,7 +1032,8 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         // 未返却の貸出情報があればVecに挿入して返す、という実装にする
 
         // 未返却の貸出情報を取得
-        let checkout: Option<Checkout> = self.find_unreturned_by_book_id(book_id).await?;
+        // 書誌に複数の蔵書がある場合は、未返却の貸出も複数になりうる
+        let checkouts: Vec<Checkout> = self.find_unreturned_by_book_id(book_id).await?;
 
         // 返却済みの貸出情報を取得
         let mut checkout_histories: Vec<Checkout> = sqlx::query_as!(
@@ -292,9 +1042,17 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 SELECT
                     rc.checkout_id,
                     rc.book_id,
+                    rc.copy_id,
+                    bc.barcode,
                     rc.user_id,
                     rc.checked_out_at,
+                    rc.due_at,
+                    rc.renewal_count,
                     rc.returned_at,
+                    rc.checkout_processed_by,
+                    rc.return_processed_by,
+                    rc.incident,
+                    rc.incident_reported_at,
                     b.title,
                     b.author,
                     b.isbn
@@ -303,6 +1061,9 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
                 INNER JOIN
                     books AS b
                 USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = rc.copy_id
                 WHERE
                     rc.book_id = $1
                 ORDER BY
@@ -314,22 +1075,23 @@ impl CheckoutRepository for CheckoutRepositoryImpl {
         .await
         .map_err(AppError::SpecificOperationError)?
         .into_iter()
-        .map(Checkout::from)
-        .collect();
+        .map(Checkout::try_from)
+        .collect::<AppResult<Vec<_>>>()?;
 
         // 貸出中である場合は返却済みの履歴の先頭に追加する
-        if let Some(co) = checkout {
-            checkout_histories.insert(0, co);
-        }
+        checkout_histories.splice(0..0, checkouts);
 
         Ok(checkout_histories)
     }
 }
 
 impl CheckoutRepositoryImpl {
-    // create, update_returnedメソッドでのトランザクションを利用するにあたり
+    // create, update_returned, renewメソッドでのトランザクションを利用するにあたり
     // トランザクション分離レベルをSERIALIZABLEにするために内部的に使うメソッド
-    async fn set_transaction_serializable(&self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> AppResult<()> {
+    async fn set_transaction_serializable(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ) -> AppResult<()> {
         sqlx::query!("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
             .execute(&mut **tx)
             .await
@@ -338,16 +1100,194 @@ impl CheckoutRepositoryImpl {
         Ok(())
     }
 
+    // update_returned, force_returnedで、貸出中のレコードを返却済みに移すために内部的に使うメソッド
+    // 延滞料金の計上と予約待ち行列の整理もあわせて行う
+    async fn move_to_returned(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        checkout_id: CheckoutId,
+        book_id: BookId,
+        processed_by: UserId,
+        returned_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        // データベース上の返却操作として、
+        // checkoutsテーブルにある該当貸出IDのレコードを、
+        // returned_atと返却操作を行ったユーザーを追加して、returned_checkoutsテーブルにINSERTする。
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO returned_checkouts
+                (checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count, returned_at, checkout_processed_by, return_processed_by)
+                SELECT checkout_id, book_id, copy_id, user_id, checked_out_at, due_at, renewal_count, $2, checkout_processed_by, $3
+                FROM checkouts
+                WHERE checkout_id = $1;
+            "#,
+            checkout_id as _,
+            returned_at,
+            processed_by as _,
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "No returning record has been updated".into(),
+            ));
+        }
+
+        // 返却期限を過ぎていた場合は、延滞日数に応じた延滞料金を罰金台帳に計上する
+        {
+            let returned = sqlx::query!(
+                r#"
+                    SELECT
+                        user_id AS "user_id: UserId",
+                        due_at
+                    FROM returned_checkouts
+                    WHERE checkout_id = $1;
+                "#,
+                checkout_id as _,
+            )
+            .fetch_one(&mut **tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            let amount = self.fine_policy.overdue_fine(returned.due_at, returned_at);
+            if amount > 0 {
+                sqlx::query!(
+                    r#"
+                        INSERT INTO fines (fine_id, user_id, checkout_id, kind, amount, note, created_at)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7);
+                    "#,
+                    FineId::new() as _,
+                    returned.user_id as _,
+                    checkout_id as _,
+                    FineKind::Overdue.as_ref(),
+                    amount,
+                    "返却期限超過による延滞料金",
+                    returned_at,
+                )
+                .execute(&mut **tx)
+                .await
+                .map_err(AppError::SpecificOperationError)?;
+            }
+        }
+
+        // 上記処理が成功したら、checkoutsテーブルから該当貸出IDのレコードを削除する
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM checkouts WHERE checkout_id = $1;
+            "#,
+            checkout_id as _,
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "No returning record has been updated".into(),
+            ));
+        }
+
+        // 予約がある場合は、先頭の予約者に取り置き期間を設定する
+        settle_reservations(tx, Some(book_id), returned_at, &self.loan_policy).await?;
+
+        Ok(())
+    }
+
+    // createで書誌に属する蔵書の状態を確認するために内部的に使うメソッド
+    async fn find_copy_states(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        book_id: BookId,
+    ) -> AppResult<Vec<CopyStateRow>> {
+        sqlx::query_as!(
+            CopyStateRow,
+            r#"
+                SELECT
+                    bc.copy_id,
+                    bc.status,
+                    EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id) AS "checked_out!"
+                FROM book_copies AS bc
+                WHERE bc.book_id = $1
+                ORDER BY bc.created_at ASC, bc.barcode ASC;
+            "#,
+            book_id as _
+        )
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)
+    }
+
+    // report_lost, report_damaged, report_repairedで蔵書の状態を更新するために内部的に使うメソッド
+    async fn update_copy_status(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        copy_id: BookCopyId,
+        status: CopyStatus,
+    ) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE book_copies SET status = $2 WHERE copy_id = $1;
+            "#,
+            copy_id as _,
+            status.as_ref(),
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "No book copy record has been updated".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // report_lost, report_damagedで弁償金を罰金台帳に計上するために内部的に使うメソッド
+    async fn create_fine(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        event: CreateFine,
+    ) -> AppResult<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO fines (fine_id, user_id, checkout_id, kind, amount, note, created_by, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8);
+            "#,
+            FineId::new() as _,
+            event.user_id as _,
+            event.checkout_id as _,
+            event.kind.as_ref(),
+            event.amount,
+            event.note,
+            event.created_by as _,
+            event.created_at,
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(())
+    }
+
     // find_history_by_book_idで未返却の貸出し情報を取得するために内部的に使うメソッド
-    async fn find_unreturned_by_book_id(&self, book_id: BookId) -> AppResult<Option<Checkout>> {
+    async fn find_unreturned_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>> {
         let res = sqlx::query_as!(
             CheckoutRow,
             r#"
                 SELECT
                     c.checkout_id,
                     c.book_id,
+                    c.copy_id,
+                    bc.barcode,
                     c.user_id,
                     c.checked_out_at,
+                    c.due_at,
+                    c.renewal_count,
+                    c.checkout_processed_by,
                     b.title,
                     b.author,
                     b.isbn
@@ -356,16 +1296,595 @@ impl CheckoutRepositoryImpl {
                 INNER JOIN
                     books AS b
                 USING (book_id)
+                INNER JOIN
+                    book_copies AS bc
+                ON bc.copy_id = c.copy_id
                 WHERE
                     c.book_id = $1
+                ORDER BY c.checked_out_at DESC
             "#,
             book_id as _
         )
-        .fetch_optional(self.db.inner_ref())
+        .fetch_all(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?
-        .map(Checkout::from);
+        .into_iter()
+        .map(Checkout::from)
+        .collect();
 
         Ok(res)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+    use std::str::FromStr;
+
+    fn checkout_repository(pool: &sqlx::PgPool) -> CheckoutRepositoryImpl {
+        CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 500),
+        )
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_due_at(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = checkout_repository(&pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let checked_out_at = Utc::now();
+
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            user_id,
+            checked_out_at,
+            user_id,
+        ))
+        .await?;
+
+        // 返却期限は貸出日時から貸出ポリシーの日数後になっていることを確認
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        let expected_due_at = checked_out_at + Duration::days(14);
+        assert!((checkout.due_at - expected_due_at).num_milliseconds().abs() < 1);
+
+        // 返却後も返却期限が履歴に保持されていることを確認
+        repo.update_returned(UpdateReturned::new(
+            checkout.id,
+            book_id,
+            user_id,
+            Utc::now(),
+        ))
+        .await?;
+        let history = repo.find_history_by_book_id(book_id).await?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].due_at, checkout.due_at);
+        assert!(history[0].returned_at.is_some());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_renew_checkout_up_to_limit(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = checkout_repository(&pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            user_id,
+            Utc::now(),
+            user_id,
+        ))
+        .await?;
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+
+        // 上限回数までは延長でき、そのたびに返却期限が延びることを確認
+        for expected_count in 1..=2 {
+            repo.renew(RenewCheckout::new(
+                checkout.id,
+                book_id,
+                user_id,
+                Utc::now(),
+            ))
+            .await?;
+            let renewed = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+            assert_eq!(renewed.renewal_count, expected_count);
+            assert_eq!(
+                renewed.due_at,
+                checkout.due_at + Duration::days(14 * expected_count as i64)
+            );
+        }
+
+        // 上限回数を超える延長はできないことを確認
+        let res = repo
+            .renew(RenewCheckout::new(
+                checkout.id,
+                book_id,
+                user_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 延長回数が貸出履歴に残ることを確認
+        let history = repo.find_history_by_book_id(book_id).await?;
+        assert_eq!(history[0].renewal_count, 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_renew_overdue_checkout(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = checkout_repository(&pool);
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // 20日前に借りた蔵書は返却期限を6日過ぎている
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            user_id,
+            Utc::now() - Duration::days(20),
+            user_id,
+        ))
+        .await?;
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+
+        // 延滞中の貸出は延長できず、返却期限と延長回数が変わらないことを確認
+        let res = repo
+            .renew(RenewCheckout::new(
+                checkout.id,
+                book_id,
+                user_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let unchanged = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        assert_eq!(unchanged.due_at, checkout.due_at);
+        assert_eq!(unchanged.renewal_count, 0);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_overdue_all(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = checkout_repository(&pool);
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let overdue_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book_id = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+
+        // 20日と1時間前に借りた蔵書は返却期限を6日と1時間過ぎているので、延滞日数は7日となる
+        let now = Utc::now();
+        repo.create(CreateCheckout::new(
+            overdue_book_id,
+            None,
+            user_id,
+            now - Duration::days(20) - Duration::hours(1),
+            user_id,
+        ))
+        .await?;
+        repo.create(CreateCheckout::new(book_id, None, user_id, now, user_id))
+            .await?;
+
+        let overdues = repo.find_overdue_all(now).await?;
+        assert_eq!(overdues.len(), 1);
+        assert_eq!(overdues[0].user.id, user_id);
+        assert_eq!(overdues[0].checkouts.len(), 1);
+        assert_eq!(
+            overdues[0].checkouts[0].checkout.book.book_id,
+            overdue_book_id
+        );
+        assert_eq!(overdues[0].checkouts[0].days_overdue, 7);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_limit_by_role(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        use crate::repository::user::UserRepositoryImpl;
+        use kernel::{model::user::event::CreateUser, repository::user::UserRepository};
+
+        let repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(1), None),
+            FinePolicy::new(10, 500),
+        );
+        let user = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()))
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_ids = [
+            "9890736e-a4e4-461a-a77d-eac3517ef11b",
+            "f397b83a-dd2a-4a01-9e77-db1eea7de5b6",
+            "17afb850-c786-49c5-a303-a3a443a2212c",
+        ]
+        .map(|id| BookId::from_str(id).unwrap());
+
+        // 一般ユーザーは上限の1冊を超えて借りられないことを確認
+        repo.create(CreateCheckout::new(
+            book_ids[0],
+            None,
+            user.id,
+            Utc::now(),
+            user.id,
+        ))
+        .await?;
+        let res = repo
+            .create(CreateCheckout::new(
+                book_ids[1],
+                None,
+                user.id,
+                Utc::now(),
+                user.id,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 上限が設定されていない管理者は複数冊借りられることを確認
+        repo.create(CreateCheckout::new(
+            book_ids[1],
+            None,
+            admin_id,
+            Utc::now(),
+            admin_id,
+        ))
+        .await?;
+        repo.create(CreateCheckout::new(
+            book_ids[2],
+            None,
+            admin_id,
+            Utc::now(),
+            admin_id,
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_checkout_and_force_return_on_behalf(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        use crate::repository::user::UserRepositoryImpl;
+        use kernel::{model::user::event::CreateUser, repository::user::UserRepository};
+
+        let repo = checkout_repository(&pool);
+        let user = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()))
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+
+        // 管理者が代理で貸し出すと、借りたユーザーと操作したユーザーが別に記録されることを確認
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            user.id,
+            Utc::now(),
+            admin_id,
+        ))
+        .await?;
+        let checkout = repo.find_unreturned_by_user_id(user.id).await?.remove(0);
+        assert_eq!(checkout.checked_out_by, user.id);
+        assert_eq!(checkout.checkout_processed_by, admin_id);
+
+        // 借りたユーザー以外は通常の返却操作ができないことを確認
+        let res = repo
+            .update_returned(UpdateReturned::new(
+                checkout.id,
+                book_id,
+                admin_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 強制返却では借りたユーザー以外でも返却でき、返却操作を行ったユーザーが記録されることを確認
+        repo.force_returned(ForceReturned::new(
+            checkout.id,
+            book_id,
+            admin_id,
+            Utc::now(),
+        ))
+        .await?;
+        let history = repo.find_history_by_book_id(book_id).await?;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].checked_out_by, user.id);
+        assert_eq!(history[0].checkout_processed_by, admin_id);
+        assert_eq!(history[0].return_processed_by, Some(admin_id));
+
+        // 貸出中でない蔵書は強制返却できないことを確認
+        let res = repo
+            .force_returned(ForceReturned::new(
+                checkout.id,
+                book_id,
+                admin_id,
+                Utc::now(),
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_find_history_by_user_id(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = checkout_repository(&pool);
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let returned_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book_id = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let now = Utc::now();
+
+        // 10日前に借りて返却した蔵書と、現在借りている蔵書を用意する
+        repo.create(CreateCheckout::new(
+            returned_book_id,
+            None,
+            user_id,
+            now - Duration::days(10),
+            user_id,
+        ))
+        .await?;
+        let returned = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        repo.update_returned(UpdateReturned::new(
+            returned.id,
+            returned_book_id,
+            user_id,
+            now - Duration::days(5),
+        ))
+        .await?;
+        repo.create(CreateCheckout::new(book_id, None, user_id, now, user_id))
+            .await?;
+
+        // 貸出中・返却済みの両方が、貸出日時の新しい順に取得できることを確認
+        let options = |limit, offset, from| CheckoutHistoryOptions {
+            limit,
+            offset,
+            from,
+            to: None,
+        };
+        let history = repo
+            .find_history_by_user_id(user_id, options(20, 0, None))
+            .await?;
+        assert_eq!(history.total, Some(2));
+        assert_eq!(history.items[0].book.book_id, book_id);
+        assert!(history.items[0].returned_at.is_none());
+        assert_eq!(history.items[1].id, returned.id);
+        assert!(history.items[1].returned_at.is_some());
+
+        // ページネーションしても全体の件数が取得できることを確認
+        let history = repo
+            .find_history_by_user_id(user_id, options(1, 1, None))
+            .await?;
+        assert_eq!(history.total, Some(2));
+        assert_eq!(history.items.len(), 1);
+        assert_eq!(history.items[0].id, returned.id);
+
+        // 貸出日時の範囲で絞り込めることを確認
+        let history = repo
+            .find_history_by_user_id(user_id, options(20, 0, Some(now - Duration::days(1))))
+            .await?;
+        assert_eq!(history.total, Some(1));
+        assert_eq!(history.items[0].book.book_id, book_id);
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_report_lost_and_damaged(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        use crate::repository::{book::BookRepositoryImpl, fine::FineRepositoryImpl};
+        use kernel::repository::{book::BookRepository, fine::FineRepository};
+
+        let repo = checkout_repository(&pool);
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let fine_repo = FineRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let lost_book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let damaged_book_id = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let damaged_copy_id = BookCopyId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+
+        // 紛失を報告すると、貸出が履歴に移り、蔵書が紛失状態になることを確認
+        repo.create(CreateCheckout::new(
+            lost_book_id,
+            None,
+            user_id,
+            Utc::now(),
+            user_id,
+        ))
+        .await?;
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        repo.report_lost(ReportLost::new(
+            checkout.id,
+            lost_book_id,
+            user_id,
+            Utc::now(),
+            Some(300),
+        ))
+        .await?;
+        assert!(repo.find_unreturned_by_user_id(user_id).await?.is_empty());
+        let history = repo.find_history_by_book_id(lost_book_id).await?;
+        assert_eq!(
+            history[0].incident.as_ref().map(|i| i.kind),
+            Some(IncidentKind::Lost)
+        );
+        let book = book_repo.find_by_id(lost_book_id).await?.unwrap();
+        assert_eq!(book.copies[0].status, CopyStatus::Lost);
+
+        // 紛失した蔵書は借りられないことを確認
+        let res = repo
+            .create(CreateCheckout::new(
+                lost_book_id,
+                None,
+                user_id,
+                Utc::now(),
+                user_id,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 貸出中の蔵書は破損を報告できないことを確認
+        repo.create(CreateCheckout::new(
+            damaged_book_id,
+            None,
+            user_id,
+            Utc::now(),
+            user_id,
+        ))
+        .await?;
+        let res = repo
+            .report_damaged(ReportDamaged::new(
+                damaged_book_id,
+                damaged_copy_id,
+                user_id,
+                Utc::now(),
+                None,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却後に破損を報告すると、直近の貸出履歴に記録され、蔵書が破損状態になることを確認
+        let checkout = repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        repo.update_returned(UpdateReturned::new(
+            checkout.id,
+            damaged_book_id,
+            user_id,
+            Utc::now(),
+        ))
+        .await?;
+        repo.report_damaged(ReportDamaged::new(
+            damaged_book_id,
+            damaged_copy_id,
+            user_id,
+            Utc::now(),
+            Some(100),
+        ))
+        .await?;
+        let history = repo.find_history_by_book_id(damaged_book_id).await?;
+        assert_eq!(
+            history[0].incident.as_ref().map(|i| i.kind),
+            Some(IncidentKind::Damaged)
+        );
+        let book = book_repo.find_by_id(damaged_book_id).await?.unwrap();
+        assert_eq!(book.copies[0].status, CopyStatus::Damaged);
+
+        // 弁償金が罰金台帳に計上されていることを確認
+        let fines = fine_repo.find_by_user_id(user_id).await?;
+        assert_eq!(fines.balance, 400);
+        assert!(fines.items.iter().all(|f| f.kind == FineKind::Replacement));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_report_repaired(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        use crate::repository::{
+            book::BookRepositoryImpl, reservation::ReservationRepositoryImpl,
+            user::UserRepositoryImpl,
+        };
+        use kernel::{
+            model::{reservation::event::CreateReservation, user::event::CreateUser},
+            repository::{
+                book::BookRepository, reservation::ReservationRepository, user::UserRepository,
+            },
+        };
+
+        let repo = checkout_repository(&pool);
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let reservation_repo = ReservationRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+        );
+        let user = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()))
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?;
+        let admin_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let book_id = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let copy_id = BookCopyId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+
+        // 破損していない蔵書は修理を報告できないことを確認
+        let res = repo
+            .report_repaired(ReportRepaired::new(book_id, copy_id, admin_id, Utc::now()))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 貸出中に予約した蔵書が返却されて取り置かれたあと、破損を報告すると取り置きが解除されることを確認
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            admin_id,
+            Utc::now(),
+            admin_id,
+        ))
+        .await?;
+        reservation_repo
+            .create(CreateReservation::new(book_id, user.id, Utc::now()))
+            .await?;
+        let checkout = repo.find_unreturned_by_user_id(admin_id).await?.remove(0);
+        repo.update_returned(UpdateReturned::new(
+            checkout.id,
+            book_id,
+            admin_id,
+            Utc::now(),
+        ))
+        .await?;
+        let reservations = reservation_repo.find_by_book_id(book_id).await?;
+        assert!(reservations[0].pickup_expires_at.is_some());
+        repo.report_damaged(ReportDamaged::new(
+            book_id,
+            copy_id,
+            admin_id,
+            Utc::now(),
+            None,
+        ))
+        .await?;
+        let reservations = reservation_repo.find_by_book_id(book_id).await?;
+        assert!(reservations[0].pickup_expires_at.is_none());
+
+        // 破損した蔵書は借りられず、貸出可能な書誌として数えられないことを確認
+        let res = repo
+            .create(CreateCheckout::new(
+                book_id,
+                None,
+                user.id,
+                Utc::now(),
+                user.id,
+            ))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.available_copies(), 0);
+
+        // 修理を報告すると貸出可能に戻り、予約者のために取り置かれ、予約者が借りられることを確認
+        repo.report_repaired(ReportRepaired::new(book_id, copy_id, admin_id, Utc::now()))
+            .await?;
+        let book = book_repo.find_by_id(book_id).await?.unwrap();
+        assert_eq!(book.copies[0].status, CopyStatus::Available);
+        let reservations = reservation_repo.find_by_book_id(book_id).await?;
+        assert!(reservations[0].pickup_expires_at.is_some());
+        repo.create(CreateCheckout::new(
+            book_id,
+            None,
+            user.id,
+            Utc::now(),
+            user.id,
+        ))
+        .await?;
+
+        Ok(())
+    }
+}
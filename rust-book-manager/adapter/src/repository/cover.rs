@@ -0,0 +1,244 @@
+use async_trait::async_trait;
+use derive_new::new;
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, ImageFormat, ImageReader, Limits};
+use kernel::{
+    model::{
+        cover::{event::UploadCover, CoverFormat, CoverImage, CoverSize},
+        id::BookId,
+    },
+    repository::cover::CoverRepository,
+};
+use shared::error::{AppError, AppResult};
+use std::{io::Cursor, sync::Arc};
+
+use crate::{database::ConnectionPool, storage::ObjectStorage};
+
+// 読み込む画像の幅・高さの上限(ピクセル)
+// 小さなファイルでも展開すると巨大になる画像でメモリを使い果たさないようにする
+const MAX_COVER_DIMENSION: u32 = 8000;
+const THUMBNAIL_JPEG_QUALITY: u8 = 85;
+
+#[derive(new)]
+pub struct CoverRepositoryImpl {
+    db: ConnectionPool,
+    storage: Arc<dyn ObjectStorage>,
+}
+
+#[async_trait]
+impl CoverRepository for CoverRepositoryImpl {
+    async fn upload(&self, event: UploadCover) -> AppResult<()> {
+        let UploadCover {
+            book_id,
+            format,
+            bytes,
+            requested_user,
+        } = event;
+
+        // 表紙画像をアップロードできるのは書誌を登録したユーザーのみ
+        let exists = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM books WHERE book_id = $1 AND user_id = $2
+                ) AS "exists!"
+            "#,
+            book_id as _,
+            requested_user as _
+        )
+        .fetch_one(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if !exists {
+            return Err(AppError::EntityNotFound(
+                "specified book not found".into(),
+            ));
+        }
+
+        // 画像の展開と縮小には時間がかかるため、ブロッキング処理用のスレッドで行う
+        let (bytes, cover) = tokio::task::spawn_blocking(move || {
+            let cover = make_thumbnails(format, &bytes)?;
+            Ok::<_, AppError>((bytes, cover))
+        })
+        .await
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))??;
+
+        self.storage
+            .put(&cover_key(book_id, CoverSize::Original), &bytes)
+            .await?;
+        for (size, thumbnail) in &cover.thumbnails {
+            self.storage.put(&cover_key(book_id, *size), thumbnail).await?;
+        }
+
+        sqlx::query!(
+            r#"
+                INSERT INTO book_covers (book_id, content_type, width, height, uploaded_by)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (book_id) DO UPDATE SET
+                    content_type = EXCLUDED.content_type,
+                    width = EXCLUDED.width,
+                    height = EXCLUDED.height,
+                    uploaded_by = EXCLUDED.uploaded_by
+            "#,
+            book_id as _,
+            format.as_ref(),
+            cover.width as i32,
+            cover.height as i32,
+            requested_user as _
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        Ok(())
+    }
+
+    async fn find(&self, book_id: BookId, size: CoverSize) -> AppResult<Option<CoverImage>> {
+        let content_type = sqlx::query_scalar!(
+            r#"
+                SELECT content_type FROM book_covers WHERE book_id = $1
+            "#,
+            book_id as _
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let Some(content_type) = content_type else {
+            return Ok(None);
+        };
+        // サムネイルはアップロードされた画像の形式によらずJPEGで保存している
+        let content_type = match size {
+            CoverSize::Original => content_type,
+            CoverSize::Medium | CoverSize::Small => CoverFormat::Jpeg.as_ref().to_string(),
+        };
+
+        let bytes = self.storage.get(&cover_key(book_id, size)).await?;
+
+        Ok(bytes.map(|bytes| CoverImage {
+            content_type,
+            bytes,
+        }))
+    }
+}
+
+fn cover_key(book_id: BookId, size: CoverSize) -> String {
+    format!("covers/{book_id}/{}", size.as_ref())
+}
+
+struct Thumbnails {
+    width: u32,
+    height: u32,
+    thumbnails: Vec<(CoverSize, Vec<u8>)>,
+}
+
+// アップロードされた画像を指定された形式として読み込み、サムネイルを生成する
+// 読み込めない場合は、形式が誤っているか壊れた画像として422を返す
+fn make_thumbnails(format: CoverFormat, bytes: &[u8]) -> AppResult<Thumbnails> {
+    let format = match format {
+        CoverFormat::Jpeg => ImageFormat::Jpeg,
+        CoverFormat::Png => ImageFormat::Png,
+        CoverFormat::Webp => ImageFormat::WebP,
+    };
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_COVER_DIMENSION);
+    limits.max_image_height = Some(MAX_COVER_DIMENSION);
+    let mut reader = ImageReader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = reader
+        .decode()
+        .map_err(|e| AppError::UnprocessableEntity(format!("表紙画像を読み込めませんでした: {e}")))?;
+
+    let thumbnails = CoverSize::THUMBNAILS
+        .into_iter()
+        .filter_map(|size| size.max_dimension().map(|max| (size, max)))
+        .map(|(size, max)| Ok((size, encode_jpeg(&shrink(&image, max))?)))
+        .collect::<AppResult<Vec<_>>>()?;
+
+    Ok(Thumbnails {
+        width: image.width(),
+        height: image.height(),
+        thumbnails,
+    })
+}
+
+// 縦横比を保ったまま、幅・高さがmax以下になるよう縮小する。拡大はしない
+fn shrink(image: &DynamicImage, max: u32) -> DynamicImage {
+    if image.width() <= max && image.height() <= max {
+        image.clone()
+    } else {
+        image.thumbnail(max, max)
+    }
+}
+
+fn encode_jpeg(image: &DynamicImage) -> AppResult<Vec<u8>> {
+    let mut bytes = Vec::new();
+    JpegEncoder::new_with_quality(&mut bytes, THUMBNAIL_JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::local::LocalFileStorage;
+    use image::{ImageBuffer, Rgb};
+    use kernel::model::id::UserId;
+    use std::str::FromStr;
+
+    fn png(width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+        let image = ImageBuffer::from_pixel(width, height, Rgb([200u8, 80, 40]));
+        let mut bytes = Vec::new();
+        DynamicImage::ImageRgb8(image).write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+        Ok(bytes)
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_upload_cover(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("covers-{}", sqlx::types::Uuid::new_v4()));
+        let repo = CoverRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            Arc::new(LocalFileStorage::new(root.clone())),
+        );
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let owner = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let upload = |format, bytes, requested_user| UploadCover {
+            book_id,
+            format,
+            bytes,
+            requested_user,
+        };
+
+        assert!(repo.find(book_id, CoverSize::Original).await?.is_none());
+
+        // 書誌を登録したユーザー以外はアップロードできない
+        let res = repo.upload(upload(CoverFormat::Png, png(600, 900)?, UserId::new())).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        // 指定した形式として読み込めない画像は受け付けない
+        let res = repo.upload(upload(CoverFormat::Jpeg, png(600, 900)?, owner)).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        let original = png(600, 900)?;
+        repo.upload(upload(CoverFormat::Png, original.clone(), owner)).await?;
+
+        let cover = repo.find(book_id, CoverSize::Original).await?.unwrap();
+        assert_eq!(cover.content_type, "image/png");
+        assert_eq!(cover.bytes, original);
+
+        // サムネイルは縦横比を保ったまま縮小したJPEGになる
+        let cover = repo.find(book_id, CoverSize::Small).await?.unwrap();
+        assert_eq!(cover.content_type, "image/jpeg");
+        let thumbnail = image::load_from_memory_with_format(&cover.bytes, ImageFormat::Jpeg)?;
+        assert_eq!((thumbnail.width(), thumbnail.height()), (107, 160));
+
+        // 小さな画像は拡大しない
+        repo.upload(upload(CoverFormat::Png, png(100, 150)?, owner)).await?;
+        let cover = repo.find(book_id, CoverSize::Medium).await?.unwrap();
+        let thumbnail = image::load_from_memory_with_format(&cover.bytes, ImageFormat::Jpeg)?;
+        assert_eq!((thumbnail.width(), thumbnail.height()), (100, 150));
+
+        tokio::fs::remove_dir_all(root).await?;
+        Ok(())
+    }
+}
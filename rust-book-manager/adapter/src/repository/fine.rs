@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        fine::{event::CreateFine, Fine, UserFineBalance, UserFines},
+        id::{CheckoutId, FineId, UserId},
+    },
+    repository::fine::FineRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{
+    model::fine::{FineRow, UserFineBalanceRow},
+    ConnectionPool,
+};
+
+#[derive(new)]
+pub struct FineRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl FineRepository for FineRepositoryImpl {
+    // 管理者による請求・支払い・免除を台帳に記録する
+    async fn create(&self, event: CreateFine) -> AppResult<()> {
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO fines (fine_id, user_id, checkout_id, kind, amount, note, created_by, created_at)
+                SELECT $1, user_id, $3, $4, $5, $6, $7, $8
+                FROM users
+                WHERE user_id = $2;
+            "#,
+            FineId::new() as _,
+            event.user_id as _,
+            event.checkout_id as _,
+            event.kind.as_ref(),
+            event.amount,
+            event.note,
+            event.created_by as _,
+            event.created_at,
+        )
+        .execute(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(
+                "specified user not found".into(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    // ユーザーの罰金台帳と未払い残高を取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<UserFines> {
+        let items = sqlx::query_as!(
+            FineRow,
+            r#"
+                SELECT
+                    fine_id,
+                    user_id,
+                    checkout_id AS "checkout_id: CheckoutId",
+                    kind,
+                    amount,
+                    note,
+                    created_by AS "created_by: UserId",
+                    created_at
+                FROM fines
+                WHERE user_id = $1
+                ORDER BY created_at DESC;
+            "#,
+            user_id as _
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into_iter()
+        .map(Fine::try_from)
+        .collect::<AppResult<Vec<_>>>()?;
+
+        let balance = items.iter().map(|fine| fine.amount).sum();
+
+        Ok(UserFines {
+            user_id,
+            balance,
+            items,
+        })
+    }
+
+    // 未払い残高のあるユーザーの一覧を、残高の多い順に取得する
+    async fn find_unpaid_balances(&self) -> AppResult<Vec<UserFineBalance>> {
+        sqlx::query_as!(
+            UserFineBalanceRow,
+            r#"
+                SELECT
+                    u.user_id,
+                    u.name AS user_name,
+                    SUM(f.amount)::BIGINT AS "balance!"
+                FROM
+                    fines AS f
+                INNER JOIN
+                    users AS u
+                USING (user_id)
+                GROUP BY u.user_id, u.name
+                HAVING SUM(f.amount) > 0
+                ORDER BY SUM(f.amount) DESC, u.name ASC;
+            "#,
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map(|rows| rows.into_iter().map(UserFineBalance::from).collect())
+        .map_err(AppError::SpecificOperationError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::checkout::CheckoutRepositoryImpl;
+    use chrono::{Duration, Utc};
+    use kernel::{
+        model::{
+            checkout::{
+                event::{CreateCheckout, UpdateReturned},
+                LoanPolicy,
+            },
+            fine::{FineKind, FinePolicy},
+            id::BookId,
+        },
+        repository::checkout::CheckoutRepository,
+    };
+    use std::str::FromStr;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_overdue_fine_blocks_checkout(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            LoanPolicy::new(14, 2, 3, Some(5), None),
+            FinePolicy::new(10, 50),
+        );
+        let repo = FineRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // 返却期限を10日と1時間過ぎて返却すると、11日分の延滞料金が計上されることを確認
+        let checked_out_at = Utc::now() - Duration::days(24) - Duration::hours(1);
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, checked_out_at, user_id)).await?;
+        let checkout = checkout_repo.find_unreturned_by_user_id(user_id).await?.remove(0);
+        checkout_repo
+            .update_returned(UpdateReturned::new(checkout.id, book_id, user_id, Utc::now()))
+            .await?;
+
+        let fines = repo.find_by_user_id(user_id).await?;
+        assert_eq!(fines.balance, 110);
+        assert_eq!(fines.items.len(), 1);
+        assert_eq!(fines.items[0].kind, FineKind::Overdue);
+        assert_eq!(fines.items[0].checkout_id, Some(checkout.id));
+
+        // 未払い残高が上限を超えていると借りられないことを確認
+        let res = checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 免除されると再び借りられることを確認
+        repo.create(CreateFine {
+            user_id,
+            checkout_id: Some(checkout.id),
+            kind: FineKind::Waiver,
+            amount: -110,
+            note: "".into(),
+            created_by: user_id,
+            created_at: Utc::now(),
+        })
+        .await?;
+        assert_eq!(repo.find_by_user_id(user_id).await?.balance, 0);
+        assert!(repo.find_unpaid_balances().await?.is_empty());
+        checkout_repo.create(CreateCheckout::new(book_id, None, user_id, Utc::now(), user_id)).await?;
+
+        Ok(())
+    }
+}
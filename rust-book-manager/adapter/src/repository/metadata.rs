@@ -0,0 +1,109 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{book::isbn::Isbn, metadata::BookMetadata},
+    repository::metadata::BookMetadataRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{model::metadata::CatalogRecordRow, ConnectionPool};
+
+// 書誌データはimport-catalogコマンドでcatalog_records・catalog_authorsに取り込んでおく
+#[derive(new)]
+pub struct BookMetadataRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl BookMetadataRepository for BookMetadataRepositoryImpl {
+    // 著者キーから引ける著者名があればキーの順に連結し、なければ版に記載された著者名を使う
+    async fn find_by_isbn(&self, isbn: &Isbn) -> AppResult<Option<BookMetadata>> {
+        sqlx::query_as!(
+            CatalogRecordRow,
+            r#"
+                SELECT
+                    c.isbn,
+                    c.title,
+                    COALESCE(
+                        (
+                            SELECT string_agg(a.name, ', ' ORDER BY k.ord)
+                            FROM unnest(c.author_keys) WITH ORDINALITY AS k(author_key, ord)
+                            INNER JOIN catalog_authors AS a USING(author_key)
+                        ),
+                        c.author
+                    ) AS author,
+                    c.publisher,
+                    c.published_year,
+                    c.page_count,
+                    c.language
+                FROM catalog_records AS c
+                WHERE c.isbn = $1
+            "#,
+            isbn.as_str()
+        )
+        .fetch_optional(self.db.inner_ref())
+        .await
+        .map(|row| row.map(BookMetadata::from))
+        .map_err(AppError::SpecificOperationError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog::CatalogImporter;
+    use std::str::FromStr;
+
+    #[sqlx::test]
+    async fn test_import_and_find_by_isbn(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let db = ConnectionPool::new(pool);
+        let importer = CatalogImporter::new(db.clone());
+        let repo = BookMetadataRepositoryImpl::new(db);
+        let isbn = Isbn::from_str("9784065369579")?;
+
+        let dir = std::env::temp_dir().join(format!("catalog-{}", sqlx::types::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await?;
+        let editions = dir.join("editions.txt");
+        let authors = dir.join("authors.txt");
+        tokio::fs::write(
+            &editions,
+            r#"/type/edition	/books/OL1M	3	2024-01-01T00:00:00	{"title": "RustによるWebアプリケーション開発", "publishers": ["講談社"], "authors": [{"key": "/authors/OL1A"}, {"key": "/authors/OL2A"}], "by_statement": "豊田優貴, 松本健太郎, 吉川哲史", "isbn_13": ["9784065369579"]}"#,
+        )
+        .await?;
+        tokio::fs::write(
+            &authors,
+            [
+                r#"/type/author	/authors/OL2A	1	2024-01-01T00:00:00	{"name": "松本健太郎"}"#,
+                r#"/type/author	/authors/OL1A	1	2024-01-01T00:00:00	{"name": "豊田優貴"}"#,
+            ]
+            .join("\n"),
+        )
+        .await?;
+
+        // 著者を取り込む前は、版に記載された著者名を使う
+        let summary = importer.import(&editions).await?;
+        assert_eq!(summary.records, 1);
+        let metadata = repo.find_by_isbn(&isbn).await?.unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("RustによるWebアプリケーション開発"));
+        assert_eq!(metadata.author.as_deref(), Some("豊田優貴, 松本健太郎, 吉川哲史"));
+
+        // 別のダンプから取り込んだ著者名を、版が参照する順に連結する
+        let summary = importer.import(&authors).await?;
+        assert_eq!(summary.authors, 2);
+        let metadata = repo.find_by_isbn(&isbn).await?.unwrap();
+        assert_eq!(metadata.author.as_deref(), Some("豊田優貴, 松本健太郎"));
+        assert_eq!(metadata.publisher.as_deref(), Some("講談社"));
+
+        assert!(repo
+            .find_by_isbn(&Isbn::from_str("9784798061702")?)
+            .await?
+            .is_none());
+
+        // 読み込めないファイルはファイルの読み込みエラーになる
+        let res = importer.import(&dir.join("missing.txt")).await;
+        assert!(matches!(res, Err(AppError::StorageError(_))));
+
+        tokio::fs::remove_dir_all(dir).await?;
+        Ok(())
+    }
+}
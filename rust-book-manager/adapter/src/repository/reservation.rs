@@ -0,0 +1,524 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use kernel::{
+    model::{
+        book::CopyStatus,
+        checkout::LoanPolicy,
+        id::{BookId, UserId},
+        reservation::{
+            event::{CreateReservation, DeleteReservation},
+            Reservation,
+        },
+    },
+    repository::reservation::ReservationRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{
+    model::reservation::ReservationRow,
+    ConnectionPool,
+};
+
+#[derive(new)]
+pub struct ReservationRepositoryImpl {
+    db: ConnectionPool,
+    loan_policy: LoanPolicy,
+}
+
+#[async_trait]
+impl ReservationRepository for ReservationRepositoryImpl {
+    // 蔵書の予約を行う
+    async fn create(&self, event: CreateReservation) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        // トランザクション分離レベルをSERIALIZABLEに設定する。
+        self.set_transaction_serializable(&mut tx).await?;
+
+        // 事前のチェックとして、以下を調べる
+        // - 指定の書誌IDの書誌が存在し、アーカイブされていないか
+        // - 存在した場合、予約するユーザー自身がこの書誌の蔵書を借りていないか
+        // - 紛失していない蔵書が1冊以上あるか
+        {
+            let res = sqlx::query!(
+                r#"
+                    SELECT
+                        b.archived_at IS NOT NULL AS "archived!",
+                        EXISTS (
+                            SELECT 1 FROM checkouts AS c WHERE c.book_id = b.book_id AND c.user_id = $2
+                        ) AS "checked_out_by_user!",
+                        EXISTS (
+                            SELECT 1 FROM book_copies AS bc WHERE bc.book_id = b.book_id AND bc.status <> $3
+                        ) AS "has_copies!"
+                    FROM
+                        books AS b
+                    WHERE
+                        b.book_id = $1;
+                "#,
+                event.book_id as _,
+                event.reserved_by as _,
+                CopyStatus::Lost.as_ref(),
+            )
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?
+            // 指定した書籍が存在しない場合
+            .ok_or_else(|| {
+                AppError::EntityNotFound(format!(
+                    "書籍({})が見つかりませんでした。",
+                    event.book_id
+                ))
+            })?;
+
+            // 指定した書籍がアーカイブされている場合
+            if res.archived {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})はアーカイブされているため予約できません。",
+                    event.book_id
+                )));
+            }
+            // 予約するユーザー自身が借りている場合
+            if res.checked_out_by_user {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})はユーザー({})が借りているため予約できません。",
+                    event.book_id, event.reserved_by
+                )));
+            }
+            // すべての蔵書が紛失している場合
+            if !res.has_copies {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "書籍({})は紛失しているため予約できません。",
+                    event.book_id
+                )));
+            }
+        }
+
+        settle_reservations(&mut tx, Some(event.book_id), event.reserved_at, &self.loan_policy)
+            .await?;
+
+        // 取り置き中の予約者の分を除いても貸出可能な蔵書がある場合は、そのまま借りられるので予約できない
+        // また、同じユーザーが同じ蔵書を重複して予約することはできない
+        let state = sqlx::query!(
+            r#"
+                SELECT
+                    (
+                        SELECT COUNT(*)
+                        FROM book_copies AS bc
+                        WHERE
+                            bc.book_id = $1
+                            AND bc.status = $3
+                            AND NOT EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id)
+                    ) AS "available!",
+                    COUNT(r.reservation_id) FILTER (WHERE r.pickup_expires_at IS NOT NULL) AS "ready!",
+                    COUNT(r.reservation_id) FILTER (WHERE r.user_id = $2) AS "reserved_by_user!"
+                FROM
+                    reservations AS r
+                WHERE
+                    r.book_id = $1;
+            "#,
+            event.book_id as _,
+            event.reserved_by as _,
+            CopyStatus::Available.as_ref(),
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if state.available > state.ready {
+            return Err(AppError::UnprocessableEntity(format!(
+                "書籍({})は貸出可能なため予約できません。",
+                event.book_id
+            )));
+        }
+        if state.reserved_by_user > 0 {
+            return Err(AppError::UnprocessableEntity(format!(
+                "書籍({})はユーザー({})がすでに予約しています。",
+                event.book_id, event.reserved_by
+            )));
+        }
+
+        let res = sqlx::query!(
+            r#"
+                INSERT INTO reservations (book_id, user_id, reserved_at) VALUES ($1, $2, $3);
+            "#,
+            event.book_id as _,
+            event.reserved_by as _,
+            event.reserved_at,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::NoRowsAffectedError(
+                "No reservation record has been created".into(),
+            ));
+        }
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 予約を取り消す
+    async fn delete(&self, event: DeleteReservation) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        self.set_transaction_serializable(&mut tx).await?;
+
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM reservations WHERE reservation_id = $1 AND book_id = $2 AND user_id = $3;
+            "#,
+            event.reservation_id as _,
+            event.book_id as _,
+            event.requested_user as _,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(
+                "specified reservation not found".into(),
+            ));
+        }
+
+        // 取り置き中の予約が取り消された場合は、次の予約者に順番を回す
+        settle_reservations(&mut tx, Some(event.book_id), event.deleted_at, &self.loan_policy)
+            .await?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    // 蔵書に対する予約待ち行列を取得する
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Reservation>> {
+        let mut tx = self.db.begin().await?;
+
+        // 取り置き期限切れの予約を整理してから取得する
+        settle_reservations(&mut tx, Some(book_id), Utc::now(), &self.loan_policy).await?;
+
+        let reservations = sqlx::query_as!(
+            ReservationRow,
+            r#"
+                SELECT
+                    r.reservation_id,
+                    r.book_id,
+                    r.user_id,
+                    r.reserved_at,
+                    ROW_NUMBER() OVER (ORDER BY r.reserved_at) AS "position!",
+                    r.pickup_expires_at
+                FROM
+                    reservations AS r
+                WHERE
+                    r.book_id = $1
+                ORDER BY r.reserved_at ASC;
+            "#,
+            book_id as _
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into_iter()
+        .map(Reservation::from)
+        .collect();
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(reservations)
+    }
+
+    // ユーザーIDに紐づく予約を取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Reservation>> {
+        let mut tx = self.db.begin().await?;
+
+        settle_reservations(&mut tx, None, Utc::now(), &self.loan_policy).await?;
+
+        // 待ち行列内の順番を出すため、蔵書ごとに順位を付けてからユーザーIDで絞り込む
+        let reservations = sqlx::query_as!(
+            ReservationRow,
+            r#"
+                SELECT
+                    q.reservation_id AS "reservation_id!",
+                    q.book_id AS "book_id!",
+                    q.user_id AS "user_id!",
+                    q.reserved_at AS "reserved_at!",
+                    q.position AS "position!",
+                    q.pickup_expires_at
+                FROM (
+                    SELECT
+                        r.reservation_id,
+                        r.book_id,
+                        r.user_id,
+                        r.reserved_at,
+                        ROW_NUMBER() OVER (PARTITION BY r.book_id ORDER BY r.reserved_at) AS position,
+                        r.pickup_expires_at
+                    FROM
+                        reservations AS r
+                ) AS q
+                WHERE
+                    q.user_id = $1
+                ORDER BY q.reserved_at ASC;
+            "#,
+            user_id as _
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .into_iter()
+        .map(Reservation::from)
+        .collect();
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(reservations)
+    }
+}
+
+impl ReservationRepositoryImpl {
+    // create, deleteメソッドでのトランザクションを利用するにあたり
+    // トランザクション分離レベルをSERIALIZABLEにするために内部的に使うメソッド
+    async fn set_transaction_serializable(&self, tx: &mut sqlx::Transaction<'_, sqlx::Postgres>) -> AppResult<()> {
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE")
+            .execute(&mut **tx)
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+        Ok(())
+    }
+}
+
+// 予約待ち行列を指定の日時時点の状態に整理する。
+// - 取り置き期限を過ぎた予約を削除する
+// - 貸出可能な蔵書がある場合は、その冊数まで先頭の予約から順に取り置き期限を設定する
+// book_idがNoneの場合はすべての蔵書を対象とする。
+// 返却・貸出・予約の各操作と同じトランザクション内で呼び出すこと。
+pub(crate) async fn settle_reservations(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    book_id: Option<BookId>,
+    now: DateTime<Utc>,
+    loan_policy: &LoanPolicy,
+) -> AppResult<()> {
+    sqlx::query!(
+        r#"
+            DELETE FROM reservations
+            WHERE
+                ($1::uuid IS NULL OR book_id = $1)
+                AND pickup_expires_at < $2;
+        "#,
+        book_id as _,
+        now,
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    // 書誌ごとに、貸出可能な蔵書の冊数から取り置き中の予約の件数を引いた数だけ、
+    // 待ち行列の先頭から順に取り置き期限を設定する
+    sqlx::query!(
+        r#"
+            UPDATE reservations
+            SET pickup_expires_at = $2
+            WHERE reservation_id IN (
+                SELECT q.reservation_id
+                FROM (
+                    SELECT
+                        r.reservation_id,
+                        r.book_id,
+                        ROW_NUMBER() OVER (PARTITION BY r.book_id ORDER BY r.reserved_at) AS position
+                    FROM
+                        reservations AS r
+                    WHERE
+                        ($1::uuid IS NULL OR r.book_id = $1)
+                        AND r.pickup_expires_at IS NULL
+                ) AS q
+                WHERE q.position <= (
+                    SELECT COUNT(*)
+                    FROM book_copies AS bc
+                    WHERE
+                        bc.book_id = q.book_id
+                        AND bc.status = $3
+                        AND NOT EXISTS (SELECT 1 FROM checkouts AS c WHERE c.copy_id = bc.copy_id)
+                ) - (
+                    SELECT COUNT(*)
+                    FROM reservations AS ready
+                    WHERE ready.book_id = q.book_id AND ready.pickup_expires_at IS NOT NULL
+                )
+            );
+        "#,
+        book_id as _,
+        loan_policy.pickup_expires_at(now),
+        CopyStatus::Available.as_ref(),
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(AppError::SpecificOperationError)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::{
+        book::BookRepositoryImpl, checkout::CheckoutRepositoryImpl, user::UserRepositoryImpl,
+    };
+    use kernel::{
+        model::{
+            book::event::CreateBookCopy,
+            checkout::event::{CreateCheckout, RenewCheckout, UpdateReturned},
+            fine::FinePolicy,
+            user::event::CreateUser,
+        },
+        repository::{book::BookRepository, checkout::CheckoutRepository, user::UserRepository},
+    };
+    use std::str::FromStr;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_hold_queue(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let loan_policy = LoanPolicy::new(14, 2, 3, Some(5), None);
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            loan_policy,
+            FinePolicy::new(10, 500),
+        );
+        let repo = ReservationRepositoryImpl::new(ConnectionPool::new(pool.clone()), loan_policy);
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let borrower = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let holder = user_repo
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?
+            .id;
+
+        // 貸出可能な蔵書は予約できないことを確認
+        let res = repo.create(CreateReservation::new(book_id, holder, Utc::now())).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        checkout_repo.create(CreateCheckout::new(book_id, None, borrower, Utc::now(), borrower)).await?;
+        repo.create(CreateReservation::new(book_id, holder, Utc::now())).await?;
+
+        // 他のユーザーが予約している場合は延長できないことを確認
+        let checkout = checkout_repo.find_unreturned_by_user_id(borrower).await?.remove(0);
+        let res = checkout_repo
+            .renew(RenewCheckout::new(checkout.id, book_id, borrower, Utc::now()))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 返却されると先頭の予約者に取り置き期限が設定されることを確認
+        let returned_at = Utc::now();
+        checkout_repo
+            .update_returned(UpdateReturned::new(checkout.id, book_id, borrower, returned_at))
+            .await?;
+        let reservations = repo.find_by_user_id(holder).await?;
+        assert_eq!(reservations.len(), 1);
+        assert_eq!(reservations[0].position, 1);
+        assert!(reservations[0].pickup_expires_at.is_some());
+
+        // 取り置き中は予約者以外は借りられず、予約者が借りると予約が完了することを確認
+        let res = checkout_repo.create(CreateCheckout::new(book_id, None, borrower, Utc::now(), borrower)).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+        checkout_repo.create(CreateCheckout::new(book_id, None, holder, Utc::now(), holder)).await?;
+        assert!(repo.find_by_book_id(book_id).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_expired_hold_is_removed(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let loan_policy = LoanPolicy::new(14, 2, 3, Some(5), None);
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            loan_policy,
+            FinePolicy::new(10, 500),
+        );
+        let repo = ReservationRepositoryImpl::new(ConnectionPool::new(pool.clone()), loan_policy);
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let borrower = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let holder = user_repo
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?
+            .id;
+
+        let now = Utc::now();
+        checkout_repo.create(CreateCheckout::new(book_id, None, borrower, now, borrower)).await?;
+        repo.create(CreateReservation::new(book_id, holder, now)).await?;
+        let checkout = checkout_repo.find_unreturned_by_user_id(borrower).await?.remove(0);
+        checkout_repo
+            .update_returned(UpdateReturned::new(checkout.id, book_id, borrower, now))
+            .await?;
+
+        // 取り置き期限を過ぎると予約が削除され、誰でも借りられるようになることを確認
+        let after_pickup_window = now + chrono::Duration::days(4);
+        checkout_repo
+            .create(CreateCheckout::new(book_id, None, borrower, after_pickup_window, borrower))
+            .await?;
+        assert!(repo.find_by_book_id(book_id).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_reservation_completed_by_checkout(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let loan_policy = LoanPolicy::new(14, 2, 3, Some(5), None);
+        let checkout_repo = CheckoutRepositoryImpl::new(
+            ConnectionPool::new(pool.clone()),
+            loan_policy,
+            FinePolicy::new(10, 500),
+        );
+        let repo = ReservationRepositoryImpl::new(ConnectionPool::new(pool.clone()), loan_policy);
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+
+        let book_id = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let borrower = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+        let create_user = |name: &str, email: &str| {
+            user_repo.create(CreateUser {
+                name: name.into(),
+                email: email.into(),
+                password: "test_password".into(),
+            })
+        };
+        let holder = create_user("Test User", "test@example.com").await?.id;
+        let other = create_user("Other User", "other@example.com").await?.id;
+
+        // 貸出中の蔵書を2人が予約したあとで、蔵書を追加する
+        checkout_repo.create(CreateCheckout::new(book_id, None, borrower, Utc::now(), borrower)).await?;
+        repo.create(CreateReservation::new(book_id, holder, Utc::now())).await?;
+        repo.create(CreateReservation::new(book_id, other, Utc::now())).await?;
+        book_repo
+            .create_copy(CreateBookCopy {
+                book_id,
+                barcode: None,
+                requested_user: borrower,
+            })
+            .await?;
+
+        // 追加した蔵書は先頭の予約者のために取り置かれ、後ろの予約者は借りられないことを確認
+        let res = checkout_repo.create(CreateCheckout::new(book_id, None, other, Utc::now(), other)).await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 予約者が借りると、その予約者の予約だけが削除されることを確認
+        checkout_repo.create(CreateCheckout::new(book_id, None, holder, Utc::now(), holder)).await?;
+        assert!(repo.find_by_user_id(holder).await?.is_empty());
+        let reservations = repo.find_by_book_id(book_id).await?;
+        assert_eq!(reservations.len(), 1);
+        assert_eq!(reservations[0].reserved_by, other);
+        assert!(reservations[0].pickup_expires_at.is_none());
+
+        Ok(())
+    }
+}
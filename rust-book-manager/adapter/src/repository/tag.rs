@@ -0,0 +1,223 @@
+use async_trait::async_trait;
+use derive_new::new;
+use kernel::{
+    model::{
+        id::TagId,
+        tag::{
+            event::{AddBookTag, RemoveBookTag},
+            Tag,
+        },
+    },
+    repository::tag::TagRepository,
+};
+use shared::error::{AppError, AppResult};
+
+use crate::database::{model::tag::TagRow, ConnectionPool};
+
+#[derive(new)]
+pub struct TagRepositoryImpl {
+    db: ConnectionPool,
+}
+
+#[async_trait]
+impl TagRepository for TagRepositoryImpl {
+    // 付けられている書誌の多い順、同数であれば名前順に取得する
+    // アーカイブした書誌は一覧に表示しないため、書誌の数にも含めない
+    async fn find_all(&self) -> AppResult<Vec<Tag>> {
+        sqlx::query_as!(
+            TagRow,
+            r#"
+                SELECT
+                    t.tag_id AS "tag_id: TagId",
+                    t.name,
+                    COUNT(b.book_id) AS "book_count!"
+                FROM
+                    tags AS t
+                LEFT OUTER JOIN
+                    book_tags AS bt
+                USING (tag_id)
+                LEFT OUTER JOIN
+                    books AS b
+                ON b.book_id = bt.book_id AND b.archived_at IS NULL
+                GROUP BY t.tag_id, t.name
+                ORDER BY COUNT(b.book_id) DESC, lower(t.name) ASC;
+            "#,
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map(|rows| rows.into_iter().map(Tag::from).collect())
+        .map_err(AppError::SpecificOperationError)
+    }
+
+    async fn add_to_book(&self, event: AddBookTag) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let exists = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (SELECT 1 FROM books WHERE book_id = $1) AS "exists!"
+            "#,
+            event.book_id as _
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if !exists {
+            return Err(AppError::EntityNotFound(
+                "specified book not found".into(),
+            ));
+        }
+
+        // 大文字・小文字だけが異なるタグがすでにあれば、そのタグを使う
+        sqlx::query!(
+            r#"
+                INSERT INTO tags (name) VALUES ($1)
+                ON CONFLICT ((lower(name))) DO NOTHING
+            "#,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO book_tags (book_id, tag_id)
+                SELECT $1, tag_id FROM tags WHERE lower(name) = lower($2)
+                ON CONFLICT DO NOTHING
+            "#,
+            event.book_id as _,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+
+    async fn remove_from_book(&self, event: RemoveBookTag) -> AppResult<()> {
+        let mut tx = self.db.begin().await?;
+
+        let res = sqlx::query!(
+            r#"
+                DELETE FROM book_tags AS bt
+                USING tags AS t
+                WHERE bt.tag_id = t.tag_id
+                    AND bt.book_id = $1
+                    AND lower(t.name) = lower($2)
+            "#,
+            event.book_id as _,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        if res.rows_affected() < 1 {
+            return Err(AppError::EntityNotFound(
+                "specified book tag not found".into(),
+            ));
+        }
+
+        // どの書誌にも付いていないタグは一覧に残さない
+        sqlx::query!(
+            r#"
+                DELETE FROM tags AS t
+                WHERE lower(t.name) = lower($1)
+                    AND NOT EXISTS (SELECT 1 FROM book_tags AS bt WHERE bt.tag_id = t.tag_id)
+            "#,
+            event.name
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::book::BookRepositoryImpl;
+    use kernel::{
+        model::{
+            book::{BookListFilter, BookListOptions},
+            id::BookId,
+        },
+        repository::book::BookRepository,
+    };
+    use std::str::FromStr;
+
+    #[sqlx::test(fixtures("common", "book"))]
+    async fn test_book_tags(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = TagRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book_repo = BookRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let book1 = BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b")?;
+        let book2 = BookId::from_str("f397b83a-dd2a-4a01-9e77-db1eea7de5b6")?;
+        let add = |book_id, name: &str| AddBookTag {
+            book_id,
+            name: name.into(),
+        };
+
+        repo.add_to_book(add(book1, "Rust")).await?;
+        repo.add_to_book(add(book1, "入門")).await?;
+        // 大文字・小文字だけが異なるタグは同じタグとして扱い、同じタグを2回付けても1つになることを確認
+        repo.add_to_book(add(book2, "rust")).await?;
+        repo.add_to_book(add(book2, "RUST")).await?;
+
+        let tags = repo.find_all().await?;
+        let counts = tags
+            .iter()
+            .map(|tag| (tag.name.as_str(), tag.book_count))
+            .collect::<Vec<_>>();
+        assert_eq!(counts, vec![("Rust", 2), ("入門", 1)]);
+
+        let book = book_repo.find_by_id(book1).await?.unwrap();
+        assert_eq!(book.tags, vec!["Rust", "入門"]);
+
+        // タグで書誌一覧を絞り込めることを確認
+        let options = |tag: &str| BookListOptions {
+            limit: 20,
+            offset: 0,
+            cursor: None,
+            search: None,
+            filter: BookListFilter {
+                tag: Some(tag.into()),
+                ..Default::default()
+            },
+            sort: None,
+        };
+        let res = book_repo.find_all(options("RUST")).await?;
+        assert_eq!(res.total, Some(2));
+        let res = book_repo.find_all(options("入門")).await?;
+        assert_eq!(res.items.iter().map(|b| b.id).collect::<Vec<_>>(), vec![book1]);
+
+        // 外したタグがどの書誌にも付いていなければ、一覧から消えることを確認
+        repo.remove_from_book(RemoveBookTag {
+            book_id: book1,
+            name: "入門".into(),
+        })
+        .await?;
+        let tags = repo.find_all().await?;
+        assert_eq!(tags.len(), 1);
+
+        // 付いていないタグや存在しない書誌は404とする
+        let res = repo
+            .remove_from_book(RemoveBookTag {
+                book_id: book1,
+                name: "入門".into(),
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+        let res = repo.add_to_book(add(BookId::new(), "Rust")).await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        Ok(())
+    }
+}
@@ -1,17 +1,29 @@
 use async_trait::async_trait;
 use derive_new::new;
+use chrono::DateTime;
 use kernel::model::{
     id::UserId,
+    list::{CursorPosition, PaginatedList, SortDirection},
     user::{
-        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserProfile, UpdateUserRole},
+        User, UserListOptions,
     },
     role::Role,
 };
 use kernel::repository::user::UserRepository;
 use shared::error::{AppError, AppResult};
+use std::str::FromStr;
 
-use crate::database::{model::user::UserRow, ConnectionPool};
+use crate::database::{
+    model::{
+        list::{paginate_by_keyset, KeysetCursor},
+        user::{PaginatedUserRow, UserRow},
+    },
+    ConnectionPool,
+};
+
+// ユーザー一覧は登録日時の新しい順に固定なので、カーソルにはこの項目名を記録する
+const SORT_KEY: &str = "CreatedAt";
 
 #[derive(new)]
 pub struct UserRepositoryImpl {
@@ -51,8 +63,8 @@ impl UserRepository for UserRepositoryImpl {
         }
     }
 
-    async fn find_all(&self) -> AppResult<Vec<User>> {
-        let users = sqlx::query_as!(
+    async fn find_by_email(&self, email: &str) -> AppResult<Option<User>> {
+        sqlx::query_as!(
             UserRow,
             r#"
                 SELECT
@@ -64,17 +76,156 @@ impl UserRepository for UserRepositoryImpl {
                     u.updated_at
                 FROM users AS u
                 INNER JOIN roles AS r USING(role_id)
-                ORDER BY u.created_at DESC;
+                WHERE lower(u.email) = lower($1)
             "#,
+            email
         )
-        .fetch_all(self.db.inner_ref())
+        .fetch_optional(self.db.inner_ref())
         .await
         .map_err(AppError::SpecificOperationError)?
-        .into_iter()
-        .filter_map(|row| User::try_from(row).ok())
-        .collect();
-        
-        Ok(users)
+        .map(User::try_from)
+        .transpose()
+    }
+
+    async fn find_all(&self, options: UserListOptions) -> AppResult<PaginatedList<User>> {
+        let UserListOptions {
+            limit,
+            offset,
+            cursor,
+        } = options;
+
+        let Some(position) = cursor else {
+            let rows = sqlx::query_as!(
+                PaginatedUserRow,
+                r#"
+                    SELECT
+                        COUNT(*) OVER() AS "total!",
+                        u.user_id,
+                        u.name,
+                        u.email,
+                        r.name as role_name,
+                        u.created_at
+                    FROM users AS u
+                    INNER JOIN roles AS r USING(role_id)
+                    ORDER BY u.created_at DESC, u.user_id DESC
+                    LIMIT $1
+                    OFFSET $2;
+                "#,
+                limit,
+                offset
+            )
+            .fetch_all(self.db.inner_ref())
+            .await
+            .map_err(AppError::SpecificOperationError)?;
+
+            let total = rows.first().map(|r| r.total).unwrap_or_default();
+            let items = rows
+                .into_iter()
+                .map(User::try_from)
+                .collect::<AppResult<Vec<_>>>()?;
+
+            return Ok(PaginatedList {
+                total: Some(total),
+                // 全件を取得した場合は、取得した件数を上限とみなす
+                limit: limit.unwrap_or(total),
+                offset,
+                items,
+                next_cursor: None,
+                prev_cursor: None,
+            });
+        };
+
+        let limit = limit.ok_or_else(|| {
+            AppError::UnprocessableEntity("カーソル方式では取得件数を指定してください。".into())
+        })?;
+
+        // カーソル方式では、登録日時とユーザーIDの組をキーにしてカーソルの前後を取得する
+        let cursor = match &position {
+            CursorPosition::First => None,
+            CursorPosition::At(cursor) => {
+                let cursor = KeysetCursor::decode(cursor)?;
+                cursor.ensure_sorted_by(SORT_KEY, SortDirection::Desc)?;
+                Some(cursor)
+            }
+        };
+        let backward = cursor.as_ref().is_some_and(|c| c.backward);
+        let (cursor_id, cursor_created_at) = match &cursor {
+            Some(c) => (
+                Some(UserId::from_str(&c.id)?),
+                Some(
+                    c.value
+                        .parse::<i64>()
+                        .ok()
+                        .and_then(DateTime::from_timestamp_micros)
+                        .ok_or_else(|| AppError::UnprocessableEntity("カーソルが不正です。".into()))?,
+                ),
+            ),
+            None => (None, None),
+        };
+
+        let rows = sqlx::query_as!(
+            UserRow,
+            r#"
+                SELECT
+                    u.user_id,
+                    u.name,
+                    u.email,
+                    r.name as role_name,
+                    u.created_at,
+                    u.updated_at
+                FROM users AS u
+                INNER JOIN roles AS r USING(role_id)
+                WHERE
+                    $2::uuid IS NULL
+                    OR (CASE WHEN $4::bool
+                        THEN (u.created_at, u.user_id) > ($3::timestamptz, $2)
+                        ELSE (u.created_at, u.user_id) < ($3::timestamptz, $2) END)
+                ORDER BY
+                    CASE WHEN $4::bool THEN u.created_at END ASC,
+                    CASE WHEN NOT $4::bool THEN u.created_at END DESC,
+                    CASE WHEN $4::bool THEN u.user_id END ASC,
+                    CASE WHEN NOT $4::bool THEN u.user_id END DESC
+                LIMIT $1;
+            "#,
+            limit + 1,
+            cursor_id as _,
+            cursor_created_at,
+            // 新しい順に並べるため、前のページを取得する場合のみ昇順に読む
+            backward
+        )
+        .fetch_all(self.db.inner_ref())
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+
+        let (rows, next_cursor, prev_cursor) = paginate_by_keyset(
+            rows,
+            limit,
+            position == CursorPosition::First,
+            backward,
+            |row, backward| {
+                KeysetCursor {
+                    sort_key: SORT_KEY.into(),
+                    direction: SortDirection::Desc,
+                    backward,
+                    id: row.user_id.to_string(),
+                    value: row.created_at.timestamp_micros().to_string(),
+                }
+                .encode()
+            },
+        );
+        let items = rows
+            .into_iter()
+            .map(User::try_from)
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(PaginatedList {
+            total: None,
+            limit,
+            offset: 0,
+            items,
+            next_cursor,
+            prev_cursor,
+        })
     }
 
     async fn create(&self, event: CreateUser) -> AppResult<User> {
@@ -96,7 +247,13 @@ impl UserRepository for UserRepositoryImpl {
         )
         .execute(self.db.inner_ref())
         .await
-        .map_err(AppError::SpecificOperationError)?;
+        .map_err(|e| match e.as_database_error() {
+            // 大文字・小文字だけが異なるメールアドレスも、lower(email)の一意インデックスで検出する
+            Some(db_error) if db_error.is_unique_violation() => AppError::UnprocessableEntity(
+                format!("メールアドレス({})はすでに使われています。", event.email),
+            ),
+            _ => AppError::SpecificOperationError(e),
+        })?;
 
         if res.rows_affected() < 1 {
             return Err(AppError::NoRowsAffectedError(
@@ -171,6 +328,88 @@ impl UserRepository for UserRepositoryImpl {
         Ok(())
     }
 
+    async fn update_profile(&self, event: UpdateUserProfile) -> AppResult<User> {
+        let mut tx = self.db.begin().await?;
+
+        let current = sqlx::query!(
+            r#"
+                SELECT email, password_hash FROM users WHERE user_id = $1 FOR UPDATE
+            "#,
+            event.user_id as _,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?
+        .ok_or_else(|| AppError::EntityNotFound("Specified user not found".into()))?;
+
+        // メールアドレスはログインに使うため、変更する場合は現在のパスワードを検証する
+        if current.email != event.email {
+            let current_password = event.current_password.as_deref().ok_or_else(|| {
+                AppError::BadRequest(
+                    "メールアドレスを変更する場合は、現在のパスワードを指定してください。".into(),
+                )
+            })?;
+            verify_password(current_password, &current.password_hash)?;
+        }
+
+        // ログイン時はメールアドレスの大文字・小文字を区別しないため、重複もそれにあわせて調べる
+        let duplicated = sqlx::query_scalar!(
+            r#"
+                SELECT EXISTS (
+                    SELECT 1 FROM users WHERE lower(email) = lower($2) AND user_id <> $1
+                ) AS "duplicated!"
+            "#,
+            event.user_id as _,
+            event.email,
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(AppError::SpecificOperationError)?;
+        if duplicated {
+            return Err(AppError::UnprocessableEntity(format!(
+                "メールアドレス({})はすでに使われています。",
+                event.email
+            )));
+        }
+
+        let user = sqlx::query_as!(
+            UserRow,
+            r#"
+                WITH updated AS (
+                    UPDATE users SET name = $2, email = $3
+                    WHERE user_id = $1
+                    RETURNING user_id, name, email, role_id, created_at, updated_at
+                )
+                SELECT
+                    u.user_id,
+                    u.name,
+                    u.email,
+                    r.name as role_name,
+                    u.created_at,
+                    u.updated_at
+                FROM updated AS u
+                INNER JOIN roles AS r USING(role_id)
+            "#,
+            event.user_id as _,
+            event.name,
+            event.email,
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            // 同時に同じメールアドレスへ変更された場合は、lower(email)の一意インデックスで検出する
+            Some(db_error) if db_error.is_unique_violation() => AppError::UnprocessableEntity(
+                format!("メールアドレス({})はすでに使われています。", event.email),
+            ),
+            _ => AppError::SpecificOperationError(e),
+        })?
+        .ok_or_else(|| AppError::EntityNotFound("Specified user not found".into()))?;
+
+        tx.commit().await.map_err(AppError::TransactionError)?;
+
+        User::try_from(user)
+    }
+
     async fn delete(&self, event: DeleteUser) -> AppResult<()> {
         let res = sqlx::query!(
             r#"
@@ -203,4 +442,168 @@ fn verify_password(password: &str, hash: &str) -> AppResult<()> {
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_create_with_duplicated_email(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+
+        // 大文字・小文字だけが異なるメールアドレスのユーザーは追加できないことを確認
+        let res = repo
+            .create(CreateUser {
+                name: "Eleazar Fig".into(),
+                email: "Eleazar.Fig@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_find_all(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        for i in 1..=4 {
+            repo.create(CreateUser {
+                name: format!("User {i}"),
+                email: format!("user{i}@example.com"),
+                password: "test_password".into(),
+            })
+            .await?;
+        }
+        let options = |limit: Option<i64>, offset: i64, cursor: Option<CursorPosition>| {
+            UserListOptions {
+                limit,
+                offset,
+                cursor,
+            }
+        };
+        let ids = |list: &PaginatedList<User>| list.items.iter().map(|u| u.id).collect::<Vec<_>>();
+
+        // 件数を指定しない場合は、全件が登録日時の新しい順に返ることを確認
+        let all = repo.find_all(options(None, 0, None)).await?;
+        assert_eq!(all.total, Some(5));
+        assert_eq!(all.limit, 5);
+        assert_eq!(all.items.len(), 5);
+        assert_eq!(all.items[4].name, "Eleazar Fig");
+        let all = ids(&all);
+
+        // オフセット方式で、件数と位置を指定して取得できることを確認
+        let res = repo.find_all(options(Some(2), 1, None)).await?;
+        assert_eq!(res.total, Some(5));
+        assert_eq!(ids(&res), all[1..3]);
+        assert!(res.next_cursor.is_none() && res.prev_cursor.is_none());
+        let res = repo.find_all(options(Some(2), 4, None)).await?;
+        assert_eq!(ids(&res), all[4..]);
+
+        // カーソル方式で、次のページを末尾までたどれることを確認
+        let page1 = repo
+            .find_all(options(Some(2), 0, Some(CursorPosition::First)))
+            .await?;
+        assert_eq!(page1.total, None);
+        assert_eq!(ids(&page1), all[0..2]);
+        assert!(page1.prev_cursor.is_none());
+        let page2 = repo
+            .find_all(options(Some(2), 0, page1.next_cursor.map(CursorPosition::At)))
+            .await?;
+        assert_eq!(ids(&page2), all[2..4]);
+        let page3 = repo
+            .find_all(options(Some(2), 0, page2.next_cursor.map(CursorPosition::At)))
+            .await?;
+        assert_eq!(ids(&page3), all[4..]);
+        assert!(page3.next_cursor.is_none());
+
+        // 前のページのカーソルで、さかのぼって取得できることを確認
+        let res = repo
+            .find_all(options(Some(2), 0, page3.prev_cursor.map(CursorPosition::At)))
+            .await?;
+        assert_eq!(ids(&res), all[2..4]);
+        assert!(res.next_cursor.is_some());
+        let res = repo
+            .find_all(options(Some(2), 0, res.prev_cursor.map(CursorPosition::At)))
+            .await?;
+        assert_eq!(ids(&res), all[0..2]);
+        assert!(res.prev_cursor.is_none());
+
+        // カーソル方式では件数の指定が必要なことを確認
+        let res = repo
+            .find_all(options(None, 0, Some(CursorPosition::First)))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        Ok(())
+    }
+
+    #[sqlx::test(fixtures("common"))]
+    async fn test_update_profile(pool: sqlx::PgPool) -> anyhow::Result<()> {
+        let repo = UserRepositoryImpl::new(ConnectionPool::new(pool.clone()));
+        let user_id = UserId::from_str("5b4c96ac-316a-4bee-8e69-cac5eb84ff4c")?;
+
+        // メールアドレスを変えなければ、現在のパスワードなしで名前を更新できることを確認
+        let user = repo
+            .update_profile(UpdateUserProfile {
+                user_id,
+                name: "Eleazar Fig Jr.".into(),
+                email: "eleazar.fig@example.com".into(),
+                current_password: None,
+            })
+            .await?;
+        assert_eq!(user.name, "Eleazar Fig Jr.");
+        assert_eq!(user.role, Role::Admin);
+
+        let user = repo
+            .create(CreateUser {
+                name: "Test User".into(),
+                email: "test.user@example.com".into(),
+                password: "test_password".into(),
+            })
+            .await?;
+        let update_email = |email: &str, current_password: Option<&str>| UpdateUserProfile {
+            user_id: user.id,
+            name: user.name.clone(),
+            email: email.into(),
+            current_password: current_password.map(String::from),
+        };
+
+        // メールアドレスの変更には、現在のパスワードが必要であることを確認
+        let res = repo
+            .update_profile(update_email("new.user@example.com", None))
+            .await;
+        assert!(matches!(res, Err(AppError::BadRequest(_))));
+        let res = repo
+            .update_profile(update_email("new.user@example.com", Some("wrong_password")))
+            .await;
+        assert!(matches!(res, Err(AppError::UnauthenticatedError)));
+
+        let updated = repo
+            .update_profile(update_email("new.user@example.com", Some("test_password")))
+            .await?;
+        assert_eq!(updated.email, "new.user@example.com");
+        let found = repo.find_by_email("New.User@example.com").await?;
+        assert_eq!(found.map(|u| u.id), Some(user.id));
+
+        // 大文字・小文字だけが異なる他のユーザーのメールアドレスには変更できないことを確認
+        let res = repo
+            .update_profile(update_email("Eleazar.Fig@example.com", Some("test_password")))
+            .await;
+        assert!(matches!(res, Err(AppError::UnprocessableEntity(_))));
+
+        // 存在しないユーザーは更新できないことを確認
+        let res = repo
+            .update_profile(UpdateUserProfile {
+                user_id: UserId::new(),
+                name: "Nobody".into(),
+                email: "nobody@example.com".into(),
+                current_password: None,
+            })
+            .await;
+        assert!(matches!(res, Err(AppError::EntityNotFound(_))));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use shared::error::{AppError, AppResult};
+use std::{
+    io::{Error, ErrorKind},
+    path::{Component, Path, PathBuf},
+};
+
+use super::ObjectStorage;
+
+// ローカルのファイルシステムに保存するストレージ
+// keyはrootからの相対パスとして扱う
+pub struct LocalFileStorage {
+    root: PathBuf,
+}
+
+impl LocalFileStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    // rootの外を指すkeyは受け付けない
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        let relative = Path::new(key);
+        let is_normal = relative.components().count() > 0
+            && relative
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+        if !is_normal {
+            return Err(AppError::StorageError(Error::new(
+                ErrorKind::InvalidInput,
+                format!("不正なキーです: {key}"),
+            )));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl ObjectStorage for LocalFileStorage {
+    async fn put(&self, key: &str, bytes: &[u8]) -> AppResult<()> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        // 書き込み途中のファイルを読まれないよう、一時ファイルに書いてから置き換える
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        tokio::fs::write(&temp, bytes).await?;
+        tokio::fs::rename(&temp, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>> {
+        match tokio::fs::read(self.path(key)?).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_put_and_get() -> anyhow::Result<()> {
+        let root = std::env::temp_dir().join(format!("local-storage-{}", sqlx::types::Uuid::new_v4()));
+        let storage = LocalFileStorage::new(root.clone());
+
+        assert_eq!(storage.get("covers/a/original").await?, None);
+        storage.put("covers/a/original", b"first").await?;
+        storage.put("covers/a/original", b"second").await?;
+        assert_eq!(storage.get("covers/a/original").await?, Some(b"second".to_vec()));
+
+        // rootの外を指すkeyは読み書きできない
+        assert!(storage.put("../escape", b"").await.is_err());
+        assert!(storage.get("/etc/passwd").await.is_err());
+
+        tokio::fs::remove_dir_all(root).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+pub mod local;
+
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+// 表紙画像などのファイルを保存するストレージ
+// keyは'/'区切りのパスで、実装ごとにファイルパスやオブジェクトストレージのキーに対応付ける
+#[async_trait]
+pub trait ObjectStorage: Send + Sync {
+    // 同じkeyのファイルがすでにある場合は置き換える
+    async fn put(&self, key: &str, bytes: &[u8]) -> AppResult<()>;
+
+    // ファイルがない場合はNoneを返す
+    async fn get(&self, key: &str) -> AppResult<Option<Vec<u8>>>;
+}
@@ -1,18 +1,39 @@
 use axum::{
+    body::{Body, Bytes},
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
+use axum_extra::{
+    headers::{ETag, HeaderMapExt, IfMatch},
+    TypedHeader,
+};
+use futures::{stream, StreamExt};
 use garde::Validate;
 use registry::AppRegistry;
-use kernel::model::{book::event::DeleteBook, id::BookId};
+use kernel::model::{
+    book::{
+        event::{ArchiveBook, CreateBook, DeleteBookCopy, RestoreBook},
+        Book,
+    },
+    id::{BookCopyId, BookId, UserId},
+};
 use shared::error::{AppError, AppResult};
+use std::collections::HashMap;
 
 use crate::{
     extractor::AuthorizedUser,
-    model::book::{
-        BookListQuery, BookResponse, CreateBookRequest, PaginatedBookResponse,
-        UpdateBookRequest, UpdateBookRequestWithIds,
+    model::{
+        book::{
+            book_etag, BookListQuery, BookResponse, CreateBookCopyRequest, CreateBookCopyRequestWithIds,
+            CreateBookRequest, DuplicateBookResponse, PaginatedBookResponse, PatchBookRequest,
+            UpdateBookRequest, UpdateBookRequestWithIds,
+        },
+        book_export::BookExportQuery,
+        book_import::{
+            parse_book_csv, BookImportErrorResponse, BookImportRecord, BookImportResponse,
+        },
     },
 };
 
@@ -20,14 +41,152 @@ pub async fn register_book(
     user: AuthorizedUser,
     State(registry): State<AppRegistry>,
     Json(req): Json<CreateBookRequest>,
-) -> Result<StatusCode, AppError> {
+) -> AppResult<Response> {
     req.validate()?;
 
+    let confirm_duplicate = req.confirm_duplicate;
+    let create_book = CreateBook::try_from(req)?;
+
+    // 重複の可能性がある書誌があれば、確認済みでない限り登録せずにそのIDを返す
+    if !confirm_duplicate {
+        let existing_book_ids = registry
+            .book_repository()
+            .find_duplicates(&create_book)
+            .await?;
+        if !existing_book_ids.is_empty() {
+            return Ok((
+                StatusCode::CONFLICT,
+                Json(DuplicateBookResponse { existing_book_ids }),
+            )
+                .into_response());
+        }
+    }
+
     registry
         .book_repository()
-        .create(req.into(), user.id())
-        .await
-        .map(|_| StatusCode::CREATED)
+        .create(create_book, user.id())
+        .await?;
+
+    Ok(StatusCode::CREATED.into_response())
+}
+
+// 全書誌を所有者と蔵書の貸出状況つきで、指定した形式で出力する
+// 書誌を少しずつ取得しながら送り出すため、書誌の件数によらず全件をメモリに読み込むことはない
+pub async fn export_books(
+    user: AuthorizedUser,
+    Query(query): Query<BookExportQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let format = query.format;
+    let books = registry
+        .book_repository()
+        .stream_all()
+        .map(move |book| book.and_then(|book| format.encode(book)));
+    let body = stream::once(async move { format.header() })
+        .chain(books)
+        .chain(stream::once(async move { Ok(format.footer()) }));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", format.file_name()),
+            ),
+        ],
+        Body::from_stream(body),
+    )
+        .into_response())
+}
+
+// CSVで複数の書誌をまとめて登録する
+// 各行をCreateBookRequestと同じ規則で検証し、問題のない行だけを1つのトランザクションで登録する
+// 問題のあった行は登録せず、行番号とその理由を返す。重複の確認は行わない
+pub async fn import_books(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    body: Bytes,
+) -> AppResult<Json<BookImportResponse>> {
+    let records = parse_book_csv(&body)?;
+
+    // 同じメールアドレスのユーザーを何度も引かないよう、引いた結果を保持しておく
+    let mut owners = HashMap::new();
+    let mut books = Vec::new();
+    let mut errors = Vec::new();
+    for (row, record) in records {
+        let result = match record {
+            Ok(record) => import_record(&user, &registry, &mut owners, record).await?,
+            Err(message) => Err(vec![message]),
+        };
+        match result {
+            Ok(book) => books.push(book),
+            Err(messages) => errors.push(BookImportErrorResponse { row, messages }),
+        }
+    }
+
+    let imported = books.len();
+    if !books.is_empty() {
+        registry.book_repository().create_many(books).await?;
+    }
+
+    Ok(Json(BookImportResponse { imported, errors }))
+}
+
+// CSVの1行を検証し、登録する書誌と所有者に変換する
+// 行に問題がある場合はその理由をErrとして返す
+async fn import_record(
+    user: &AuthorizedUser,
+    registry: &AppRegistry,
+    owners: &mut HashMap<String, Option<UserId>>,
+    record: BookImportRecord,
+) -> AppResult<Result<(CreateBook, UserId), Vec<String>>> {
+    let owner_email = record.owner_email.clone();
+    let req = CreateBookRequest::from(record);
+    if let Err(report) = req.validate() {
+        return Ok(Err(report
+            .iter()
+            .map(|(path, error)| format!("{path}: {error}"))
+            .collect()));
+    }
+    let create_book = match CreateBook::try_from(req) {
+        Ok(create_book) => create_book,
+        Err(e) => return Ok(Err(vec![e.to_string()])),
+    };
+
+    let owner = match owner_email {
+        None => user.id(),
+        Some(email) => {
+            let key = email.to_lowercase();
+            let owner = match owners.get(&key) {
+                Some(owner) => *owner,
+                None => {
+                    let owner = registry
+                        .user_repository()
+                        .find_by_email(&email)
+                        .await?
+                        .map(|u| u.id);
+                    owners.insert(key, owner);
+                    owner
+                }
+            };
+            match owner {
+                None => return Ok(Err(vec![format!("owner_email: {email} のユーザーが見つかりません")])),
+                // 他のユーザーを所有者として登録できるのは管理者のみ
+                Some(owner) if owner != user.id() && !user.is_admin() => {
+                    return Ok(Err(vec![
+                        "owner_email: 他のユーザーを所有者にできるのは管理者のみです".into(),
+                    ]))
+                }
+                Some(owner) => owner,
+            }
+        }
+    };
+
+    Ok(Ok((create_book, owner)))
 }
 
 pub async fn show_book_list(
@@ -55,48 +214,172 @@ pub async fn show_book(
     _user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
-) -> AppResult<Json<BookResponse>> {
+) -> AppResult<(TypedHeader<ETag>, Json<BookResponse>)> {
     tracing::info!("ここにログを追加した");
-    registry
+    let book = registry
         .book_repository()
         .find_by_id(book_id)
-        .await
-        .and_then(|bc| match bc {
-            Some(bc) => Ok(Json(bc.into())),
-            None => Err(AppError::EntityNotFound("not found".into())),
-        })
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("not found".into()))?;
+
+    Ok((TypedHeader(book_etag(book.version)?), Json(book.into())))
 }
 
+// ほかのユーザーの更新を上書きしないよう、show_bookで返したETagをIf-Matchに指定させる
+// 更新できた場合は、更新後の書誌のETagを返す
 pub async fn update_book(
     user: AuthorizedUser,
     Path(book_id): Path<BookId>,
+    headers: HeaderMap,
     State(registry): State<AppRegistry>,
     Json(req): Json<UpdateBookRequest>,
-) -> AppResult<StatusCode> {
+) -> AppResult<(TypedHeader<ETag>, StatusCode)> {
+    req.validate()?;
+
+    // If-Matchがない場合もIfMatchとしては解釈できてしまうため、ヘッダーの有無を先に調べる
+    if !headers.contains_key(header::IF_MATCH) {
+        return Err(AppError::PreconditionRequired);
+    }
+    let book = registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("specified book not found".into()))?;
+    check_if_match(&headers, &book)?;
+
+    let update_book = UpdateBookRequestWithIds::new(book_id, user.id(), book.version, req);
+
+    registry
+        .book_repository()
+        .update(update_book.try_into()?)
+        .await?;
+
+    Ok((TypedHeader(book_etag(book.version + 1)?), StatusCode::OK))
+}
+
+/// JSON Merge Patch(RFC 7396)で書誌の一部のフィールドだけを更新する
+/// 指定しなかったフィールドはクライアントが見ていない版の値のまま残るため、update_bookと同じくIf-Matchを必須とする
+pub async fn patch_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    headers: HeaderMap,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<PatchBookRequest>,
+) -> AppResult<(TypedHeader<ETag>, StatusCode)> {
+    if !headers.contains_key(header::IF_MATCH) {
+        return Err(AppError::PreconditionRequired);
+    }
+    let book = registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("specified book not found".into()))?;
+    check_if_match(&headers, &book)?;
+
+    let version = book.version;
+    let req = req.apply(book)?;
     req.validate()?;
 
-    let update_book = UpdateBookRequestWithIds::new(book_id, user.id(), req);
+    let update_book = UpdateBookRequestWithIds::new(book_id, user.id(), version, req);
 
     registry
         .book_repository()
-        .update(update_book.into())
+        .update(update_book.try_into()?)
+        .await?;
+
+    Ok((TypedHeader(book_etag(version + 1)?), StatusCode::OK))
+}
+
+// If-Matchが書誌の現在のETagと一致するかを調べる
+// IfMatchとして解釈する際には形式の不正なETagが読み飛ばされるため、形式は先に調べる
+fn check_if_match(headers: &HeaderMap, book: &Book) -> AppResult<()> {
+    let well_formed = headers.get_all(header::IF_MATCH).iter().all(|value| {
+        value.to_str().is_ok_and(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .all(|tag| tag == "*" || tag.parse::<ETag>().is_ok())
+        })
+    });
+    let if_match = headers
+        .typed_get::<IfMatch>()
+        .filter(|_| well_formed)
+        .ok_or_else(|| AppError::BadRequest("If-Matchの形式が不正です。".into()))?;
+    if !if_match.precondition_passes(&book_etag(book.version)?) {
+        return Err(AppError::PreconditionFailed(format!(
+            "書籍({})は更新されています(版番号: {})。",
+            book.id, book.version
+        )));
+    }
+    Ok(())
+}
+
+// 書誌は削除せずにアーカイブし、貸出履歴などはそのまま残す
+pub async fn delete_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let archive_book = ArchiveBook {
+        book_id,
+        requested_user: user.id(),
+    };
+
+    registry
+        .book_repository()
+        .archive(archive_book)
         .await
         .map(|_| StatusCode::OK)
 }
 
-pub async fn delete_book(
+// アーカイブした書誌を元に戻す。管理者のみが行える
+pub async fn restore_book(
     user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
 ) -> AppResult<StatusCode> {
-    let delete_book = DeleteBook {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .book_repository()
+        .restore(RestoreBook { book_id })
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+pub async fn register_book_copy(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateBookCopyRequest>,
+) -> AppResult<StatusCode> {
+    req.validate()?;
+
+    let create_copy = CreateBookCopyRequestWithIds::new(book_id, user.id(), req);
+
+    registry
+        .book_repository()
+        .create_copy(create_copy.into())
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+pub async fn delete_book_copy(
+    user: AuthorizedUser,
+    Path((book_id, copy_id)): Path<(BookId, BookCopyId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let delete_copy = DeleteBookCopy {
         book_id,
+        copy_id,
         requested_user: user.id(),
     };
 
     registry
         .book_repository()
-        .delete(delete_book)
+        .delete_copy(delete_copy)
         .await
         .map(|_| StatusCode::OK)
 }
@@ -1,22 +1,41 @@
-use crate::{extractor::AuthorizedUser, model::checkout::CheckoutsResponse};
+use crate::{
+    extractor::AuthorizedUser,
+    model::checkout::{
+        CheckoutOnBehalfRequest, CheckoutRequest, CheckoutsResponse, OverdueCheckoutsResponse, ReportIncidentRequest,
+    },
+};
 use axum::{
+    body::Bytes,
     extract::{Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
     Json,
 };
 use kernel::model::{
-    checkout::event::{CreateCheckout, UpdateReturned},
-    id::{BookId, CheckoutId},
+    checkout::event::{
+        CreateCheckout, ForceReturned, RenewCheckout, ReportDamaged, ReportLost, ReportRepaired,
+        UpdateReturned,
+    },
+    id::{BookCopyId, BookId, CheckoutId},
 };
+use garde::Validate;
 use registry::AppRegistry;
-use shared::error::AppResult;
+use shared::error::{AppError, AppResult};
 
 pub async fn checkout_book(
     user: AuthorizedUser,
     Path(book_id): Path<BookId>,
     State(registry): State<AppRegistry>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> AppResult<StatusCode> {
-    let create_checkout_history = CreateCheckout::new(book_id, user.id(), chrono::Utc::now());
+    let req = parse_checkout_request(&headers, &body)?;
+    let create_checkout_history = CreateCheckout::new(
+        book_id,
+        req.copy_id,
+        user.id(),
+        chrono::Utc::now(),
+        user.id(),
+    );
 
     registry
         .checkout_repository()
@@ -25,6 +44,28 @@ pub async fn checkout_book(
         .map(|_| StatusCode::CREATED)
 }
 
+// 本文を省略した場合は蔵書の指定がないものとして扱う
+// 本文がある場合はJSONとして解釈できなければ、蔵書の指定を省略したものとはみなさずエラーにする
+fn parse_checkout_request(headers: &HeaderMap, body: &Bytes) -> AppResult<CheckoutRequest> {
+    if body.is_empty() {
+        return Ok(CheckoutRequest::default());
+    }
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .is_some_and(|v| v == "application/json" || v.ends_with("+json"));
+    if !is_json {
+        return Err(AppError::BadRequest(
+            "Content-Typeにはapplication/jsonを指定してください。".into(),
+        ));
+    }
+    Json::<CheckoutRequest>::from_bytes(body)
+        .map(|Json(req)| req)
+        .map_err(|e| AppError::BadRequest(e.body_text()))
+}
+
 pub async fn return_book(
     user: AuthorizedUser,
     Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
@@ -39,6 +80,128 @@ pub async fn return_book(
         .map(|_| StatusCode::OK)
 }
 
+// 指定のユーザーに代わって貸出操作を行う(Admin only)
+pub async fn checkout_book_on_behalf(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CheckoutOnBehalfRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let create_checkout_history =
+        CreateCheckout::new(book_id, req.copy_id, req.user_id, chrono::Utc::now(), user.id());
+
+    registry
+        .checkout_repository()
+        .create(create_checkout_history)
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+// 借りたユーザーに関わらず返却操作を行う(Admin only)
+pub async fn force_return_book(
+    user: AuthorizedUser,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let force_returned = ForceReturned::new(checkout_id, book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .checkout_repository()
+        .force_returned(force_returned)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+// 貸出中の蔵書の紛失を報告する(Admin only)
+pub async fn report_lost(
+    user: AuthorizedUser,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<ReportIncidentRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+    req.validate()?;
+
+    let report_lost = ReportLost::new(
+        checkout_id,
+        book_id,
+        user.id(),
+        chrono::Utc::now(),
+        req.replacement_fee,
+    );
+
+    registry
+        .checkout_repository()
+        .report_lost(report_lost)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+// 返却済みの蔵書の破損を報告する(Admin only)
+pub async fn report_damaged(
+    user: AuthorizedUser,
+    Path((book_id, copy_id)): Path<(BookId, BookCopyId)>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<ReportIncidentRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+    req.validate()?;
+
+    let report_damaged =
+        ReportDamaged::new(book_id, copy_id, user.id(), chrono::Utc::now(), req.replacement_fee);
+
+    registry
+        .checkout_repository()
+        .report_damaged(report_damaged)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+// 破損した蔵書の修理を報告し、貸出可能な状態に戻す(Admin only)
+pub async fn report_repaired(
+    user: AuthorizedUser,
+    Path((book_id, copy_id)): Path<(BookId, BookCopyId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    let report_repaired = ReportRepaired::new(book_id, copy_id, user.id(), chrono::Utc::now());
+
+    registry
+        .checkout_repository()
+        .report_repaired(report_repaired)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+pub async fn renew_checkout(
+    user: AuthorizedUser,
+    Path((book_id, checkout_id)): Path<(BookId, CheckoutId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let renew_checkout = RenewCheckout::new(checkout_id, book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .checkout_repository()
+        .renew(renew_checkout)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
 pub async fn show_checked_out_list(
     _user: AuthorizedUser,
     State(registry): State<AppRegistry>,
@@ -51,6 +214,23 @@ pub async fn show_checked_out_list(
         .map(Json)
 }
 
+// 返却期限を過ぎた貸出の一覧をユーザーごとに取得する(Admin only)
+pub async fn show_overdue_list(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<OverdueCheckoutsResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .checkout_repository()
+        .find_overdue_all(chrono::Utc::now())
+        .await
+        .map(OverdueCheckoutsResponse::from)
+        .map(Json)
+}
+
 pub async fn checkout_history(
     _user: AuthorizedUser,
     Path(book_id): Path<BookId>,
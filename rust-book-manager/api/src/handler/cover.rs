@@ -0,0 +1,94 @@
+use axum::{
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use kernel::model::{
+    cover::{event::UploadCover, CoverFormat, MAX_COVER_SIZE},
+    id::BookId,
+};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
+
+use crate::{extractor::AuthorizedUser, model::cover::CoverQuery};
+
+// 画像を入れるmultipartのフィールド名
+const COVER_FIELD_NAME: &str = "file";
+
+// multipart/form-dataのfileフィールドで表紙画像を受け取る
+// 形式はフィールドのContent-Typeで判断し、JPEG・PNG・WebPのみ受け付ける
+pub async fn upload_book_cover(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    mut multipart: Multipart,
+) -> AppResult<StatusCode> {
+    let multipart_error = |e: axum::extract::multipart::MultipartError| {
+        AppError::UnprocessableEntity(e.body_text())
+    };
+
+    let mut field = loop {
+        match multipart.next_field().await.map_err(multipart_error)? {
+            Some(field) if field.name() == Some(COVER_FIELD_NAME) => break field,
+            Some(_) => continue,
+            None => {
+                return Err(AppError::UnprocessableEntity(format!(
+                    "{COVER_FIELD_NAME}フィールドに画像を指定してください"
+                )))
+            }
+        }
+    };
+
+    let format = field
+        .content_type()
+        .and_then(|content_type| CoverFormat::from_str(content_type).ok())
+        .ok_or_else(|| {
+            AppError::UnprocessableEntity("表紙画像はJPEG・PNG・WebPのいずれかにしてください".into())
+        })?;
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = field.chunk().await.map_err(multipart_error)? {
+        if bytes.len() + chunk.len() > MAX_COVER_SIZE {
+            return Err(AppError::UnprocessableEntity(format!(
+                "表紙画像は{}MB以下にしてください",
+                MAX_COVER_SIZE / 1024 / 1024
+            )));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    registry
+        .cover_repository()
+        .upload(UploadCover {
+            book_id,
+            format,
+            bytes,
+            requested_user: user.id(),
+        })
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+// imgタグから直接読み込めるよう、表紙画像の取得には認証を求めない
+// BookResponseのURLには更新日時が含まれ、画像を置き換えるとURLも変わるため長めにキャッシュさせる
+pub async fn show_book_cover(
+    Path(book_id): Path<BookId>,
+    Query(query): Query<CoverQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Response> {
+    let cover = registry
+        .cover_repository()
+        .find(book_id, query.size.into())
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("specified book cover not found".into()))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, cover.content_type),
+            (header::CACHE_CONTROL, "public, max-age=86400".to_string()),
+        ],
+        cover.bytes,
+    )
+        .into_response())
+}
@@ -0,0 +1,86 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::id::UserId;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::fine::{
+        CreateFineRequest, CreateFineRequestWithIds, FineBalancesResponse, UserFinesResponse,
+    },
+};
+
+/// ユーザーが自分自身の罰金台帳と未払い残高を取得する
+pub async fn get_my_fines(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<UserFinesResponse>> {
+    registry
+        .fine_repository()
+        .find_by_user_id(user.id())
+        .await
+        .map(UserFinesResponse::from)
+        .map(Json)
+}
+
+/// 指定ユーザーの罰金台帳と未払い残高を取得する(Admin only)
+pub async fn get_user_fines(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<UserFinesResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .fine_repository()
+        .find_by_user_id(user_id)
+        .await
+        .map(UserFinesResponse::from)
+        .map(Json)
+}
+
+/// 指定ユーザーの罰金台帳に請求・支払い・免除を記録する(Admin only)
+pub async fn register_fine(
+    user: AuthorizedUser,
+    Path(user_id): Path<UserId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<CreateFineRequest>,
+) -> AppResult<StatusCode> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    req.validate()?;
+
+    let create_fine = CreateFineRequestWithIds::new(user_id, user.id(), chrono::Utc::now(), req);
+
+    registry
+        .fine_repository()
+        .create(create_fine.into())
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+/// 未払い残高のあるユーザーの一覧を取得する(Admin only)
+pub async fn list_unpaid_fines(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<FineBalancesResponse>> {
+    if !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .fine_repository()
+        .find_unpaid_balances()
+        .await
+        .map(FineBalancesResponse::from)
+        .map(Json)
+}
@@ -0,0 +1,29 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use kernel::model::book::isbn::Isbn;
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
+
+use crate::{extractor::AuthorizedUser, model::metadata::BookMetadataResponse};
+
+/// ISBNから書誌情報を引き、蔵書登録時の入力補助に使えるようにする
+/// ISBN-10で指定した場合もISBN-13に変換して引く
+/// 書誌データはimport-catalogコマンドで取り込んでおく
+pub async fn lookup_isbn(
+    _user: AuthorizedUser,
+    Path(isbn): Path<String>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookMetadataResponse>> {
+    let isbn = Isbn::from_str(&isbn)?;
+
+    registry
+        .book_metadata_repository()
+        .find_by_isbn(&isbn)
+        .await?
+        .map(BookMetadataResponse::from)
+        .map(Json)
+        .ok_or_else(|| AppError::EntityNotFound(format!("ISBN {isbn} の書誌情報が見つかりません")))
+}
@@ -2,4 +2,10 @@ pub mod book;
 pub mod auth;
 pub mod user;
 pub mod checkout;
+pub mod reservation;
+pub mod fine;
 pub mod health;
+pub mod metadata;
+pub mod tag;
+pub mod cover;
+pub mod revision;
@@ -0,0 +1,54 @@
+use crate::{extractor::AuthorizedUser, model::reservation::ReservationsResponse};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use kernel::model::{
+    id::{BookId, ReservationId},
+    reservation::event::{CreateReservation, DeleteReservation},
+};
+use registry::AppRegistry;
+use shared::error::AppResult;
+
+pub async fn reserve_book(
+    user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let create_reservation = CreateReservation::new(book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .reservation_repository()
+        .create(create_reservation)
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+pub async fn cancel_reservation(
+    user: AuthorizedUser,
+    Path((book_id, reservation_id)): Path<(BookId, ReservationId)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let delete_reservation =
+        DeleteReservation::new(reservation_id, book_id, user.id(), chrono::Utc::now());
+
+    registry
+        .reservation_repository()
+        .delete(delete_reservation)
+        .await
+        .map(|_| StatusCode::OK)
+}
+
+pub async fn show_reservation_list(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<ReservationsResponse>> {
+    registry
+        .reservation_repository()
+        .find_by_book_id(book_id)
+        .await
+        .map(ReservationsResponse::from)
+        .map(Json)
+}
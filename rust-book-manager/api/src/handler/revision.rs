@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use kernel::model::{id::BookId, revision::event::RevertBook};
+use registry::AppRegistry;
+use shared::error::{AppError, AppResult};
+
+use crate::{extractor::AuthorizedUser, model::revision::BookRevisionsResponse};
+
+/// 書誌の版を新しい順に取得する
+pub async fn show_book_revisions(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<BookRevisionsResponse>> {
+    registry
+        .book_repository()
+        .find_revisions(book_id)
+        .await
+        .map(BookRevisionsResponse::from)
+        .map(Json)
+}
+
+/// 書誌を以前の版の内容に戻す。書誌の所有者か管理者のみが行える
+/// 権限を確認してから戻すまでの間にほかの更新があった場合は戻さない
+pub async fn revert_book(
+    user: AuthorizedUser,
+    Path((book_id, revision)): Path<(BookId, i32)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    let book = registry
+        .book_repository()
+        .find_by_id(book_id)
+        .await?
+        .ok_or_else(|| AppError::EntityNotFound("specified book not found".into()))?;
+    if book.owner.id != user.id() && !user.is_admin() {
+        return Err(AppError::ForbiddenOperation);
+    }
+
+    registry
+        .book_repository()
+        .revert(RevertBook {
+            book_id,
+            revision,
+            expected_version: book.version,
+            requested_user: user.id(),
+        })
+        .await
+        .map(|_| StatusCode::OK)
+}
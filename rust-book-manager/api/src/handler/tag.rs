@@ -0,0 +1,60 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use garde::Validate;
+use kernel::model::{
+    id::BookId,
+    tag::event::{AddBookTag, RemoveBookTag},
+};
+use registry::AppRegistry;
+use shared::error::AppResult;
+
+use crate::{
+    extractor::AuthorizedUser,
+    model::tag::{AddBookTagRequest, TagsResponse},
+};
+
+pub async fn show_tag_list(
+    _user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<TagsResponse>> {
+    registry
+        .tag_repository()
+        .find_all()
+        .await
+        .map(TagsResponse::from)
+        .map(Json)
+}
+
+// タグは書誌の所有者に限らず、ログインしているユーザーであれば付け外しできる
+pub async fn add_book_tag(
+    _user: AuthorizedUser,
+    Path(book_id): Path<BookId>,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<AddBookTagRequest>,
+) -> AppResult<StatusCode> {
+    req.validate()?;
+
+    registry
+        .tag_repository()
+        .add_to_book(AddBookTag {
+            book_id,
+            name: req.name,
+        })
+        .await
+        .map(|_| StatusCode::CREATED)
+}
+
+pub async fn remove_book_tag(
+    _user: AuthorizedUser,
+    Path((book_id, name)): Path<(BookId, String)>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<StatusCode> {
+    registry
+        .tag_repository()
+        .remove_from_book(RemoveBookTag { book_id, name })
+        .await
+        .map(|_| StatusCode::OK)
+}
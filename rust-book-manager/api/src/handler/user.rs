@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
@@ -11,12 +11,15 @@ use shared::error::{AppError, AppResult};
 use crate::{
     extractor::AuthorizedUser,
     model::user::{
-        CreateUserRequest, UpdateUserPasswordRequest,
-        UpdateUserPasswordRequestWithUserId, UpdateUserRoleRequest,
-        UpdateUserRoleRequestWithUserId, UserResponse, UsersResponse,
+        CreateUserRequest, PatchUserProfileRequest, UpdateUserPasswordRequest,
+        UpdateUserPasswordRequestWithUserId, UpdateUserProfileRequestWithUserId, UpdateUserRoleRequest,
+        UpdateUserRoleRequestWithUserId, UserListQuery, PaginatedUserResponse, UserResponse,
     },
 };
-use crate::model::checkout::CheckoutsResponse;
+use crate::model::{
+    checkout::{CheckoutHistoryQuery, CheckoutsResponse, PaginatedCheckoutResponse},
+    reservation::ReservationsResponse,
+};
 
 /// ユーザーを追加する（Admin only）
 pub async fn register_user(
@@ -38,19 +41,20 @@ pub async fn register_user(
 }
 
 /// ユーザーの一覧を取得する
+/// limit・offset・paging・cursorのいずれも指定しない場合は、ページネーションせずに全件を返す
 pub async fn list_users(
     _user: AuthorizedUser,
+    Query(query): Query<UserListQuery>,
     State(registry): State<AppRegistry>,
-) -> AppResult<Json<UsersResponse>> {
-    let items = registry
-        .user_repository()
-        .find_all()
-        .await?
-        .into_iter()
-        .map(UserResponse::from)
-        .collect();
+) -> AppResult<Json<PaginatedUserResponse>> {
+    query.validate()?;
 
-    Ok(Json(UsersResponse { items }))
+    registry
+        .user_repository()
+        .find_all(query.into())
+        .await
+        .map(PaginatedUserResponse::from)
+        .map(Json)
 }
 
 /// ユーザーを削除する(Admin only)
@@ -94,6 +98,23 @@ pub async fn get_current_user(user: AuthorizedUser) -> Json<UserResponse> {
     Json(UserResponse::from(user.user))
 }
 
+/// ユーザーが自分自身の名前やメールアドレスを、JSON Merge Patchで指定したフィールドだけ変更する
+pub async fn patch_current_user(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+    Json(req): Json<PatchUserProfileRequest>,
+) -> AppResult<Json<UserResponse>> {
+    let req = req.apply(&user.user)?;
+    req.validate()?;
+
+    registry
+        .user_repository()
+        .update_profile(UpdateUserProfileRequestWithUserId::new(user.id(), req).into())
+        .await
+        .map(UserResponse::from)
+        .map(Json)
+}
+
 /// ユーザーが自分自身のパスワード変更する
 pub async fn change_password(
     user: AuthorizedUser,
@@ -125,4 +146,33 @@ pub async fn get_checkouts(
         .await
         .map(CheckoutsResponse::from)
         .map(Json)
+}
+
+// ユーザーが自身の貸出履歴（返却済みも含む）を取得する
+pub async fn get_checkout_history(
+    user: AuthorizedUser,
+    Query(query): Query<CheckoutHistoryQuery>,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<PaginatedCheckoutResponse>> {
+    query.validate()?;
+
+    registry
+        .checkout_repository()
+        .find_history_by_user_id(user.id(), query.into())
+        .await
+        .map(PaginatedCheckoutResponse::from)
+        .map(Json)
+}
+
+// ユーザーが自身の予約の一覧を取得する
+pub async fn get_reservations(
+    user: AuthorizedUser,
+    State(registry): State<AppRegistry>,
+) -> AppResult<Json<ReservationsResponse>> {
+    registry
+        .reservation_repository()
+        .find_by_user_id(user.id())
+        .await
+        .map(ReservationsResponse::from)
+        .map(Json)
 }
\ No newline at end of file
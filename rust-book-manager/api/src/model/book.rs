@@ -1,44 +1,170 @@
+use axum_extra::headers::ETag;
 use derive_new::new;
 use chrono::{DateTime, Utc};
 use garde::Validate;
 use kernel::model::{
     book::{
-        event::{CreateBook, UpdateBook},
-        Book, BookListOptions, Checkout,
+        event::{CreateBook, CreateBookCopy, UpdateBook},
+        isbn::Isbn,
+        Book, BookCopy, BookListFilter, BookListOptions, BookSort, BookSortKey, Checkout,
+        CopyStatus,
     },
-    id::{BookId, UserId, CheckoutId},
-    list::PaginatedList,
+    id::{BookCopyId, BookId, UserId, CheckoutId},
+    list::{PaginatedList, SortDirection},
 };
 use serde::{Deserialize, Serialize};
+use shared::error::{AppError, AppResult};
+use std::str::FromStr;
 
-use super::user::{BookOwner, CheckoutUser};
+use super::{
+    list::{cursor_position, default_limit, PagingName},
+    patch::Patch,
+    user::{BookOwner, CheckoutUser},
+};
 
 #[derive(Debug, Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateBookRequest {
     #[garde(length(min = 1))]
     pub title: String,
-    #[garde(length(min = 1))]
-    pub author: String,
-    #[garde(length(min = 1))]
+    // 著者が1人の場合はauthor、共著の場合はauthorsに順番どおりに指定する
+    #[garde(inner(length(min = 1, max = 255)))]
+    pub author: Option<String>,
+    #[garde(
+        length(max = MAX_AUTHORS),
+        inner(length(min = 1, max = 255)),
+        custom(require_one_of_author(&self.author))
+    )]
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[garde(custom(validate_isbn))]
     pub isbn: String,
     #[garde(skip)]
     pub description: String,
+    #[garde(inner(length(min = 1, max = 255)))]
+    pub publisher: Option<String>,
+    #[garde(inner(range(min = 1000, max = 9999)))]
+    pub published_year: Option<i32>,
+    // ISO 639-2の3文字の言語コード(例: jpn, eng)
+    #[garde(inner(custom(validate_language)))]
+    pub language: Option<String>,
+    #[garde(inner(range(min = 1)))]
+    pub page_count: Option<i32>,
+    #[garde(inner(length(min = 1, max = 64)))]
+    pub edition: Option<String>,
+    // 件名分類
+    #[garde(length(max = MAX_SUBJECTS), inner(length(min = 1, max = 100)))]
+    #[serde(default)]
+    pub subjects: Vec<String>,
+    // 最初の蔵書のバーコード。省略した場合は蔵書IDから採番する
+    #[garde(inner(length(min = 1, max = 64)))]
+    pub barcode: Option<String>,
+    // ISBNやタイトル・著者が同じ書誌がすでにある場合でも、別の書誌として登録することを確認済みであればtrue
+    // 同じ書誌の蔵書を増やすだけであれば、POST /books/:book_id/copies を使う
+    #[garde(skip)]
+    #[serde(default)]
+    pub confirm_duplicate: bool,
 }
 
-impl From<CreateBookRequest> for CreateBook {
-    fn from(value: CreateBookRequest) -> Self {
+// 重複する可能性のある書誌がある場合に、409 Conflictとあわせて返す
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateBookResponse {
+    pub existing_book_ids: Vec<BookId>,
+}
+
+const MAX_AUTHORS: usize = 20;
+const MAX_SUBJECTS: usize = 20;
+
+// ISBN-10またはISBN-13として正しいかを、チェックディジットも含めて検証する
+fn validate_isbn(value: &str, _context: &()) -> garde::Result {
+    Isbn::from_str(value)
+        .map(|_| ())
+        .map_err(|e| garde::Error::new(e.to_string()))
+}
+
+// authorとauthorsは、どちらか一方だけを指定する
+fn require_one_of_author(
+    author: &Option<String>,
+) -> impl FnOnce(&Vec<String>, &()) -> garde::Result + '_ {
+    move |authors, _| match (author, authors.is_empty()) {
+        (None, true) => Err(garde::Error::new("author or authors is required")),
+        (Some(_), false) => Err(garde::Error::new(
+            "author and authors cannot be specified together",
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn validate_language(value: &str, _context: &()) -> garde::Result {
+    if value.len() == 3 && value.bytes().all(|b| b.is_ascii_lowercase()) {
+        Ok(())
+    } else {
+        Err(garde::Error::new("not an ISO 639-2 language code"))
+    }
+}
+
+// 著者が1人の場合のauthorも、1要素のauthorsとして扱う
+fn into_authors(author: Option<String>, authors: Vec<String>) -> Vec<String> {
+    match author {
+        Some(author) => vec![author],
+        None => authors,
+    }
+}
+
+impl TryFrom<CreateBookRequest> for CreateBook {
+    type Error = AppError;
+
+    fn try_from(value: CreateBookRequest) -> AppResult<Self> {
         let CreateBookRequest {
             title,
             author,
+            authors,
             isbn,
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            barcode,
+            ..
         } = value;
-        Self {
+        Ok(Self {
             title,
-            author,
-            isbn,
+            authors: into_authors(author, authors),
+            isbn: Isbn::from_str(&isbn)?,
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            barcode,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateBookCopyRequest {
+    #[garde(inner(length(min = 1, max = 64)))]
+    pub barcode: Option<String>,
+}
+
+#[derive(new)]
+pub struct CreateBookCopyRequestWithIds(BookId, UserId, CreateBookCopyRequest);
+
+impl From<CreateBookCopyRequestWithIds> for CreateBookCopy {
+    fn from(value: CreateBookCopyRequestWithIds) -> Self {
+        let CreateBookCopyRequestWithIds(book_id, user_id, CreateBookCopyRequest { barcode }) =
+            value;
+        CreateBookCopy {
+            book_id,
+            barcode,
+            requested_user: user_id,
         }
     }
 }
@@ -48,46 +174,145 @@ impl From<CreateBookRequest> for CreateBook {
 pub struct UpdateBookRequest {
     #[garde(length(min = 1))]
     pub title: String,
-    #[garde(length(min = 1))]
-    pub author: String,
-    #[garde(length(min = 1))]
+    // 著者が1人の場合はauthor、共著の場合はauthorsに順番どおりに指定する
+    #[garde(inner(length(min = 1, max = 255)))]
+    pub author: Option<String>,
+    #[garde(
+        length(max = MAX_AUTHORS),
+        inner(length(min = 1, max = 255)),
+        custom(require_one_of_author(&self.author))
+    )]
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[garde(custom(validate_isbn))]
     pub isbn: String,
     #[garde(skip)]
     pub description: String,
+    #[garde(inner(length(min = 1, max = 255)))]
+    pub publisher: Option<String>,
+    #[garde(inner(range(min = 1000, max = 9999)))]
+    pub published_year: Option<i32>,
+    // ISO 639-2の3文字の言語コード(例: jpn, eng)
+    #[garde(inner(custom(validate_language)))]
+    pub language: Option<String>,
+    #[garde(inner(range(min = 1)))]
+    pub page_count: Option<i32>,
+    #[garde(inner(length(min = 1, max = 64)))]
+    pub edition: Option<String>,
+    // 件名分類
+    #[garde(length(max = MAX_SUBJECTS), inner(length(min = 1, max = 100)))]
+    #[serde(default)]
+    pub subjects: Vec<String>,
 }
 
 // パスパラメータからBookId
 // リクエスト時のAuthorizedUserから取り出すUserId,
 // UpdateBookRequestの3つの値のセットをUpdateBook型に変換するための一時的な型
 #[derive(new)]
-pub struct UpdateBookRequestWithIds(BookId, UserId, UpdateBookRequest);
+// 3つ目の値は更新の前提とする書誌の版番号
+pub struct UpdateBookRequestWithIds(BookId, UserId, i32, UpdateBookRequest);
+
+impl TryFrom<UpdateBookRequestWithIds> for UpdateBook {
+    type Error = AppError;
 
-impl From<UpdateBookRequestWithIds> for UpdateBook {
-    fn from(value: UpdateBookRequestWithIds) -> Self {
+    fn try_from(value: UpdateBookRequestWithIds) -> AppResult<Self> {
         let UpdateBookRequestWithIds(
             book_id,
             user_id,
+            expected_version,
             UpdateBookRequest {
                 title,
                 author,
+                authors,
                 isbn,
                 description,
+                publisher,
+                published_year,
+                language,
+                page_count,
+                edition,
+                subjects,
             },
         ) = value;
-        UpdateBook {
+        Ok(UpdateBook {
             book_id,
+            title,
+            authors: into_authors(author, authors),
+            isbn: Isbn::from_str(&isbn)?,
+            description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            expected_version,
+            requested_user: user_id,
+        })
+    }
+}
+
+// JSON Merge Patchで書誌の一部のフィールドだけを更新するための型
+// 現在の書誌に適用してUpdateBookRequestにしてから、更新時と同じ検証を行う
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct PatchBookRequest {
+    pub title: Patch<String>,
+    pub author: Patch<String>,
+    pub authors: Patch<Vec<String>>,
+    pub isbn: Patch<String>,
+    pub description: Patch<String>,
+    pub publisher: Patch<String>,
+    pub published_year: Patch<i32>,
+    pub language: Patch<String>,
+    pub page_count: Patch<i32>,
+    pub edition: Patch<String>,
+    pub subjects: Patch<Vec<String>>,
+}
+
+impl PatchBookRequest {
+    pub fn apply(self, book: Book) -> AppResult<UpdateBookRequest> {
+        let PatchBookRequest {
             title,
             author,
+            authors,
             isbn,
             description,
-            requested_user: user_id,
-        }
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+        } = self;
+        // authorとauthorsはどちらか一方だけを指定するため、
+        // 一方が指定された場合は現在の著者を引き継がずに指定された方だけを使う
+        let (author, authors) = if author.is_unchanged() && authors.is_unchanged() {
+            (None, book.authors)
+        } else {
+            (author.apply(None), authors.apply_or_default(Vec::new()))
+        };
+        Ok(UpdateBookRequest {
+            title: title.apply_required(book.title, "title")?,
+            author,
+            authors,
+            isbn: isbn.apply_required(book.isbn.into(), "isbn")?,
+            description: description.apply_or_default(book.description),
+            publisher: publisher.apply(book.publisher),
+            published_year: published_year.apply(book.published_year),
+            language: language.apply(book.language),
+            page_count: page_count.apply(book.page_count),
+            edition: edition.apply(book.edition),
+            subjects: subjects.apply_or_default(book.subjects),
+        })
     }
 }
 
 // クエリでlimitとoffsettを受け取るための型
 // handler側のメソッドで、クエリのデータを取得する
+// sortを省略した場合、orderは無視される
 #[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
 pub struct BookListQuery {
     #[garde(range(min = 0))]
     #[serde(default = "default_limit")]
@@ -95,51 +320,239 @@ pub struct BookListQuery {
     #[garde(range(min = 0))]
     #[serde(default)]
     pub offset: i64,
+    #[garde(inner(length(min = 1, max = 200)))]
+    pub search: Option<String>,
+    #[garde(inner(length(min = 1)))]
+    pub author: Option<String>,
+    #[garde(skip)]
+    pub owner: Option<UserId>,
+    #[garde(inner(length(min = 1)))]
+    pub tag: Option<String>,
+    #[garde(skip)]
+    pub available: Option<bool>,
+    #[garde(skip)]
+    pub created_from: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub created_to: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub sort: Option<BookSortKeyName>,
+    #[garde(skip)]
+    #[serde(default)]
+    pub order: SortDirectionName,
+    #[garde(skip)]
+    #[serde(default)]
+    pub paging: PagingName,
+    #[garde(inner(length(min = 1)))]
+    pub cursor: Option<String>,
 }
 
-const DEFAULT_LIMIT: i64 = 20;
-const fn default_limit() -> i64 {
-    DEFAULT_LIMIT
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BookSortKeyName {
+    Title,
+    Author,
+    CreatedAt,
+}
+
+impl From<BookSortKeyName> for BookSortKey {
+    fn from(value: BookSortKeyName) -> Self {
+        match value {
+            BookSortKeyName::Title => Self::Title,
+            BookSortKeyName::Author => Self::Author,
+            BookSortKeyName::CreatedAt => Self::CreatedAt,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SortDirectionName {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl From<SortDirectionName> for SortDirection {
+    fn from(value: SortDirectionName) -> Self {
+        match value {
+            SortDirectionName::Asc => Self::Asc,
+            SortDirectionName::Desc => Self::Desc,
+        }
+    }
 }
 
 impl From<BookListQuery> for BookListOptions {
     fn from(value: BookListQuery) -> Self {
-        let BookListQuery { limit, offset } = value;
-        Self { limit, offset }
+        let BookListQuery {
+            limit,
+            offset,
+            search,
+            author,
+            owner,
+            tag,
+            available,
+            created_from,
+            created_to,
+            sort,
+            order,
+            paging,
+            cursor,
+        } = value;
+        Self {
+            limit,
+            offset,
+            cursor: cursor_position(paging, cursor),
+            search,
+            filter: BookListFilter {
+                author,
+                owner,
+                tag,
+                available,
+                created_from,
+                created_to,
+            },
+            sort: sort.map(|key| BookSort {
+                key: key.into(),
+                direction: order.into(),
+            }),
+        }
     }
 }
 
+// 書誌の版番号から、show_bookで返しupdate_bookのIf-Matchで受け付けるETagを作る
+// 蔵書の貸出状況などは版番号に含まれないため、書誌情報の更新の競合の検出にだけ使う
+pub fn book_etag(version: i32) -> AppResult<ETag> {
+    format!("\"{version}\"")
+        .parse::<ETag>()
+        .map_err(|e| AppError::ConversionEntityError(e.to_string()))
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BookResponse {
     pub id: BookId,
     pub title: String,
+    // 著者を連結した表示用の著者名
     pub author: String,
+    pub authors: Vec<String>,
     pub isbn: String,
     pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    pub tags: Vec<String>,
+    // 表紙画像がない場合はNone
+    pub cover_url: Option<String>,
+    pub cover_thumbnail_url: Option<String>,
+    // アーカイブした日時。アーカイブしていない場合はNone
+    pub archived_at: Option<DateTime<Utc>>,
     pub owner: BookOwner,
+    pub total_copies: usize,
+    pub available_copies: usize,
+    pub copies: Vec<BookCopyResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookCopyResponse {
+    pub id: BookCopyId,
+    pub barcode: String,
+    pub status: CopyStatusName,
     pub checkout: Option<BookCheckoutResponse>,
 }
 
+impl From<BookCopy> for BookCopyResponse {
+    fn from(value: BookCopy) -> Self {
+        let BookCopy {
+            id,
+            barcode,
+            status,
+            checkout,
+        } = value;
+        Self {
+            id,
+            barcode,
+            status: status.into(),
+            checkout: checkout.map(BookCheckoutResponse::from),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum CopyStatusName {
+    Available,
+    Lost,
+    Damaged,
+}
+
+impl From<CopyStatus> for CopyStatusName {
+    fn from(value: CopyStatus) -> Self {
+        match value {
+            CopyStatus::Available => Self::Available,
+            CopyStatus::Lost => Self::Lost,
+            CopyStatus::Damaged => Self::Damaged,
+        }
+    }
+}
+
 impl From<Book> for BookResponse {
     fn from(value: Book) -> Self {
+        let total_copies = value.total_copies();
+        let available_copies = value.available_copies();
+        let author = value.author();
         let Book  {
             id,
             title,
-            author,
+            authors,
             isbn,
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            tags,
+            cover_updated_at,
+            archived_at,
+            // 版番号はETagとして返す
+            version: _,
             owner,
-            checkout,
+            copies,
         } = value;
+        // 表紙画像を置き換えたときにキャッシュされた古い画像を使われないよう、URLに更新日時を含める
+        let cover_url = |size: &str| {
+            cover_updated_at.map(|updated_at| {
+                format!(
+                    "/api/v1/books/{id}/cover?size={size}&v={}",
+                    updated_at.timestamp_millis()
+                )
+            })
+        };
         Self {
             id,
             title,
             author,
-            isbn,
+            authors,
+            isbn: isbn.into(),
             description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            tags,
+            cover_url: cover_url("original"),
+            cover_thumbnail_url: cover_url("medium"),
+            archived_at,
             owner: owner.into(),
-            checkout: checkout.map(BookCheckoutResponse::from),
+            total_copies,
+            available_copies,
+            copies: copies.into_iter().map(BookCopyResponse::from).collect(),
         }
     }
 }
@@ -147,13 +560,19 @@ impl From<Book> for BookResponse {
 // apiレイヤーでのページネーション表現用の型
 // 型の内部で持つフィールドはPaginatedListt<Book>と同じであるが、
 // serde::Serializeを実装しているのでJSONに変換してクライアントに返せる。
+// カーソル方式の場合はtotalを返さず、nextCursor, prevCursorを次のリクエストのcursorに指定する
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginatedBookResponse {
-    pub total: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
     pub limit: i64,
     pub offset: i64,
     pub items: Vec<BookResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
 }
 
 impl From<PaginatedList<Book>> for PaginatedBookResponse {
@@ -163,12 +582,16 @@ impl From<PaginatedList<Book>> for PaginatedBookResponse {
             limit,
             offset,
             items,
+            next_cursor,
+            prev_cursor,
         } = value;
         Self {
             total,
             limit,
             offset,
             items: items.into_iter().map(BookResponse::from).collect(),
+            next_cursor: next_cursor.map(String::from),
+            prev_cursor: prev_cursor.map(String::from),
         }
     }
 }
@@ -179,6 +602,7 @@ pub struct BookCheckoutResponse {
     pub id: CheckoutId,
     pub checked_out_by: CheckoutUser,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
 }
 
 impl From<Checkout> for BookCheckoutResponse {
@@ -187,11 +611,13 @@ impl From<Checkout> for BookCheckoutResponse {
             checkout_id,
             checked_out_by,
             checked_out_at,
+            due_at,
         } = value;
         BookCheckoutResponse {
             id: checkout_id,
             checked_out_by: checked_out_by.into(),
             checked_out_at,
+            due_at,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,306 @@
+use kernel::model::book::{Book, BookCopy};
+use serde::{Deserialize, Serialize};
+use shared::error::{AppError, AppResult};
+
+use super::book::BookResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct BookExportQuery {
+    pub format: BookExportFormat,
+}
+
+// 書誌の出力形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookExportFormat {
+    Csv,
+    Jsonl,
+    Marcxml,
+}
+
+impl BookExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Jsonl => "application/x-ndjson",
+            Self::Marcxml => "application/marcxml+xml",
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Csv => "books.csv",
+            Self::Jsonl => "books.jsonl",
+            Self::Marcxml => "books.xml",
+        }
+    }
+
+    // 書誌より前に出力する内容。CSVはヘッダー行、MARCXMLはcollection要素の開始タグ
+    pub fn header(self) -> AppResult<Vec<u8>> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv_writer();
+                writer.write_record(CSV_COLUMNS).map_err(csv_error)?;
+                writer.into_inner().map_err(|e| csv_error(e.into_error().into()))
+            }
+            Self::Jsonl => Ok(Vec::new()),
+            Self::Marcxml => Ok(concat!(
+                r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+                "\n",
+                r#"<collection xmlns="http://www.loc.gov/MARC21/slim">"#,
+                "\n"
+            )
+            .into()),
+        }
+    }
+
+    // 1件の書誌を出力する内容に変換する
+    pub fn encode(self, book: Book) -> AppResult<Vec<u8>> {
+        match self {
+            Self::Csv => {
+                let mut writer = csv_writer();
+                writer
+                    .serialize(BookExportCsvRecord::from(book))
+                    .map_err(csv_error)?;
+                writer.into_inner().map_err(|e| csv_error(e.into_error().into()))
+            }
+            Self::Jsonl => {
+                let mut line = serde_json::to_vec(&BookResponse::from(book))
+                    .map_err(|e| AppError::ConversionEntityError(e.to_string()))?;
+                line.push(b'\n');
+                Ok(line)
+            }
+            Self::Marcxml => Ok(marc_record(&book).into_bytes()),
+        }
+    }
+
+    // 書誌より後に出力する内容
+    pub fn footer(self) -> Vec<u8> {
+        match self {
+            Self::Marcxml => "</collection>\n".into(),
+            Self::Csv | Self::Jsonl => Vec::new(),
+        }
+    }
+}
+
+// 書誌ごとにヘッダーを出力しないよう、ヘッダー行はheaderで別に書き込む
+fn csv_writer() -> csv::Writer<Vec<u8>> {
+    csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new())
+}
+
+fn csv_error(e: csv::Error) -> AppError {
+    AppError::ConversionEntityError(e.to_string())
+}
+
+// 複数の値を1つの列にまとめる際の区切り文字。著者名などにカンマが含まれることがあるためセミコロンを使う
+const CSV_VALUE_SEPARATOR: &str = "; ";
+
+const CSV_COLUMNS: [&str; 18] = [
+    "id",
+    "title",
+    "authors",
+    "isbn",
+    "description",
+    "publisher",
+    "published_year",
+    "language",
+    "page_count",
+    "edition",
+    "subjects",
+    "tags",
+    "owner_id",
+    "owner_name",
+    "total_copies",
+    "available_copies",
+    "checked_out_by",
+    "next_due_at",
+];
+
+// CSVの1行。列の順序はCSV_COLUMNSと揃える
+#[derive(Debug, Serialize)]
+struct BookExportCsvRecord {
+    id: String,
+    title: String,
+    authors: String,
+    isbn: String,
+    description: String,
+    publisher: Option<String>,
+    published_year: Option<i32>,
+    language: Option<String>,
+    page_count: Option<i32>,
+    edition: Option<String>,
+    subjects: String,
+    tags: String,
+    owner_id: String,
+    owner_name: String,
+    total_copies: usize,
+    available_copies: usize,
+    // 貸出中の蔵書を借りているユーザー名
+    checked_out_by: String,
+    // 貸出中の蔵書のうち、最も早い返却期限
+    next_due_at: Option<String>,
+}
+
+impl From<Book> for BookExportCsvRecord {
+    fn from(value: Book) -> Self {
+        let total_copies = value.total_copies();
+        let available_copies = value.available_copies();
+        let checkouts = value
+            .copies
+            .iter()
+            .filter_map(|copy| copy.checkout.as_ref())
+            .collect::<Vec<_>>();
+        let checked_out_by = checkouts
+            .iter()
+            .map(|c| c.checked_out_by.name.as_str())
+            .collect::<Vec<_>>()
+            .join(CSV_VALUE_SEPARATOR);
+        let next_due_at = checkouts.iter().map(|c| c.due_at).min().map(|d| d.to_rfc3339());
+        Self {
+            id: value.id.to_string(),
+            title: value.title,
+            authors: value.authors.join(CSV_VALUE_SEPARATOR),
+            isbn: value.isbn.into(),
+            description: value.description,
+            publisher: value.publisher,
+            published_year: value.published_year,
+            language: value.language,
+            page_count: value.page_count,
+            edition: value.edition,
+            subjects: value.subjects.join(CSV_VALUE_SEPARATOR),
+            tags: value.tags.join(CSV_VALUE_SEPARATOR),
+            owner_id: value.owner.id.to_string(),
+            owner_name: value.owner.name,
+            total_copies,
+            available_copies,
+            checked_out_by,
+            next_due_at,
+        }
+    }
+}
+
+// MARC21の書誌レコードに変換する。レコード長などはMARCXMLでは意味を持たないため、リーダーは0で埋める
+// 所有者は852(所在)、蔵書は876(蔵書情報)に出力し、貸出は独自の990に蔵書ごとに出力する
+fn marc_record(book: &Book) -> String {
+    let mut record = String::from("  <record>\n    <leader>00000nam a2200000 a 4500</leader>\n");
+    control_field(&mut record, "001", &book.id.to_string());
+    data_field(&mut record, "020", "  ", &[("a", book.isbn.as_str())]);
+    if let Some(language) = &book.language {
+        data_field(&mut record, "041", "  ", &[("a", language)]);
+    }
+    if let Some((first, rest)) = book.authors.split_first() {
+        data_field(&mut record, "100", "1 ", &[("a", first)]);
+        for author in rest {
+            data_field(&mut record, "700", "1 ", &[("a", author)]);
+        }
+    }
+    data_field(&mut record, "245", "00", &[("a", &book.title)]);
+    if let Some(edition) = &book.edition {
+        data_field(&mut record, "250", "  ", &[("a", edition)]);
+    }
+    let published_year = book.published_year.map(|y| y.to_string());
+    let publication = [
+        book.publisher.as_deref().map(|p| ("b", p)),
+        published_year.as_deref().map(|y| ("c", y)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    if !publication.is_empty() {
+        data_field(&mut record, "264", " 1", &publication);
+    }
+    if let Some(page_count) = book.page_count {
+        data_field(&mut record, "300", "  ", &[("a", &format!("{page_count} p."))]);
+    }
+    if !book.description.is_empty() {
+        data_field(&mut record, "520", "  ", &[("a", &book.description)]);
+    }
+    for subject in &book.subjects {
+        data_field(&mut record, "650", " 4", &[("a", subject)]);
+    }
+    for tag in &book.tags {
+        data_field(&mut record, "653", "  ", &[("a", tag)]);
+    }
+    let owner_id = book.owner.id.to_string();
+    data_field(&mut record, "852", "  ", &[("a", &book.owner.name), ("x", &owner_id)]);
+    for copy in &book.copies {
+        copy_fields(&mut record, copy);
+    }
+    record.push_str("  </record>\n");
+    record
+}
+
+fn copy_fields(record: &mut String, copy: &BookCopy) {
+    let copy_id = copy.id.to_string();
+    let status = match copy.checkout {
+        Some(_) => "CheckedOut",
+        None => copy.status.as_ref(),
+    };
+    data_field(
+        record,
+        "876",
+        "  ",
+        &[("a", &copy_id), ("p", &copy.barcode), ("j", status)],
+    );
+    if let Some(checkout) = &copy.checkout {
+        let user_id = checkout.checked_out_by.id.to_string();
+        let checked_out_at = checkout.checked_out_at.to_rfc3339();
+        let due_at = checkout.due_at.to_rfc3339();
+        data_field(
+            record,
+            "990",
+            "  ",
+            &[
+                ("p", &copy.barcode),
+                ("a", &user_id),
+                ("b", &checkout.checked_out_by.name),
+                ("c", &checked_out_at),
+                ("d", &due_at),
+            ],
+        );
+    }
+}
+
+fn control_field(record: &mut String, tag: &str, value: &str) {
+    record.push_str(&format!(
+        "    <controlfield tag=\"{tag}\">{}</controlfield>\n",
+        escape_xml(value)
+    ));
+}
+
+// indicatorsは第1・第2指示子を並べた2文字
+fn data_field(record: &mut String, tag: &str, indicators: &str, subfields: &[(&str, &str)]) {
+    let mut indicators = indicators.chars();
+    let ind1 = indicators.next().unwrap_or(' ');
+    let ind2 = indicators.next().unwrap_or(' ');
+    record.push_str(&format!(
+        "    <datafield tag=\"{tag}\" ind1=\"{ind1}\" ind2=\"{ind2}\">\n"
+    ));
+    for (code, value) in subfields {
+        record.push_str(&format!(
+            "      <subfield code=\"{code}\">{}</subfield>\n",
+            escape_xml(value)
+        ));
+    }
+    record.push_str("    </datafield>\n");
+}
+
+// XMLの特殊文字をエスケープする。XML 1.0で使えない制御文字は取り除く
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
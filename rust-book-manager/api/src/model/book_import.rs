@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use shared::error::{AppError, AppResult};
+
+use super::book::CreateBookRequest;
+
+// 1回のインポートで受け付ける行数の上限(ヘッダー行を除く)
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+// インポートするCSVの1行。ヘッダー行の列名で対応付けるため、列の順序は問わない
+// title, author, isbnの列は必須で、description, owner_emailの列は省略できる
+#[derive(Debug, Deserialize)]
+pub struct BookImportRecord {
+    pub title: String,
+    pub author: String,
+    pub isbn: String,
+    #[serde(default)]
+    pub description: String,
+    // 書誌の所有者とするユーザーのメールアドレス。空の場合はインポートしたユーザーが所有者になる
+    #[serde(default)]
+    pub owner_email: Option<String>,
+}
+
+impl From<BookImportRecord> for CreateBookRequest {
+    fn from(value: BookImportRecord) -> Self {
+        let BookImportRecord {
+            title,
+            author,
+            isbn,
+            description,
+            owner_email: _,
+        } = value;
+        Self {
+            title,
+            author: Some(author),
+            authors: vec![],
+            isbn,
+            description,
+            publisher: None,
+            published_year: None,
+            language: None,
+            page_count: None,
+            edition: None,
+            subjects: vec![],
+            barcode: None,
+            confirm_duplicate: true,
+        }
+    }
+}
+
+const REQUIRED_COLUMNS: [&str; 3] = ["title", "author", "isbn"];
+
+// CSVを読み込み、各行を行番号(ヘッダー行を1行目とする)とあわせて返す
+// 列が足りないなど読み込めない行は、その理由をErrとして返す
+pub fn parse_book_csv(bytes: &[u8]) -> AppResult<Vec<(u64, Result<BookImportRecord, String>)>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(bytes);
+
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::UnprocessableEntity(format!("CSVのヘッダー行を読み込めません: {e}")))?
+        .clone();
+    let missing = REQUIRED_COLUMNS
+        .into_iter()
+        .filter(|column| !headers.iter().any(|header| header == *column))
+        .collect::<Vec<_>>();
+    if !missing.is_empty() {
+        return Err(AppError::UnprocessableEntity(format!(
+            "CSVに必要な列がありません: {}",
+            missing.join(", ")
+        )));
+    }
+
+    let mut records = Vec::new();
+    for result in reader.records() {
+        if records.len() == MAX_IMPORT_ROWS {
+            return Err(AppError::UnprocessableEntity(format!(
+                "一度にインポートできるのは{MAX_IMPORT_ROWS}行までです"
+            )));
+        }
+        let record = match result {
+            Ok(record) => (
+                record.position().map_or(0, |p| p.line()),
+                record
+                    .deserialize::<BookImportRecord>(Some(&headers))
+                    .map_err(|e| e.to_string()),
+            ),
+            Err(e) => (e.position().map_or(0, |p| p.line()), Err(e.to_string())),
+        };
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportResponse {
+    // 登録した書誌の数
+    pub imported: usize,
+    // 登録しなかった行と、その理由
+    pub errors: Vec<BookImportErrorResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookImportErrorResponse {
+    pub row: u64,
+    pub messages: Vec<String>,
+}
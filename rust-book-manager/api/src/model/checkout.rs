@@ -1,9 +1,16 @@
 use chrono::{DateTime, Utc};
+use garde::Validate;
 use kernel::model::{
-    checkout::{Checkout, CheckoutBook},
-    id::{BookId, CheckoutId, UserId},
+    checkout::{
+        Checkout, CheckoutBook, CheckoutHistoryOptions, CheckoutIncident, IncidentKind,
+        OverdueCheckout, UserOverdueCheckouts,
+    },
+    id::{BookCopyId, BookId, CheckoutId, UserId},
+    list::PaginatedList,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+
+use super::{list::default_limit, user::CheckoutUser};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -19,13 +26,79 @@ impl From<Vec<Checkout>> for CheckoutsResponse {
     }
 }
 
+// 貸出履歴の取得条件をクエリで受け取るための型
+// from, toはRFC 3339形式の日時で、貸出日時がその範囲に含まれるものに絞り込む
+#[derive(Debug, Deserialize, Validate)]
+pub struct CheckoutHistoryQuery {
+    #[garde(range(min = 0))]
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+    #[garde(range(min = 0))]
+    #[serde(default)]
+    pub offset: i64,
+    #[garde(skip)]
+    pub from: Option<DateTime<Utc>>,
+    #[garde(skip)]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl From<CheckoutHistoryQuery> for CheckoutHistoryOptions {
+    fn from(value: CheckoutHistoryQuery) -> Self {
+        let CheckoutHistoryQuery {
+            limit,
+            offset,
+            from,
+            to,
+        } = value;
+        Self {
+            limit,
+            offset,
+            from,
+            to,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaginatedCheckoutResponse {
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub items: Vec<CheckoutResponse>,
+}
+
+impl From<PaginatedList<Checkout>> for PaginatedCheckoutResponse {
+    fn from(value: PaginatedList<Checkout>) -> Self {
+        // 貸出履歴はオフセット方式でのみ取得するため、件数は常に数えられている
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+            ..
+        } = value;
+        Self {
+            total: total.unwrap_or_default(),
+            limit,
+            offset,
+            items: items.into_iter().map(CheckoutResponse::from).collect(),
+        }
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutResponse {
     pub id: CheckoutId,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
     pub returned_at: Option<DateTime<Utc>>,
+    pub checkout_processed_by: UserId,
+    pub return_processed_by: Option<UserId>,
+    pub incident: Option<CheckoutIncidentResponse>,
     pub book: CheckoutBookResponse,
 }
 
@@ -35,19 +108,87 @@ impl From<Checkout> for CheckoutResponse {
             id,
             checked_out_by,
             checked_out_at,
+            due_at,
+            renewal_count,
             returned_at,
+            checkout_processed_by,
+            return_processed_by,
+            incident,
             book,
         } = value;
         Self {
             id,
             checked_out_by,
             checked_out_at,
+            due_at,
+            renewal_count,
             returned_at,
+            checkout_processed_by,
+            return_processed_by,
+            incident: incident.map(CheckoutIncidentResponse::from),
             book: book.into(),
         }
     }
 }
 
+#[derive(Serialize)]
+pub enum IncidentKindName {
+    Lost,
+    Damaged,
+}
+
+impl From<IncidentKind> for IncidentKindName {
+    fn from(value: IncidentKind) -> Self {
+        match value {
+            IncidentKind::Lost => Self::Lost,
+            IncidentKind::Damaged => Self::Damaged,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutIncidentResponse {
+    pub kind: IncidentKindName,
+    pub reported_at: DateTime<Utc>,
+}
+
+impl From<CheckoutIncident> for CheckoutIncidentResponse {
+    fn from(value: CheckoutIncident) -> Self {
+        let CheckoutIncident { kind, reported_at } = value;
+        Self {
+            kind: kind.into(),
+            reported_at,
+        }
+    }
+}
+
+// 紛失・破損を報告する際に、弁償金を請求する場合はその金額を指定する
+#[derive(Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportIncidentRequest {
+    #[garde(range(min = 1))]
+    pub replacement_fee: Option<i64>,
+}
+
+// 管理者が代理で貸し出す際に、借りるユーザーを指定する
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutOnBehalfRequest {
+    pub user_id: UserId,
+    #[serde(default)]
+    pub copy_id: Option<BookCopyId>,
+}
+
+// 貸し出す蔵書を指定する場合のリクエスト
+// 省略した場合は貸出可能な蔵書のうち最初のものを割り当てる
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutRequest {
+    #[serde(default)]
+    pub copy_id: Option<BookCopyId>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CheckoutBookResponse {
@@ -55,6 +196,8 @@ pub struct CheckoutBookResponse {
     pub title: String,
     pub author: String,
     pub isbn: String,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
 }
 
 impl From<CheckoutBook> for CheckoutBookResponse {
@@ -64,12 +207,74 @@ impl From<CheckoutBook> for CheckoutBookResponse {
             title,
             author,
             isbn,
+            copy_id,
+            barcode,
         } = value;
         Self {
             id: book_id,
             title,
             author,
             isbn,
+            copy_id,
+            barcode,
         }
     }
-}
\ No newline at end of file
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueCheckoutsResponse {
+    pub items: Vec<UserOverdueCheckoutsResponse>,
+}
+
+impl From<Vec<UserOverdueCheckouts>> for OverdueCheckoutsResponse {
+    fn from(value: Vec<UserOverdueCheckouts>) -> Self {
+        Self {
+            items: value
+                .into_iter()
+                .map(UserOverdueCheckoutsResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOverdueCheckoutsResponse {
+    pub user: CheckoutUser,
+    pub checkouts: Vec<OverdueCheckoutResponse>,
+}
+
+impl From<UserOverdueCheckouts> for UserOverdueCheckoutsResponse {
+    fn from(value: UserOverdueCheckouts) -> Self {
+        let UserOverdueCheckouts { user, checkouts } = value;
+        Self {
+            user: user.into(),
+            checkouts: checkouts
+                .into_iter()
+                .map(OverdueCheckoutResponse::from)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverdueCheckoutResponse {
+    #[serde(flatten)]
+    pub checkout: CheckoutResponse,
+    pub days_overdue: i64,
+}
+
+impl From<OverdueCheckout> for OverdueCheckoutResponse {
+    fn from(value: OverdueCheckout) -> Self {
+        let OverdueCheckout {
+            checkout,
+            days_overdue,
+        } = value;
+        Self {
+            checkout: checkout.into(),
+            days_overdue,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use kernel::model::cover::CoverSize;
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverQuery {
+    #[serde(default)]
+    pub size: CoverSizeName,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CoverSizeName {
+    #[default]
+    Original,
+    Medium,
+    Small,
+}
+
+impl From<CoverSizeName> for CoverSize {
+    fn from(value: CoverSizeName) -> Self {
+        match value {
+            CoverSizeName::Original => Self::Original,
+            CoverSizeName::Medium => Self::Medium,
+            CoverSizeName::Small => Self::Small,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use garde::Validate;
+use kernel::model::{
+    fine::{event::CreateFine, Fine, FineKind, UserFineBalance, UserFines},
+    id::{CheckoutId, FineId, UserId},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub enum FineKindName {
+    Overdue,
+    Replacement,
+    Adjustment,
+    Payment,
+    Waiver,
+}
+
+impl From<FineKind> for FineKindName {
+    fn from(value: FineKind) -> Self {
+        match value {
+            FineKind::Overdue => Self::Overdue,
+            FineKind::Replacement => Self::Replacement,
+            FineKind::Adjustment => Self::Adjustment,
+            FineKind::Payment => Self::Payment,
+            FineKind::Waiver => Self::Waiver,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFinesResponse {
+    pub user_id: UserId,
+    pub balance: i64,
+    pub items: Vec<FineResponse>,
+}
+
+impl From<UserFines> for UserFinesResponse {
+    fn from(value: UserFines) -> Self {
+        let UserFines {
+            user_id,
+            balance,
+            items,
+        } = value;
+        Self {
+            user_id,
+            balance,
+            items: items.into_iter().map(FineResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FineResponse {
+    pub id: FineId,
+    pub checkout_id: Option<CheckoutId>,
+    pub kind: FineKindName,
+    pub amount: i64,
+    pub note: String,
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<Fine> for FineResponse {
+    fn from(value: Fine) -> Self {
+        let Fine {
+            id,
+            user_id: _,
+            checkout_id,
+            kind,
+            amount,
+            note,
+            created_by,
+            created_at,
+        } = value;
+        Self {
+            id,
+            checkout_id,
+            kind: kind.into(),
+            amount,
+            note,
+            created_by,
+            created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FineBalancesResponse {
+    pub items: Vec<FineBalanceResponse>,
+}
+
+impl From<Vec<UserFineBalance>> for FineBalancesResponse {
+    fn from(value: Vec<UserFineBalance>) -> Self {
+        Self {
+            items: value.into_iter().map(FineBalanceResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FineBalanceResponse {
+    pub user_id: UserId,
+    pub user_name: String,
+    pub balance: i64,
+}
+
+impl From<UserFineBalance> for FineBalanceResponse {
+    fn from(value: UserFineBalance) -> Self {
+        let UserFineBalance {
+            user_id,
+            user_name,
+            balance,
+        } = value;
+        Self {
+            user_id,
+            user_name,
+            balance,
+        }
+    }
+}
+
+// 管理者が手動で記録できる台帳の種別
+// 延滞料金(Overdue)は返却時に自動で計上されるため含めない
+#[derive(Deserialize)]
+pub enum ManualFineKindName {
+    Adjustment,
+    Payment,
+    Waiver,
+}
+
+// amountは常に正の値で受け取り、支払い・免除の場合はマイナスとして記録する
+#[derive(Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFineRequest {
+    #[garde(skip)]
+    kind: ManualFineKindName,
+    #[garde(range(min = 1))]
+    amount: i64,
+    #[garde(skip)]
+    checkout_id: Option<CheckoutId>,
+    #[garde(length(max = 1024))]
+    #[serde(default)]
+    note: String,
+}
+
+// パスパラメータから取り出す対象ユーザーのUserId、
+// 操作した管理者のUserId、CreateFineRequestをCreateFine型に変換するための一時的な型
+#[derive(new)]
+pub struct CreateFineRequestWithIds(UserId, UserId, DateTime<Utc>, CreateFineRequest);
+
+impl From<CreateFineRequestWithIds> for CreateFine {
+    fn from(value: CreateFineRequestWithIds) -> Self {
+        let CreateFineRequestWithIds(
+            user_id,
+            created_by,
+            created_at,
+            CreateFineRequest {
+                kind,
+                amount,
+                checkout_id,
+                note,
+            },
+        ) = value;
+        let (kind, amount) = match kind {
+            ManualFineKindName::Adjustment => (FineKind::Adjustment, amount),
+            ManualFineKindName::Payment => (FineKind::Payment, -amount),
+            ManualFineKindName::Waiver => (FineKind::Waiver, -amount),
+        };
+        CreateFine {
+            user_id,
+            checkout_id,
+            kind,
+            amount,
+            note,
+            created_by,
+            created_at,
+        }
+    }
+}
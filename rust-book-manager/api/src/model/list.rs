@@ -0,0 +1,25 @@
+use kernel::model::list::{CursorPosition, PageCursor};
+use serde::Deserialize;
+
+// 一覧のページネーションの方式
+// cursorを指定した場合は、pagingの指定によらずカーソル方式になる
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PagingName {
+    #[default]
+    Offset,
+    Cursor,
+}
+
+pub(crate) fn cursor_position(paging: PagingName, cursor: Option<String>) -> Option<CursorPosition> {
+    match (paging, cursor) {
+        (_, Some(cursor)) => Some(CursorPosition::At(PageCursor::new(cursor))),
+        (PagingName::Cursor, None) => Some(CursorPosition::First),
+        (PagingName::Offset, None) => None,
+    }
+}
+
+const DEFAULT_LIMIT: i64 = 20;
+pub(crate) const fn default_limit() -> i64 {
+    DEFAULT_LIMIT
+}
@@ -0,0 +1,39 @@
+use kernel::model::metadata::BookMetadata;
+use serde::Serialize;
+
+// 蔵書登録画面の入力補助に使う書誌情報
+// isbn, title, authorと出版社などの書誌情報は、そのままCreateBookRequestに使える
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookMetadataResponse {
+    pub isbn: String,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub page_count: Option<i32>,
+    pub language: Option<String>,
+}
+
+impl From<BookMetadata> for BookMetadataResponse {
+    fn from(value: BookMetadata) -> Self {
+        let BookMetadata {
+            isbn,
+            title,
+            author,
+            publisher,
+            published_year,
+            page_count,
+            language,
+        } = value;
+        Self {
+            isbn: isbn.into(),
+            title,
+            author,
+            publisher,
+            published_year,
+            page_count,
+            language,
+        }
+    }
+}
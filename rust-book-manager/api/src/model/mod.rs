@@ -1,4 +1,14 @@
 pub mod book;
+pub mod list;
 pub mod auth;
 pub mod user;
 pub mod checkout;
+pub mod reservation;
+pub mod fine;
+pub mod metadata;
+pub mod tag;
+pub mod cover;
+pub mod book_export;
+pub mod book_import;
+pub mod revision;
+pub mod patch;
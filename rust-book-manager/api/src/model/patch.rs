@@ -0,0 +1,59 @@
+use serde::{Deserialize, Deserializer};
+use shared::error::{AppError, AppResult};
+
+// JSON Merge Patch(RFC 7396)で受け取る1つのフィールドの値
+// フィールドを省略した場合はUnchanged、nullの場合はRemove、それ以外はSetになる
+// 省略とnullを区別するため、構造体には#[serde(default)]を付けて使う
+#[derive(Debug, Default, PartialEq, Eq)]
+pub enum Patch<T> {
+    #[default]
+    Unchanged,
+    Remove,
+    Set(T),
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Patch<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Option::<T>::deserialize(deserializer).map(|value| match value {
+            Some(value) => Self::Set(value),
+            None => Self::Remove,
+        })
+    }
+}
+
+impl<T> Patch<T> {
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, Self::Unchanged)
+    }
+
+    // 値を持たないことができるフィールドに適用する
+    pub fn apply(self, current: Option<T>) -> Option<T> {
+        match self {
+            Self::Unchanged => current,
+            Self::Remove => None,
+            Self::Set(value) => Some(value),
+        }
+    }
+
+    // 必須のフィールドに適用する。nullで取り除くことはできない
+    pub fn apply_required(self, current: T, field: &str) -> AppResult<T> {
+        match self {
+            Self::Unchanged => Ok(current),
+            Self::Remove => Err(AppError::BadRequest(format!(
+                "{field}はnullにできません。"
+            ))),
+            Self::Set(value) => Ok(value),
+        }
+    }
+}
+
+impl<T: Default> Patch<T> {
+    // 空の値を持てるフィールド(説明文や件名分類など)に適用する。nullの場合は空にする
+    pub fn apply_or_default(self, current: T) -> T {
+        match self {
+            Self::Unchanged => current,
+            Self::Remove => T::default(),
+            Self::Set(value) => value,
+        }
+    }
+}
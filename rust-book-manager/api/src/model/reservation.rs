@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    id::{BookId, ReservationId, UserId},
+    reservation::Reservation,
+};
+use serde::Serialize;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservationsResponse {
+    pub items: Vec<ReservationResponse>,
+}
+
+impl From<Vec<Reservation>> for ReservationsResponse {
+    fn from(value: Vec<Reservation>) -> Self {
+        Self {
+            items: value.into_iter().map(ReservationResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReservationResponse {
+    pub id: ReservationId,
+    pub book_id: BookId,
+    pub reserved_by: UserId,
+    pub reserved_at: DateTime<Utc>,
+    pub position: i64,
+    pub pickup_expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<Reservation> for ReservationResponse {
+    fn from(value: Reservation) -> Self {
+        let Reservation {
+            id,
+            book_id,
+            reserved_by,
+            reserved_at,
+            position,
+            pickup_expires_at,
+        } = value;
+        Self {
+            id,
+            book_id,
+            reserved_by,
+            reserved_at,
+            position,
+            pickup_expires_at,
+        }
+    }
+}
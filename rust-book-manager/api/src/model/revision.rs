@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use kernel::model::{
+    book::display_author,
+    id::{BookId, UserId},
+    revision::{BookEditor, BookField, BookRevision, BookSnapshot},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookRevisionsResponse {
+    pub items: Vec<BookRevisionResponse>,
+}
+
+impl From<Vec<BookRevision>> for BookRevisionsResponse {
+    fn from(value: Vec<BookRevision>) -> Self {
+        Self {
+            items: value.into_iter().map(BookRevisionResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookRevisionResponse {
+    pub book_id: BookId,
+    pub revision: i32,
+    pub title: String,
+    // 著者を連結した表示用の著者名
+    pub author: String,
+    pub authors: Vec<String>,
+    pub isbn: String,
+    pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    pub changed_fields: Vec<BookFieldName>,
+    // 編集したユーザーが削除されている場合はNone
+    pub edited_by: Option<BookEditorResponse>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<BookRevision> for BookRevisionResponse {
+    fn from(value: BookRevision) -> Self {
+        let BookRevision {
+            book_id,
+            revision,
+            snapshot:
+                BookSnapshot {
+                    title,
+                    authors,
+                    isbn,
+                    description,
+                    publisher,
+                    published_year,
+                    language,
+                    page_count,
+                    edition,
+                    subjects,
+                },
+            changed_fields,
+            edited_by,
+            created_at,
+        } = value;
+        Self {
+            book_id,
+            revision,
+            title,
+            author: display_author(&authors),
+            authors,
+            isbn: isbn.into(),
+            description,
+            publisher,
+            published_year,
+            language,
+            page_count,
+            edition,
+            subjects,
+            changed_fields: changed_fields.into_iter().map(BookFieldName::from).collect(),
+            edited_by: edited_by.map(BookEditorResponse::from),
+            created_at,
+        }
+    }
+}
+
+// BookResponseのフィールド名と揃える
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BookFieldName {
+    Title,
+    Authors,
+    Isbn,
+    Description,
+    Publisher,
+    PublishedYear,
+    Language,
+    PageCount,
+    Edition,
+    Subjects,
+}
+
+impl From<BookField> for BookFieldName {
+    fn from(value: BookField) -> Self {
+        match value {
+            BookField::Title => Self::Title,
+            BookField::Authors => Self::Authors,
+            BookField::Isbn => Self::Isbn,
+            BookField::Description => Self::Description,
+            BookField::Publisher => Self::Publisher,
+            BookField::PublishedYear => Self::PublishedYear,
+            BookField::Language => Self::Language,
+            BookField::PageCount => Self::PageCount,
+            BookField::Edition => Self::Edition,
+            BookField::Subjects => Self::Subjects,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookEditorResponse {
+    pub id: UserId,
+    pub name: String,
+}
+
+impl From<BookEditor> for BookEditorResponse {
+    fn from(value: BookEditor) -> Self {
+        let BookEditor { id, name } = value;
+        Self { id, name }
+    }
+}
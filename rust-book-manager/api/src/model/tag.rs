@@ -0,0 +1,57 @@
+use garde::Validate;
+use kernel::model::{id::TagId, tag::Tag};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AddBookTagRequest {
+    #[garde(length(min = 1, max = 64), custom(validate_tag_name))]
+    pub name: String,
+}
+
+// 前後の空白や、パスパラメータに使えない'/'を含むタグ名は受け付けない
+fn validate_tag_name(value: &str, _context: &()) -> garde::Result {
+    if value.trim() != value || value.contains('/') {
+        return Err(garde::Error::new(
+            "tag name must not have surrounding spaces or contain '/'",
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagsResponse {
+    pub items: Vec<TagResponse>,
+}
+
+impl From<Vec<Tag>> for TagsResponse {
+    fn from(value: Vec<Tag>) -> Self {
+        Self {
+            items: value.into_iter().map(TagResponse::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagResponse {
+    pub id: TagId,
+    pub name: String,
+    pub book_count: i64,
+}
+
+impl From<Tag> for TagResponse {
+    fn from(value: Tag) -> Self {
+        let Tag {
+            id,
+            name,
+            book_count,
+        } = value;
+        Self {
+            id,
+            name,
+            book_count,
+        }
+    }
+}
@@ -2,15 +2,22 @@ use derive_new::new;
 use garde::Validate;
 use kernel::model::{
     id::UserId,
+    list::PaginatedList,
     role::Role,
     user::{
-        event::{CreateUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{CreateUser, UpdateUserPassword, UpdateUserProfile, UpdateUserRole},
+        User, UserListOptions,
     },
 };
 use serde::{Deserialize, Serialize};
+use shared::error::{AppError, AppResult};
 use strum::VariantNames;
 
+use super::{
+    list::{cursor_position, default_limit, PagingName},
+    patch::Patch,
+};
+
 #[derive(Serialize, Deserialize, VariantNames)]
 #[strum(serialize_all = "kebab-case")]
 pub enum RoleName {
@@ -36,10 +43,76 @@ impl From<RoleName> for Role {
     }
 }
 
+// ユーザー一覧のページネーションをクエリで受け取るための型
+// ページネーションを導入する前のクライアントのため、いずれも指定しない場合は全件を返す
+#[derive(Debug, Deserialize, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UserListQuery {
+    #[garde(inner(range(min = 0)))]
+    pub limit: Option<i64>,
+    #[garde(inner(range(min = 0)))]
+    pub offset: Option<i64>,
+    #[garde(skip)]
+    #[serde(default)]
+    pub paging: PagingName,
+    #[garde(inner(length(min = 1)))]
+    pub cursor: Option<String>,
+}
+
+impl From<UserListQuery> for UserListOptions {
+    fn from(value: UserListQuery) -> Self {
+        let UserListQuery {
+            limit,
+            offset,
+            paging,
+            cursor,
+        } = value;
+        let cursor = cursor_position(paging, cursor);
+        let limit = match (limit, offset, &cursor) {
+            (None, None, None) => None,
+            (limit, _, _) => Some(limit.unwrap_or_else(default_limit)),
+        };
+        Self {
+            limit,
+            offset: offset.unwrap_or_default(),
+            cursor,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct UsersResponse {
+pub struct PaginatedUserResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+    pub limit: i64,
+    pub offset: i64,
     pub items: Vec<UserResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
+}
+
+impl From<PaginatedList<User>> for PaginatedUserResponse {
+    fn from(value: PaginatedList<User>) -> Self {
+        let PaginatedList {
+            total,
+            limit,
+            offset,
+            items,
+            next_cursor,
+            prev_cursor,
+        } = value;
+        Self {
+            total,
+            limit,
+            offset,
+            items: items.into_iter().map(UserResponse::from).collect(),
+            next_cursor: next_cursor.map(String::from),
+            prev_cursor: prev_cursor.map(String::from),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -100,6 +173,65 @@ impl From<UpdateUserPasswordRequestWithUserId> for UpdateUserPassword {
     }
 }
 
+// JSON Merge Patchでユーザー自身のプロフィールを更新するための型
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct PatchUserProfileRequest {
+    pub name: Patch<String>,
+    pub email: Patch<String>,
+    // メールアドレスを変更する場合は必須
+    pub current_password: Option<String>,
+}
+
+impl PatchUserProfileRequest {
+    pub fn apply(self, user: &User) -> AppResult<UpdateUserProfileRequest> {
+        let email = self.email.apply_required(user.email.clone(), "email")?;
+        if email != user.email && self.current_password.is_none() {
+            return Err(AppError::BadRequest(
+                "メールアドレスを変更する場合は、currentPasswordを指定してください。".into(),
+            ));
+        }
+        Ok(UpdateUserProfileRequest {
+            name: self.name.apply_required(user.name.clone(), "name")?,
+            email,
+            current_password: self.current_password,
+        })
+    }
+}
+
+// 現在のプロフィールにパッチを適用した、更新後のプロフィール
+#[derive(Debug, Validate)]
+pub struct UpdateUserProfileRequest {
+    #[garde(length(min = 1))]
+    name: String,
+    #[garde(email)]
+    email: String,
+    #[garde(skip)]
+    current_password: Option<String>,
+}
+
+#[derive(new)]
+pub struct UpdateUserProfileRequestWithUserId(UserId, UpdateUserProfileRequest);
+
+impl From<UpdateUserProfileRequestWithUserId> for UpdateUserProfile {
+    fn from(value: UpdateUserProfileRequestWithUserId) -> Self {
+        let UpdateUserProfileRequestWithUserId(
+            user_id,
+            UpdateUserProfileRequest {
+                name,
+                email,
+                current_password,
+            },
+        ) = value;
+        UpdateUserProfile {
+            user_id,
+            name,
+            email,
+            current_password,
+        }
+    }
+}
+
 #[derive(Deserialize, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateUserRequest {
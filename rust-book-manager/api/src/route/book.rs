@@ -1,31 +1,82 @@
 use axum::{
-    routing::{delete, get, post, put},
+    extract::DefaultBodyLimit,
+    routing::{delete, get, patch, post, put},
     Router,
 };
+use kernel::model::cover::MAX_COVER_SIZE;
 use registry::AppRegistry;
 
 use crate::handler::{
     book::{
-        delete_book, update_book, register_book, show_book, show_book_list
+        delete_book, delete_book_copy, export_books, import_books, patch_book, update_book,
+        register_book, register_book_copy, restore_book, show_book, show_book_list
     },
+    cover::{show_book_cover, upload_book_cover},
     checkout::{
-        checkout_book, checkout_history, return_book, show_checked_out_list,
+        checkout_book, checkout_book_on_behalf, checkout_history, force_return_book,
+        renew_checkout, report_damaged, report_lost, report_repaired, return_book,
+        show_checked_out_list, show_overdue_list,
     },
+    reservation::{cancel_reservation, reserve_book, show_reservation_list},
+    revision::{revert_book, show_book_revisions},
+    tag::{add_book_tag, remove_book_tag},
 };
 
 pub fn build_book_routers() -> Router<AppRegistry> {
     let books_routers = Router::new()
         .route("/", post(register_book))
         .route("/", get(show_book_list))
+        .route("/import", post(import_books))
+        .route("/export", get(export_books))
         .route("/:book_id", get(show_book))
         .route("/:book_id", put(update_book))
-        .route("/:book_id", delete(delete_book));
+        .route("/:book_id", patch(patch_book))
+        .route("/:book_id", delete(delete_book))
+        .route("/:book_id/restored", put(restore_book))
+        .route("/:book_id/copies", post(register_book_copy))
+        .route("/:book_id/copies/:copy_id", delete(delete_book_copy));
 
     let checkout_router = Router::new()
         .route("/checkouts", get(show_checked_out_list))
+        .route("/checkouts/overdue", get(show_overdue_list))
         .route("/:book_id/checkouts", post(checkout_book))
+        .route("/:book_id/checkouts/on-behalf", post(checkout_book_on_behalf))
         .route("/:book_id/checkouts/:checkout_id/returned", put(return_book),)
+        .route("/:book_id/checkouts/:checkout_id/force-returned", put(force_return_book))
+        .route("/:book_id/checkouts/:checkout_id/renewed", put(renew_checkout))
+        .route("/:book_id/checkouts/:checkout_id/lost", put(report_lost))
+        .route("/:book_id/copies/:copy_id/damaged", put(report_damaged))
+        .route("/:book_id/copies/:copy_id/repaired", put(report_repaired))
         .route("/:book_id/checkout-history", get(checkout_history));
 
-    Router::new().nest("/books", books_routers.merge(checkout_router))
+    let reservation_router = Router::new()
+        .route("/:book_id/reservations", get(show_reservation_list))
+        .route("/:book_id/reservations", post(reserve_book))
+        .route("/:book_id/reservations/:reservation_id", delete(cancel_reservation));
+
+    let revision_router = Router::new()
+        .route("/:book_id/revisions", get(show_book_revisions))
+        .route("/:book_id/revisions/:revision/reverted", put(revert_book));
+
+    let tag_router = Router::new()
+        .route("/:book_id/tags", post(add_book_tag))
+        .route("/:book_id/tags/:tag_name", delete(remove_book_tag));
+
+    // multipartの区切りやヘッダーの分だけ、表紙画像の上限より少し大きなリクエストまで受け付ける
+    let cover_router = Router::new().route(
+        "/:book_id/cover",
+        get(show_book_cover)
+            .post(upload_book_cover)
+            .layer(DefaultBodyLimit::max(MAX_COVER_SIZE + 64 * 1024)),
+    );
+
+    Router::new().nest(
+        "/books",
+        books_routers
+            .merge(checkout_router)
+            .merge(reservation_router)
+            .merge(revision_router)
+            .merge(tag_router)
+            .merge(cover_router),
+    )
 }
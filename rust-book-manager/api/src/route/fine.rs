@@ -0,0 +1,11 @@
+use axum::{routing::get, Router};
+use registry::AppRegistry;
+
+use crate::handler::fine::{get_my_fines, get_user_fines, list_unpaid_fines, register_fine};
+
+pub fn build_fine_router() -> Router<AppRegistry> {
+    Router::new()
+        .route("/fines", get(list_unpaid_fines))
+        .route("/users/me/fines", get(get_my_fines))
+        .route("/users/:user_id/fines", get(get_user_fines).post(register_fine))
+}
@@ -0,0 +1,8 @@
+use axum::{routing::get, Router};
+use registry::AppRegistry;
+
+use crate::handler::metadata::lookup_isbn;
+
+pub fn build_metadata_router() -> Router<AppRegistry> {
+    Router::new().route("/isbn/:isbn/lookup", get(lookup_isbn))
+}
@@ -1,5 +1,8 @@
 pub mod book;
 pub mod auth;
 pub mod user;
+pub mod fine;
 pub mod v1;
 pub mod health;
+pub mod metadata;
+pub mod tag;
@@ -2,15 +2,19 @@ use axum::Router;
 use registry::AppRegistry;
 
 use super::{
-    book::build_book_routers, health::build_healtth_check_routers,
-    user::build_user_router,
+    book::build_book_routers, fine::build_fine_router,
+    health::build_healtth_check_routers, metadata::build_metadata_router,
+    tag::build_tag_router, user::build_user_router,
 };
 
 pub fn routes() -> Router<AppRegistry> {
     let router = Router::new()
         .merge(build_healtth_check_routers())
         .merge(build_book_routers())
-        .merge(build_user_router());
+        .merge(build_user_router())
+        .merge(build_fine_router())
+        .merge(build_metadata_router())
+        .merge(build_tag_router());
 
     Router::new().nest("/api/v1", router)
 }
\ No newline at end of file
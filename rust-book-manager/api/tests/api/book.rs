@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{str::FromStr, sync::Arc};
 
 use axum::{body::Body, http::Request, Router};
 use rstest::rstest;
@@ -6,17 +6,18 @@ use tower::ServiceExt;
 
 use crate::{
     deserialize_json,
-    helper::{fixture, make_router, v1, TesRequestExt},
+    helper::{fixture, fixture_admin, make_router, v1, TesRequestExt},
 };
 use api::model::book::PaginatedBookResponse;
 use kernel::{
     model::{
-        book::Book,
-        id::{BookId, UserId},
+        book::{isbn::Isbn, Book, BookCopy, CopyStatus},
+        id::{BookCopyId, BookId, UserId},
         list::PaginatedList,
+        metadata::BookMetadata,
         user::BookOwner,
     },
-    repository::book::MockBookRepository,
+    repository::{book::MockBookRepository, metadata::MockBookMetadataRepository},
 };
 
 #[rstest]
@@ -24,6 +25,10 @@ use kernel::{
 #[case("/books?limit=50", 50, 0)]
 #[case("/books?limit=50&offset=20", 50, 20)]
 #[case("/books?offset=20", 20, 20)]
+#[case("/books?search=rust", 20, 0)]
+#[case("/books?available=true&sort=title&order=desc", 20, 0)]
+#[case("/books?paging=cursor&limit=10", 10, 0)]
+#[case("/books?tag=Rust", 20, 0)]
 #[tokio::test]
 async fn show_book_list_with_query_200(
     // 1. fixtureとして、mockオブジェクトを渡している
@@ -41,20 +46,37 @@ async fn show_book_list_with_query_200(
             let items = vec![Book {
                 id: book_id,
                 title: "RustによるWebアプリケーション開発".to_string(),
-                isbn: "".to_string(),
-                author: "Yuki Toyoda".to_string(),
+                isbn: Isbn::from_str("9784065369579")?,
+                authors: vec!["Yuki Toyoda".to_string()],
                 description: "RustによるWebアプリケーション開発".to_string(),
+                publisher: Some("講談社".to_string()),
+                published_year: Some(2024),
+                language: Some("jpn".to_string()),
+                page_count: Some(512),
+                edition: None,
+                subjects: vec![],
+                tags: vec!["Rust".to_string()],
+                cover_updated_at: None,
+                archived_at: None,
+                version: 1,
                 owner: BookOwner {
                     id: UserId::new(),
                     name: "Yuki Toyoda".to_string(),
                 },
-                checkout: None,
+                copies: vec![BookCopy {
+                    id: BookCopyId::new(),
+                    barcode: "0001".to_string(),
+                    status: CopyStatus::Available,
+                    checkout: None,
+                }],
             }];
             Ok(PaginatedList {
-                total: 1,
+                total: Some(1),
                 limit: opt.limit,
                 offset: opt.offset,
                 items,
+                next_cursor: None,
+                prev_cursor: None,
             })
         });
         Arc::new(mock)
@@ -81,6 +103,8 @@ async fn show_book_list_with_query_200(
 #[rstest]
 #[case("/books?limit=-1")]
 #[case("/books?offset=aaa")]
+#[case("/books?search=")]
+#[case("/books?sort=isbn")]
 #[tokio::test]
 async fn show_book_list_with_query_400(
     mut fixture: registry::MockAppRegistryExt,
@@ -95,20 +119,37 @@ async fn show_book_list_with_query_400(
             let items = vec![Book {
                 id: book_id,
                 title: "RustによるWebアプリケーション開発".to_string(),
-                isbn: "".to_string(),
-                author: "Yuki Toyoda".to_string(),
+                isbn: Isbn::from_str("9784065369579")?,
+                authors: vec!["Yuki Toyoda".to_string()],
                 description: "RustによるWebアプリケーション開発".to_string(),
+                publisher: Some("講談社".to_string()),
+                published_year: Some(2024),
+                language: Some("jpn".to_string()),
+                page_count: Some(512),
+                edition: None,
+                subjects: vec![],
+                tags: vec!["Rust".to_string()],
+                cover_updated_at: None,
+                archived_at: None,
+                version: 1,
                 owner: BookOwner {
                     id: UserId::new(),
                     name: "Yuki Toyoda".to_string(),
                 },
-                checkout: None,
+                copies: vec![BookCopy {
+                    id: BookCopyId::new(),
+                    barcode: "0001".to_string(),
+                    status: CopyStatus::Available,
+                    checkout: None,
+                }],
             }];
             Ok(PaginatedList {
-                total: 1,
+                total: Some(1),
                 limit: opt.limit,
                 offset: opt.offset,
                 items,
+                next_cursor: None,
+                prev_cursor: None,
             })
         });
         Arc::new(mock)
@@ -121,4 +162,376 @@ async fn show_book_list_with_query_400(
     assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[rstest]
+#[case("978-4-06-536957-8")]
+#[case("4-06-536957-7")]
+#[case("not an isbn")]
+#[tokio::test]
+async fn register_book_with_invalid_isbn_400(
+    fixture: registry::MockAppRegistryExt,
+    #[case] isbn: &str,
+) -> anyhow::Result<()> {
+    // チェックディジットが合わないISBNでは蔵書を登録できない
+    let app: Router = make_router(fixture);
+
+    let body = serde_json::json!({
+        "title": "RustによるWebアプリケーション開発",
+        "author": "Yuki Toyoda",
+        "isbn": isbn,
+        "description": "",
+    });
+    let req = Request::post(v1("/books"))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(serde_json::json!({ "authors": ["豊田優貴", "松本健太郎"] }), axum::http::StatusCode::CREATED)]
+#[case(serde_json::json!({ "author": "豊田優貴", "publisher": "講談社", "publishedYear": 2024, "language": "jpn", "pageCount": 512, "edition": "初版", "subjects": ["Rust"] }), axum::http::StatusCode::CREATED)]
+#[case(serde_json::json!({}), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "author": "豊田優貴", "authors": ["松本健太郎"] }), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "authors": [""] }), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "author": "豊田優貴", "publishedYear": 24 }), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "author": "豊田優貴", "language": "ja" }), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "author": "豊田優貴", "pageCount": 0 }), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({ "author": "豊田優貴", "subjects": [""] }), axum::http::StatusCode::BAD_REQUEST)]
+#[tokio::test]
+async fn register_book_with_bibliographic_fields(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] fields: serde_json::Value,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 著者は1人以上必要で、authorとauthorsはどちらか一方だけを指定する
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_duplicates().returning(|_| Ok(vec![]));
+        mock.expect_create().returning(|_, _| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let mut body = serde_json::json!({
+        "title": "RustによるWebアプリケーション開発",
+        "isbn": "9784065369579",
+        "description": "",
+    });
+    for (key, value) in fields.as_object().into_iter().flatten() {
+        body[key] = value.clone();
+    }
+    let req = Request::post(v1("/books"))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(false, axum::http::StatusCode::CONFLICT)]
+#[case(true, axum::http::StatusCode::CREATED)]
+#[tokio::test]
+async fn register_duplicate_book(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] confirm_duplicate: bool,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 重複の可能性がある書誌があれば、確認済みでない限り409を返す
+    let existing_book_id = BookId::new();
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_duplicates()
+            .returning(move |_| Ok(vec![existing_book_id]));
+        mock.expect_create().returning(|_, _| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let body = serde_json::json!({
+        "title": "RustによるWebアプリケーション開発",
+        "author": "Yuki Toyoda",
+        "isbn": "9784065369579",
+        "description": "",
+        "confirmDuplicate": confirm_duplicate,
+    });
+    let req = Request::post(v1("/books"))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    if !confirm_duplicate {
+        let result = deserialize_json!(resp, serde_json::Value);
+        assert_eq!(
+            result["existingBookIds"],
+            serde_json::json!([existing_book_id.to_string()])
+        );
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case("4-06-536957-6", axum::http::StatusCode::OK)]
+#[case("9784798061702", axum::http::StatusCode::NOT_FOUND)]
+#[tokio::test]
+async fn lookup_isbn(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] isbn: &str,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 書誌データにあるISBNだけ書誌情報を返し、ISBN-10で指定してもISBN-13で引く
+    fixture.expect_book_metadata_repository().returning(|| {
+        let mut mock = MockBookMetadataRepository::new();
+        mock.expect_find_by_isbn().returning(|isbn| {
+            Ok((isbn.as_str() == "9784065369579").then(|| BookMetadata {
+                isbn: isbn.clone(),
+                title: Some("RustによるWebアプリケーション開発".into()),
+                author: Some("豊田優貴".into()),
+                publisher: Some("講談社".into()),
+                published_year: Some(2024),
+                page_count: Some(480),
+                language: Some("jpn".into()),
+            }))
+        });
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1(&format!("/isbn/{isbn}/lookup")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    if expected == axum::http::StatusCode::OK {
+        let result = deserialize_json!(resp, serde_json::Value);
+        assert_eq!(result["isbn"], "9784065369579");
+        assert_eq!(result["publishedYear"], 2024);
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn restore_book_200(mut fixture_admin: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 管理者はアーカイブした書誌を元に戻せる
+    let book_id = BookId::new();
+    fixture_admin.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_restore()
+            .withf(move |event| event.book_id == book_id)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture_admin);
+
+    let req = Request::put(v1(&format!("/books/{book_id}/restored")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn restore_book_403(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 管理者以外は書誌を元に戻せない
+    let app: Router = make_router(fixture);
+
+    let req = Request::put(v1(&format!("/books/{}/restored", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+fn book_with_version(book_id: BookId, version: i32) -> Book {
+    Book {
+        id: book_id,
+        title: "RustによるWebアプリケーション開発".to_string(),
+        isbn: Isbn::from_str("9784065369579").unwrap(),
+        authors: vec!["Yuki Toyoda".to_string()],
+        description: "".to_string(),
+        publisher: None,
+        published_year: None,
+        language: None,
+        page_count: None,
+        edition: None,
+        subjects: vec![],
+        tags: vec![],
+        cover_updated_at: None,
+        archived_at: None,
+        version,
+        owner: BookOwner {
+            id: UserId::new(),
+            name: "Yuki Toyoda".to_string(),
+        },
+        copies: vec![],
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_book_with_etag(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 書誌の版番号をETagとして返す
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(|book_id| Ok(Some(book_with_version(book_id, 3))));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1(&format!("/books/{}", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.headers()["ETag"], "\"3\"");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(Some("\"3\""), axum::http::StatusCode::OK)]
+#[case(Some("*"), axum::http::StatusCode::OK)]
+#[case(Some("\"2\""), axum::http::StatusCode::PRECONDITION_FAILED)]
+#[case(Some("W/\"3\""), axum::http::StatusCode::PRECONDITION_FAILED)]
+#[case(Some("3"), axum::http::StatusCode::BAD_REQUEST)]
+#[case(None, axum::http::StatusCode::PRECONDITION_REQUIRED)]
+#[tokio::test]
+async fn update_book_with_if_match(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] if_match: Option<&str>,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // If-Matchの指定が必須で、現在の書誌のETagと一致する場合だけ、取得時の版番号を前提に更新する
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(|book_id| Ok(Some(book_with_version(book_id, 3))));
+        mock.expect_update()
+            .withf(|event| event.expected_version == 3)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let body = serde_json::json!({
+        "title": "RustによるWebアプリケーション開発",
+        "author": "Yuki Toyoda",
+        "isbn": "9784065369579",
+        "description": "更新後の説明",
+    });
+    let mut req = Request::put(v1(&format!("/books/{}", BookId::new())))
+        .bearer()
+        .application_json();
+    if let Some(if_match) = if_match {
+        req = req.header("If-Match", if_match);
+    }
+    let resp = app.oneshot(req.body(Body::from(body.to_string()))?).await?;
+    assert_eq!(resp.status(), expected);
+    if expected == axum::http::StatusCode::OK {
+        assert_eq!(resp.headers()["ETag"], "\"4\"");
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn patch_book_200(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 指定したフィールドだけを変更し、省略したフィールドは現在の値のまま、nullのフィールドは取り除いて更新する
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(|book_id| {
+            Ok(Some(Book {
+                publisher: Some("講談社".to_string()),
+                subjects: vec!["Rust".to_string()],
+                ..book_with_version(book_id, 3)
+            }))
+        });
+        mock.expect_update()
+            .withf(|event| {
+                event.expected_version == 3
+                    && event.title == "RustによるWebアプリケーション開発"
+                    && event.authors == vec!["Yuki Toyoda"]
+                    && event.isbn.as_str() == "9784065369579"
+                    && event.description == "更新後の説明"
+                    && event.publisher.is_none()
+                    && event.subjects == vec!["Rust"]
+            })
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let body = serde_json::json!({
+        "description": "更新後の説明",
+        "publisher": null,
+    });
+    let req = Request::patch(v1(&format!("/books/{}", BookId::new())))
+        .bearer()
+        .header("Content-Type", "application/merge-patch+json")
+        .header("If-Match", "\"3\"")
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.headers()["ETag"], "\"4\"");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(serde_json::json!({"title": null}), Some("\"3\""), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"author": null}), Some("\"3\""), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"author": "A", "authors": ["B"]}), Some("\"3\""), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"pageCount": 0}), Some("\"3\""), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"owner": "someone"}), Some("\"3\""), axum::http::StatusCode::UNPROCESSABLE_ENTITY)]
+#[case(serde_json::json!({"description": "説明"}), Some("\"2\""), axum::http::StatusCode::PRECONDITION_FAILED)]
+#[case(serde_json::json!({"description": "説明"}), Some("2"), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"description": "説明"}), None, axum::http::StatusCode::PRECONDITION_REQUIRED)]
+#[tokio::test]
+async fn patch_book_error(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] body: serde_json::Value,
+    #[case] if_match: Option<&str>,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 必須のフィールドは取り除けず、適用後の書誌は更新時と同じ検証を通る必要がある
+    // If-Matchがない場合や、現在の書誌のETagと一致しない場合は更新しない
+    fixture.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id()
+            .returning(|book_id| Ok(Some(book_with_version(book_id, 3))));
+        mock.expect_update().never();
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let mut req = Request::patch(v1(&format!("/books/{}", BookId::new())))
+        .bearer()
+        .application_json();
+    if let Some(if_match) = if_match {
+        req = req.header("If-Match", if_match);
+    }
+    let resp = app.oneshot(req.body(Body::from(body.to_string()))?).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{
+    body::{to_bytes, Body},
+    http::{header, Request},
+    Router,
+};
+use chrono::{TimeZone, Utc};
+use futures::{stream, StreamExt};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::helper::{fixture, fixture_admin, make_router, v1, TesRequestExt};
+use kernel::{
+    model::{
+        book::{isbn::Isbn, Book, BookCopy, Checkout, CopyStatus},
+        id::{BookCopyId, BookId, CheckoutId, UserId},
+        user::{BookOwner, CheckoutUser},
+    },
+    repository::book::MockBookRepository,
+};
+
+fn book() -> Book {
+    Book {
+        id: BookId::from_str("9890736e-a4e4-461a-a77d-eac3517ef11b").unwrap(),
+        title: "Rust <入門> & 実践".to_string(),
+        authors: vec!["初田直也".to_string(), "山田良明".to_string()],
+        isbn: Isbn::from_str("9784798061702").unwrap(),
+        description: "".to_string(),
+        publisher: Some("ソシム".to_string()),
+        published_year: Some(2019),
+        language: Some("jpn".to_string()),
+        page_count: None,
+        edition: None,
+        subjects: vec![],
+        tags: vec!["Rust".to_string()],
+        cover_updated_at: None,
+        archived_at: None,
+        version: 1,
+        owner: BookOwner {
+            id: UserId::new(),
+            name: "Eleazar Fig".to_string(),
+        },
+        copies: vec![
+            BookCopy {
+                id: BookCopyId::new(),
+                barcode: "0001".to_string(),
+                status: CopyStatus::Available,
+                checkout: Some(Checkout {
+                    checkout_id: CheckoutId::new(),
+                    checked_out_by: CheckoutUser {
+                        id: UserId::new(),
+                        name: "Yuki Toyoda".to_string(),
+                    },
+                    checked_out_at: Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap(),
+                    due_at: Utc.with_ymd_and_hms(2024, 12, 15, 0, 0, 0).unwrap(),
+                }),
+            },
+            BookCopy {
+                id: BookCopyId::new(),
+                barcode: "0002".to_string(),
+                status: CopyStatus::Available,
+                checkout: None,
+            },
+        ],
+    }
+}
+
+#[rstest]
+#[case("csv", "text/csv; charset=utf-8", &[
+    "id,title,authors,isbn,description,publisher,published_year,language,page_count,edition,subjects,tags,owner_id,owner_name,total_copies,available_copies,checked_out_by,next_due_at\n",
+    ",Rust <入門> & 実践,初田直也; 山田良明,9784798061702,,ソシム,2019,jpn,,,,Rust,",
+    ",Eleazar Fig,2,1,Yuki Toyoda,2024-12-15T00:00:00+00:00\n",
+])]
+#[case("jsonl", "application/x-ndjson", &[
+    r#""title":"Rust <入門> & 実践""#,
+    r#""checkedOutBy":{"id":"#,
+    "}\n",
+])]
+#[case("marcxml", "application/marcxml+xml", &[
+    r#"<collection xmlns="http://www.loc.gov/MARC21/slim">"#,
+    r#"<subfield code="a">Rust &lt;入門&gt; &amp; 実践</subfield>"#,
+    r#"<datafield tag="700" ind1="1" ind2=" ">"#,
+    r#"<subfield code="j">CheckedOut</subfield>"#,
+    r#"<subfield code="d">2024-12-15T00:00:00+00:00</subfield>"#,
+    "</collection>\n",
+])]
+#[tokio::test]
+async fn export_books(
+    mut fixture_admin: registry::MockAppRegistryExt,
+    #[case] format: &str,
+    #[case] content_type: &str,
+    #[case] expected: &[&str],
+) -> anyhow::Result<()> {
+    // 管理者は全書誌を指定した形式で出力できる
+    fixture_admin.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_stream_all()
+            .returning(|| stream::iter(vec![Ok(book())]).boxed());
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture_admin);
+
+    let req = Request::get(v1(&format!("/books/export?format={format}")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], content_type);
+
+    let body = to_bytes(resp.into_body(), usize::MAX).await?;
+    let body = String::from_utf8(body.to_vec())?;
+    for part in expected {
+        assert!(body.contains(part), "{part} not found in {body}");
+    }
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/books/export?format=csv", axum::http::StatusCode::FORBIDDEN)]
+#[case("/books/export?format=pdf", axum::http::StatusCode::BAD_REQUEST)]
+#[tokio::test]
+async fn export_books_error(
+    fixture: registry::MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 管理者以外は出力できず、未対応の形式は指定できない
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
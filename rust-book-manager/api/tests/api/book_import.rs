@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use axum::{body::Body, http::Request, Router};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::{
+    deserialize_json,
+    helper::{fixture, fixture_auth, make_router, v1, TesRequestExt},
+};
+use api::model::book_import::BookImportResponse;
+use kernel::{
+    model::{role::Role, user::User},
+    repository::{book::MockBookRepository, user::MockUserRepository},
+};
+
+#[rstest]
+#[tokio::test]
+async fn import_books(mut fixture_auth: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 問題のない行だけを登録し、問題のあった行は行番号と理由を返す
+    fixture_auth.expect_book_repository().returning(|| {
+        let mut mock = MockBookRepository::new();
+        mock.expect_create_many()
+            .withf(|books| books.len() == 2)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    fixture_auth.expect_user_repository().returning(|| {
+        let mut mock = MockUserRepository::new();
+        mock.expect_find_current_user().returning(|id| {
+            Ok(Some(User {
+                id,
+                name: "dummy-user".to_string(),
+                email: "dummy@example.com".to_string(),
+                role: Role::User,
+            }))
+        });
+        mock.expect_find_by_email().returning(|_| Ok(None));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture_auth);
+
+    let csv = [
+        "title,author,isbn,description,owner_email",
+        "RustによるWebアプリケーション開発,豊田優貴,978-4-06-536957-9,,",
+        "\"実践Rust, 入門\",初田直也,9784798061702,\"複数行の\n説明文\",",
+        "ISBNが不正,著者,978-4-06-536957-8,,",
+        ",著者なし,9784065301951,,",
+        "所有者が不明,著者,9784065301951,,nobody@example.com",
+        "列が足りない,著者",
+    ]
+    .join("\n");
+    let req = Request::post(v1("/books/import"))
+        .bearer()
+        .header("Content-Type", "text/csv")
+        .body(Body::from(csv))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let result = deserialize_json!(resp, BookImportResponse);
+    assert_eq!(result.imported, 2);
+    let rows = result.errors.iter().map(|e| e.row).collect::<Vec<_>>();
+    assert_eq!(rows, vec![5, 6, 7, 8]);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn import_books_without_required_columns(
+    fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    let app: Router = make_router(fixture);
+
+    let req = Request::post(v1("/books/import"))
+        .bearer()
+        .header("Content-Type", "text/csv")
+        .body(Body::from("title,isbn\nRust,9784065369579\n"))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{body::Body, http::Request, Router};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::helper::{fixture, fixture_admin, make_router, v1, TesRequestExt};
+use kernel::{
+    model::id::{BookCopyId, BookId},
+    repository::checkout::MockCheckoutRepository,
+};
+
+const COPY_ID: &str = "3c9ff8e7-6f43-4b7e-a3bb-9c7d8b5f5f0e";
+
+#[rstest]
+#[tokio::test]
+async fn show_overdue_list_403_for_non_admin(
+    fixture: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    // 管理者以外は延滞一覧を取得できない
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1("/books/checkouts/overdue")).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::FORBIDDEN);
+
+    Ok(())
+}
+
+#[rstest]
+#[case(None, "", Some(None), axum::http::StatusCode::CREATED)]
+#[case(Some("application/json"), r#"{}"#, Some(None), axum::http::StatusCode::CREATED)]
+#[case(
+    Some("application/json"),
+    r#"{"copyId": "3c9ff8e7-6f43-4b7e-a3bb-9c7d8b5f5f0e"}"#,
+    Some(Some(COPY_ID)),
+    axum::http::StatusCode::CREATED
+)]
+#[case(Some("application/json"), r#"{"copyId": "#, None, axum::http::StatusCode::BAD_REQUEST)]
+#[case(Some("application/json"), r#"{"copyId": "0001"}"#, None, axum::http::StatusCode::BAD_REQUEST)]
+#[case(
+    Some("text/plain"),
+    r#"{"copyId": "3c9ff8e7-6f43-4b7e-a3bb-9c7d8b5f5f0e"}"#,
+    None,
+    axum::http::StatusCode::BAD_REQUEST
+)]
+#[tokio::test]
+async fn checkout_book_with_body(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] content_type: Option<&str>,
+    #[case] body: &'static str,
+    #[case] expected_copy_id: Option<Option<&'static str>>,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 本文を省略した場合だけ蔵書の指定がないものとして扱い、解釈できない本文では貸し出さない
+    fixture.expect_checkout_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        match expected_copy_id {
+            Some(copy_id) => {
+                let copy_id = copy_id.map(|id| BookCopyId::from_str(id).unwrap());
+                mock.expect_create()
+                    .withf(move |event| event.copy_id == copy_id)
+                    .returning(|_| Ok(()));
+            }
+            None => {
+                mock.expect_create().never();
+            }
+        }
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let mut req = Request::post(v1(&format!("/books/{}/checkouts", BookId::new()))).bearer();
+    if let Some(content_type) = content_type {
+        req = req.header("Content-Type", content_type);
+    }
+    let resp = app.oneshot(req.body(Body::from(body))?).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn report_repaired_200(mut fixture_admin: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 管理者は破損した蔵書の修理を報告できる
+    let book_id = BookId::new();
+    let copy_id = BookCopyId::from_str(COPY_ID)?;
+    fixture_admin.expect_checkout_repository().returning(move || {
+        let mut mock = MockCheckoutRepository::new();
+        mock.expect_report_repaired()
+            .withf(move |event| event.book_id == book_id && event.copy_id == copy_id)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture_admin);
+
+    let req = Request::put(v1(&format!("/books/{book_id}/copies/{copy_id}/repaired")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn report_repaired_403(fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 管理者以外は蔵書の修理を報告できない
+    let app: Router = make_router(fixture);
+
+    let req = Request::put(v1(&format!("/books/{}/copies/{COPY_ID}/repaired", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::FORBIDDEN);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use axum::{body::Body, http::Request, Router};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::helper::{fixture, make_router, v1, TesRequestExt};
+use kernel::{
+    model::{
+        cover::{CoverImage, CoverSize, MAX_COVER_SIZE},
+        id::BookId,
+    },
+    repository::cover::MockCoverRepository,
+};
+
+const BOUNDARY: &str = "cover-boundary";
+
+fn multipart_body(field_name: &str, content_type: &str, bytes: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{field_name}\"; filename=\"cover\"\r\nContent-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(bytes);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+#[rstest]
+#[case("file", "image/png", 16, axum::http::StatusCode::CREATED)]
+#[case("file", "image/gif", 16, axum::http::StatusCode::UNPROCESSABLE_ENTITY)]
+#[case("image", "image/png", 16, axum::http::StatusCode::UNPROCESSABLE_ENTITY)]
+#[case("file", "image/png", MAX_COVER_SIZE + 1, axum::http::StatusCode::UNPROCESSABLE_ENTITY)]
+#[tokio::test]
+async fn upload_book_cover(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] field_name: &str,
+    #[case] content_type: &str,
+    #[case] size: usize,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 画像の形式とサイズを確認してから保存する
+    fixture.expect_cover_repository().returning(|| {
+        let mut mock = MockCoverRepository::new();
+        mock.expect_upload().returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::post(v1(&format!("/books/{}/cover", BookId::new())))
+        .bearer()
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(multipart_body(field_name, content_type, &vec![0u8; size])))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_book_cover(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_cover_repository().returning(|| {
+        let mut mock = MockCoverRepository::new();
+        mock.expect_find().returning(|_, size| {
+            Ok((size == CoverSize::Small).then(|| CoverImage {
+                content_type: "image/jpeg".into(),
+                bytes: vec![0xff, 0xd8],
+            }))
+        });
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    // 表紙画像の取得には認証を求めない
+    let book_id = BookId::new();
+    let req = Request::get(v1(&format!("/books/{book_id}/cover?size=small"))).body(Body::empty())?;
+    let resp = app.clone().oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.headers()["Content-Type"], "image/jpeg");
+
+    let req = Request::get(v1(&format!("/books/{book_id}/cover"))).body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+
+    Ok(())
+}
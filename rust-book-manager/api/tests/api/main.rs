@@ -1,2 +1,9 @@
 mod book;
-mod helper;
\ No newline at end of file
+mod book_export;
+mod book_import;
+mod checkout;
+mod cover;
+mod helper;
+mod revision;
+mod tag;
+mod user;
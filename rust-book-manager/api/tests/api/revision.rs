@@ -0,0 +1,155 @@
+use std::{str::FromStr, sync::Arc};
+
+use axum::{body::Body, http::Request, Router};
+use chrono::Utc;
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::{
+    deserialize_json,
+    helper::{fixture, fixture_admin, make_router, v1, TesRequestExt},
+};
+use api::model::revision::{BookFieldName, BookRevisionsResponse};
+use kernel::{
+    model::{
+        book::{isbn::Isbn, Book},
+        id::{BookId, UserId},
+        revision::{BookEditor, BookField, BookRevision, BookSnapshot},
+        user::BookOwner,
+    },
+    repository::book::MockBookRepository,
+};
+
+fn snapshot(description: &str) -> BookSnapshot {
+    BookSnapshot {
+        title: "RustによるWebアプリケーション開発".to_string(),
+        authors: vec!["豊田優貴".to_string()],
+        isbn: Isbn::from_str("9784065369579").unwrap(),
+        description: description.to_string(),
+        publisher: None,
+        published_year: None,
+        language: None,
+        page_count: None,
+        edition: None,
+        subjects: vec![],
+    }
+}
+
+#[rstest]
+#[tokio::test]
+async fn show_book_revisions(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    // 書誌の版を新しい順に、変更された項目と編集者つきで返す
+    let book_id = BookId::new();
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_revisions().returning(move |book_id| {
+            Ok(vec![
+                BookRevision {
+                    book_id,
+                    revision: 2,
+                    snapshot: snapshot("更新後の説明"),
+                    changed_fields: vec![BookField::Description, BookField::PageCount],
+                    edited_by: None,
+                    created_at: Utc::now(),
+                },
+                BookRevision {
+                    book_id,
+                    revision: 1,
+                    snapshot: snapshot(""),
+                    changed_fields: BookField::ALL.to_vec(),
+                    edited_by: Some(BookEditor {
+                        id: UserId::new(),
+                        name: "Yuki Toyoda".to_string(),
+                    }),
+                    created_at: Utc::now(),
+                },
+            ])
+        });
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1(&format!("/books/{book_id}/revisions")))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let result = deserialize_json!(resp, BookRevisionsResponse);
+    assert_eq!(result.items.len(), 2);
+    assert_eq!(result.items[0].revision, 2);
+    assert_eq!(
+        result.items[0].changed_fields,
+        vec![BookFieldName::Description, BookFieldName::PageCount]
+    );
+    assert!(result.items[0].edited_by.is_none());
+    assert_eq!(result.items[1].edited_by.as_ref().unwrap().name, "Yuki Toyoda");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(true, axum::http::StatusCode::OK)]
+#[case(false, axum::http::StatusCode::FORBIDDEN)]
+#[tokio::test]
+async fn revert_book(
+    fixture: registry::MockAppRegistryExt,
+    fixture_admin: registry::MockAppRegistryExt,
+    #[case] admin: bool,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 書誌の所有者でなくても管理者であれば以前の版に戻せるが、それ以外のユーザーは戻せない
+    let mut fixture = if admin { fixture_admin } else { fixture };
+    fixture.expect_book_repository().returning(move || {
+        let mut mock = MockBookRepository::new();
+        mock.expect_find_by_id().returning(|book_id| {
+            let BookSnapshot {
+                title,
+                authors,
+                isbn,
+                description,
+                publisher,
+                published_year,
+                language,
+                page_count,
+                edition,
+                subjects,
+            } = snapshot("");
+            Ok(Some(Book {
+                id: book_id,
+                title,
+                authors,
+                isbn,
+                description,
+                publisher,
+                published_year,
+                language,
+                page_count,
+                edition,
+                subjects,
+                tags: vec![],
+                cover_updated_at: None,
+                archived_at: None,
+                version: 1,
+                owner: BookOwner {
+                    id: UserId::new(),
+                    name: "Yuki Toyoda".to_string(),
+                },
+                copies: vec![],
+            }))
+        });
+        mock.expect_revert()
+            .withf(|event| event.revision == 1 && event.expected_version == 1)
+            .returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::put(v1(&format!("/books/{}/revisions/1/reverted", BookId::new())))
+        .bearer()
+        .body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use axum::{body::Body, http::Request, Router};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::{
+    deserialize_json,
+    helper::{fixture, make_router, v1, TesRequestExt},
+};
+use api::model::tag::TagsResponse;
+use kernel::{
+    model::{
+        id::{BookId, TagId},
+        tag::Tag,
+    },
+    repository::tag::MockTagRepository,
+};
+
+#[rstest]
+#[tokio::test]
+async fn show_tag_list(mut fixture: registry::MockAppRegistryExt) -> anyhow::Result<()> {
+    fixture.expect_tag_repository().returning(|| {
+        let mut mock = MockTagRepository::new();
+        mock.expect_find_all().returning(|| {
+            Ok(vec![Tag {
+                id: TagId::new(),
+                name: "Rust".into(),
+                book_count: 2,
+            }])
+        });
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let req = Request::get(v1("/tags")).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let result = deserialize_json!(resp, TagsResponse);
+    assert_eq!(result.items.len(), 1);
+    assert_eq!(result.items[0].book_count, 2);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("Rust", axum::http::StatusCode::CREATED)]
+#[case("", axum::http::StatusCode::BAD_REQUEST)]
+#[case(" Rust", axum::http::StatusCode::BAD_REQUEST)]
+#[case("Rust/Web", axum::http::StatusCode::BAD_REQUEST)]
+#[tokio::test]
+async fn add_book_tag(
+    mut fixture: registry::MockAppRegistryExt,
+    #[case] name: &str,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    fixture.expect_tag_repository().returning(|| {
+        let mut mock = MockTagRepository::new();
+        mock.expect_add_to_book().returning(|_| Ok(()));
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture);
+
+    let body = serde_json::json!({ "name": name });
+    let req = Request::post(v1(&format!("/books/{}/tags", BookId::new())))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use axum::{body::Body, http::Request, Router};
+use rstest::rstest;
+use tower::ServiceExt;
+
+use crate::{
+    deserialize_json,
+    helper::{fixture_auth, make_router, v1, TesRequestExt},
+};
+use api::model::user::{PaginatedUserResponse, UserResponse};
+use kernel::{
+    model::{id::UserId, list::PaginatedList, role::Role, user::User},
+    repository::user::MockUserRepository,
+};
+
+// 現在のユーザーを返し、プロフィールの更新内容をそのまま反映したユーザーを返すモック
+fn user_repository(expect_update: bool) -> MockUserRepository {
+    let mut mock = MockUserRepository::new();
+    mock.expect_find_current_user().returning(|id| {
+        Ok(Some(User {
+            id,
+            name: "dummy-user".to_string(),
+            email: "dummy@example.com".to_string(),
+            role: Role::User,
+        }))
+    });
+    if expect_update {
+        mock.expect_update_profile()
+            .withf(|event| event.current_password.as_deref() == Some("password"))
+            .returning(|event| {
+                Ok(User {
+                    id: event.user_id,
+                    name: event.name,
+                    email: event.email,
+                    role: Role::User,
+                })
+            });
+    } else {
+        mock.expect_update_profile().never();
+    }
+    mock
+}
+
+#[rstest]
+#[tokio::test]
+async fn patch_current_user_200(
+    mut fixture_auth: registry::MockAppRegistryExt,
+) -> anyhow::Result<()> {
+    // 指定したフィールドだけを変更し、省略したフィールドは現在の値のまま更新する
+    fixture_auth
+        .expect_user_repository()
+        .returning(|| Arc::new(user_repository(true)));
+    let app: Router = make_router(fixture_auth);
+
+    let body = serde_json::json!({ "email": "new@example.com", "currentPassword": "password" });
+    let req = Request::patch(v1("/users/me"))
+        .bearer()
+        .header("Content-Type", "application/merge-patch+json")
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let result = deserialize_json!(resp, UserResponse);
+    assert_eq!(result.name, "dummy-user");
+    assert_eq!(result.email, "new@example.com");
+
+    Ok(())
+}
+
+#[rstest]
+#[case(serde_json::json!({"name": null}), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"name": ""}), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"email": "not-an-email", "currentPassword": "password"}), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"email": "new@example.com"}), axum::http::StatusCode::BAD_REQUEST)]
+#[case(serde_json::json!({"role": "admin"}), axum::http::StatusCode::UNPROCESSABLE_ENTITY)]
+#[tokio::test]
+async fn patch_current_user_error(
+    mut fixture_auth: registry::MockAppRegistryExt,
+    #[case] body: serde_json::Value,
+    #[case] expected: axum::http::StatusCode,
+) -> anyhow::Result<()> {
+    // 名前とメールアドレスは取り除けず、ロールなどプロフィール以外のフィールドは変更できない
+    // メールアドレスを変更する場合は現在のパスワードが必要
+    fixture_auth
+        .expect_user_repository()
+        .returning(|| Arc::new(user_repository(false)));
+    let app: Router = make_router(fixture_auth);
+
+    let req = Request::patch(v1("/users/me"))
+        .bearer()
+        .application_json()
+        .body(Body::from(body.to_string()))?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), expected);
+
+    Ok(())
+}
+
+#[rstest]
+#[case("/users", None)]
+#[case("/users?limit=5", Some(5))]
+#[case("/users?offset=10", Some(20))]
+#[case("/users?paging=cursor", Some(20))]
+#[tokio::test]
+async fn list_users_200(
+    mut fixture_auth: registry::MockAppRegistryExt,
+    #[case] path: &str,
+    #[case] expected_limit: Option<i64>,
+) -> anyhow::Result<()> {
+    // ページネーションの指定がない場合は件数を制限せず、いずれかを指定した場合は既定の件数で区切る
+    fixture_auth.expect_user_repository().returning(move || {
+        let mut mock = user_repository(false);
+        mock.expect_find_all()
+            .withf(move |options| options.limit == expected_limit)
+            .returning(|options| {
+                Ok(PaginatedList {
+                    total: Some(1),
+                    limit: options.limit.unwrap_or(1),
+                    offset: options.offset,
+                    items: vec![User {
+                        id: UserId::new(),
+                        name: "dummy-user".to_string(),
+                        email: "dummy@example.com".to_string(),
+                        role: Role::User,
+                    }],
+                    next_cursor: None,
+                    prev_cursor: None,
+                })
+            });
+        Arc::new(mock)
+    });
+    let app: Router = make_router(fixture_auth);
+
+    let req = Request::get(v1(path)).bearer().body(Body::empty())?;
+    let resp = app.oneshot(req).await?;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let result = deserialize_json!(resp, PaginatedUserResponse);
+    assert_eq!(result.items.len(), 1);
+
+    Ok(())
+}
@@ -1,24 +1,66 @@
-use crate::model::id::{BookId, UserId};
+use crate::model::{
+    book::isbn::Isbn,
+    id::{BookCopyId, BookId, UserId},
+};
 
 pub struct CreateBook {
     pub title: String,
-    pub author: String,
-    pub isbn: String,
+    pub authors: Vec<String>,
+    pub isbn: Isbn,
     pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    // 最初の蔵書のバーコード。Noneの場合は蔵書IDから採番する
+    pub barcode: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct UpdateBook {
     pub book_id: BookId,
     pub title: String,
-    pub author: String,
-    pub isbn: String,
+    pub authors: Vec<String>,
+    pub isbn: Isbn,
     pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+    // 更新の前提とする書誌の版番号。書誌がこの版番号のままである場合のみ更新する
+    pub expected_version: i32,
     pub requested_user: UserId,
 }
 
+// 書誌を削除する代わりにアーカイブする
 #[derive(Debug)]
-pub struct DeleteBook {
+pub struct ArchiveBook {
     pub book_id: BookId,
     pub requested_user: UserId,
-}
\ No newline at end of file
+}
+
+// アーカイブした書誌を元に戻す
+#[derive(Debug)]
+pub struct RestoreBook {
+    pub book_id: BookId,
+}
+
+// 書誌に蔵書を追加する
+// barcodeがNoneの場合は蔵書IDから採番する
+#[derive(Debug)]
+pub struct CreateBookCopy {
+    pub book_id: BookId,
+    pub barcode: Option<String>,
+    pub requested_user: UserId,
+}
+
+#[derive(Debug)]
+pub struct DeleteBookCopy {
+    pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub requested_user: UserId,
+}
@@ -0,0 +1,128 @@
+use shared::error::AppError;
+use std::str::FromStr;
+
+// ISBNを表す値型
+// ISBN-10またはISBN-13として受け取り、チェックディジットを検証したうえで、
+// ハイフンを除いたISBN-13の形式に正規化して保持する
+// 新たに保存する値はFromStrで検証したものに限り、データベースから読み込む場合はfrom_storedで変換する
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Isbn(String);
+
+impl Isbn {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    // データベースに保存されている値から作る
+    // 正規化を導入する前に登録され、移行時に正規化できなかった値も読み出せるよう、
+    // 検証に失敗した場合は保存されている値をそのまま保持する
+    pub fn from_stored(value: String) -> Self {
+        Self::from_str(&value).unwrap_or(Self(value))
+    }
+}
+
+impl FromStr for Isbn {
+    type Err = AppError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || AppError::UnprocessableEntity(format!("ISBNの形式が正しくありません: {s}"));
+
+        // 区切りのハイフンと空白は無視する
+        let chars = s
+            .chars()
+            .filter(|c| *c != '-' && !c.is_whitespace())
+            .collect::<Vec<_>>();
+
+        let digits = match chars.len() {
+            10 => {
+                // ISBN-10のチェックディジットは末尾のみXで10を表す
+                let mut digits = Vec::with_capacity(10);
+                for (i, c) in chars.iter().enumerate() {
+                    match (i, c.to_digit(10)) {
+                        (_, Some(d)) => digits.push(d),
+                        (9, None) if *c == 'X' || *c == 'x' => digits.push(10),
+                        _ => return Err(invalid()),
+                    }
+                }
+                let sum: u32 = digits
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| (10 - i as u32) * d)
+                    .sum();
+                if sum % 11 != 0 {
+                    return Err(invalid());
+                }
+                // 先頭に978を付け、チェックディジットを計算し直してISBN-13にする
+                let mut digits = [9, 7, 8]
+                    .into_iter()
+                    .chain(digits.into_iter().take(9))
+                    .collect::<Vec<_>>();
+                digits.push((10 - isbn13_weighted_sum(&digits) % 10) % 10);
+                digits
+            }
+            13 => {
+                let digits = chars
+                    .iter()
+                    .map(|c| c.to_digit(10))
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(invalid)?;
+                if !(digits.starts_with(&[9, 7, 8]) || digits.starts_with(&[9, 7, 9]))
+                    || isbn13_weighted_sum(&digits) % 10 != 0
+                {
+                    return Err(invalid());
+                }
+                digits
+            }
+            _ => return Err(invalid()),
+        };
+
+        Ok(Self(digits.iter().map(|d| d.to_string()).collect()))
+    }
+}
+
+// ISBN-13の各桁に1と3の重みを交互に掛けた和
+fn isbn13_weighted_sum(digits: &[u32]) -> u32 {
+    digits
+        .iter()
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { *d } else { d * 3 })
+        .sum()
+}
+
+impl std::fmt::Display for Isbn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<Isbn> for String {
+    fn from(value: Isbn) -> Self {
+        value.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_isbn() {
+        // ISBN-13はハイフンを除いてそのまま保持する
+        assert_eq!(Isbn::from_str("978-4-06-536957-9").unwrap().as_str(), "9784065369579");
+        // ISBN-10はISBN-13に変換する
+        assert_eq!(Isbn::from_str("4-06-536957-6").unwrap().as_str(), "9784065369579");
+        assert_eq!(Isbn::from_str("080442957X").unwrap().as_str(), "9780804429573");
+        // チェックディジットが合わないもの、桁数や接頭辞が正しくないものは受け付けない
+        assert!(Isbn::from_str("978-4-06-536957-8").is_err());
+        assert!(Isbn::from_str("4-06-536957-7").is_err());
+        assert!(Isbn::from_str("1234567890123").is_err());
+        assert!(Isbn::from_str("Test ISBN").is_err());
+    }
+
+    #[test]
+    fn test_isbn_from_stored() {
+        // 保存されている値は正規化して読み出し、正規化できない値はそのまま読み出す
+        assert_eq!(Isbn::from_stored("4-06-536957-6".into()).as_str(), "9784065369579");
+        assert_eq!(Isbn::from_stored("Test ISBN".into()).as_str(), "Test ISBN");
+    }
+}
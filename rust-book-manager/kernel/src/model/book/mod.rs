@@ -1,26 +1,132 @@
 use crate::model::{
-    id::{BookId, CheckoutId},
+    book::isbn::Isbn,
+    id::{BookCopyId, BookId, CheckoutId, UserId},
+    list::{CursorPosition, SortDirection},
     user::{BookOwner, CheckoutUser},
 };
 use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
 
 pub mod event;
+pub mod isbn;
 
 #[derive(Debug)]
 pub struct Book {
     pub id: BookId,
     pub title: String,
-    pub author: String,
-    pub isbn: String,
+    // 著者。共著の場合は奥付などの表記どおりの順に並べる
+    pub authors: Vec<String>,
+    pub isbn: Isbn,
     pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    // ISO 639-2の3文字の言語コード
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    // 件名分類
+    pub subjects: Vec<String>,
+    // 付けられているタグ名(名前順)
+    pub tags: Vec<String>,
+    // 表紙画像を最後にアップロードした日時。表紙画像がない場合はNone
+    pub cover_updated_at: Option<DateTime<Utc>>,
+    // アーカイブした日時。アーカイブしていない場合はNone
+    pub archived_at: Option<DateTime<Utc>>,
+    // 書誌情報を更新するたびに増える版番号。更新の競合を検出するために使う
+    pub version: i32,
     pub owner: BookOwner,
+    pub copies: Vec<BookCopy>,
+}
+
+impl Book {
+    // 一覧の並び替えや検索に使う、著者を連結した表示用の著者名
+    pub fn author(&self) -> String {
+        display_author(&self.authors)
+    }
+
+    // 書誌が持つ蔵書の冊数
+    pub fn total_copies(&self) -> usize {
+        self.copies.len()
+    }
+
+    // 貸出可能な蔵書の冊数
+    pub fn available_copies(&self) -> usize {
+        self.copies.iter().filter(|copy| copy.is_available()).count()
+    }
+}
+
+pub fn display_author(authors: &[String]) -> String {
+    authors.join(", ")
+}
+
+// 書誌に属する物理的な蔵書
+// 貸出は蔵書単位で行い、バーコードで識別する
+#[derive(Debug)]
+pub struct BookCopy {
+    pub id: BookCopyId,
+    pub barcode: String,
+    pub status: CopyStatus,
     pub checkout: Option<Checkout>,
 }
 
+impl BookCopy {
+    // 紛失・破損しておらず、貸出中でもない蔵書は貸出可能
+    pub fn is_available(&self) -> bool {
+        self.status == CopyStatus::Available && self.checkout.is_none()
+    }
+}
+
+// 蔵書の状態
+// 紛失・破損した蔵書は貸出できない。破損した蔵書は修理すると貸出可能に戻る
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, Default, PartialEq, Eq)]
+pub enum CopyStatus {
+    #[default]
+    Available,
+    Lost,
+    Damaged,
+}
+
 #[derive(Debug)]
 pub struct BookListOptions {
     pub limit: i64,
     pub offset: i64,
+    // 指定された場合はoffsetを使わず、カーソル方式で取得する
+    pub cursor: Option<CursorPosition>,
+    // 指定された場合はタイトル・著者・説明文を全文検索し、関連度の高い順に返す
+    pub search: Option<String>,
+    pub filter: BookListFilter,
+    // 指定しない場合は、検索時は関連度順、それ以外は登録日時の新しい順に返す
+    // ただしカーソル方式では関連度順に並べられないため、常に登録日時の新しい順になる
+    pub sort: Option<BookSort>,
+}
+
+// 蔵書一覧の絞り込み条件
+// 指定されていない条件では絞り込まない
+#[derive(Debug, Default)]
+pub struct BookListFilter {
+    // 著者名の部分一致(大文字・小文字は区別しない)
+    pub author: Option<String>,
+    pub owner: Option<UserId>,
+    // 指定したタグが付いている書誌(大文字・小文字は区別しない)
+    pub tag: Option<String>,
+    // trueなら貸出可能な蔵書が1冊以上ある書誌に、falseなら1冊もない書誌に絞り込む
+    pub available: Option<bool>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BookSort {
+    pub key: BookSortKey,
+    pub direction: SortDirection,
+}
+
+// 蔵書一覧の並び替えに使える項目
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, PartialEq, Eq)]
+pub enum BookSortKey {
+    Title,
+    Author,
+    CreatedAt,
 }
 
 #[derive(Debug)]
@@ -28,4 +134,5 @@ pub struct Checkout {
     pub checkout_id: CheckoutId,
     pub checked_out_by: CheckoutUser,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
 }
\ No newline at end of file
@@ -1,13 +1,18 @@
 use chrono::{DateTime, Utc};
 use derive_new::new;
 
-use crate::model::id::{BookId, CheckoutId, UserId};
+use crate::model::id::{BookCopyId, BookId, CheckoutId, UserId};
 
+// checked_out_byは借りるユーザー、processed_byは貸出操作を行ったユーザー
+// 管理者が代理で貸し出す場合は両者が異なる
+// copy_idがNoneの場合は、書誌の蔵書のうち貸出可能なものを1冊選んで貸し出す
 #[derive(new)]
 pub struct CreateCheckout {
     pub book_id: BookId,
+    pub copy_id: Option<BookCopyId>,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub processed_by: UserId,
 }
 
 #[derive(new)]
@@ -16,4 +21,52 @@ pub struct UpdateReturned {
     pub book_id: BookId,
     pub returned_by: UserId,
     pub returned_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+
+// 管理者が借りたユーザーに代わって強制的に返却する
+#[derive(new)]
+pub struct ForceReturned {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub processed_by: UserId,
+    pub returned_at: DateTime<Utc>,
+}
+
+#[derive(new)]
+pub struct RenewCheckout {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub renewed_by: UserId,
+    pub renewed_at: DateTime<Utc>,
+}
+
+// 貸出中の蔵書の紛失を報告する
+// replacement_feeを指定した場合は、借りたユーザーに弁償金を請求する
+#[derive(new)]
+pub struct ReportLost {
+    pub checkout_id: CheckoutId,
+    pub book_id: BookId,
+    pub reported_by: UserId,
+    pub reported_at: DateTime<Utc>,
+    pub replacement_fee: Option<i64>,
+}
+
+// 返却済みの蔵書の破損を報告する
+// 破損は直近の貸出で生じたものとして扱い、replacement_feeを指定した場合はその貸出のユーザーに請求する
+#[derive(new)]
+pub struct ReportDamaged {
+    pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub reported_by: UserId,
+    pub reported_at: DateTime<Utc>,
+    pub replacement_fee: Option<i64>,
+}
+
+// 破損した蔵書の修理を報告する
+#[derive(new)]
+pub struct ReportRepaired {
+    pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub repaired_by: UserId,
+    pub repaired_at: DateTime<Utc>,
+}
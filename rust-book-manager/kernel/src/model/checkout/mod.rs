@@ -1,5 +1,11 @@
-use crate::model::id::{BookId, CheckoutId, UserId};
-use chrono::{DateTime, Utc};
+use crate::model::{
+    id::{BookCopyId, BookId, CheckoutId, UserId},
+    role::Role,
+    user::CheckoutUser,
+};
+use chrono::{DateTime, Duration, Utc};
+use derive_new::new;
+use strum::{AsRefStr, EnumString};
 
 pub mod event;
 
@@ -8,14 +14,104 @@ pub struct Checkout {
     pub id: CheckoutId,
     pub checked_out_by: UserId,
     pub checked_out_at: DateTime<Utc>,
+    pub due_at: DateTime<Utc>,
+    pub renewal_count: i32,
     pub returned_at: Option<DateTime<Utc>>,
+    // 貸出・返却の操作を行ったユーザー
+    // 管理者が代理で操作した場合は、借りたユーザーとは異なる
+    pub checkout_processed_by: UserId,
+    pub return_processed_by: Option<UserId>,
+    // 紛失・破損が報告された場合はその内容
+    pub incident: Option<CheckoutIncident>,
     pub book: CheckoutBook,
 }
 
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, PartialEq, Eq)]
+pub enum IncidentKind {
+    // 貸出中に紛失した
+    Lost,
+    // 返却後に破損が見つかった
+    Damaged,
+}
+
+#[derive(Debug)]
+pub struct CheckoutIncident {
+    pub kind: IncidentKind,
+    pub reported_at: DateTime<Utc>,
+}
+
 #[derive(Debug)]
 pub struct CheckoutBook {
     pub book_id: BookId,
+    pub copy_id: BookCopyId,
+    pub barcode: String,
     pub title: String,
     pub author: String,
     pub isbn: String,
-}
\ No newline at end of file
+}
+
+// ユーザーの貸出履歴を取得する際の条件
+// from, toを指定した場合は、貸出日時がその範囲に含まれるものに絞り込む
+#[derive(Debug)]
+pub struct CheckoutHistoryOptions {
+    pub limit: i64,
+    pub offset: i64,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+// 返却期限を過ぎた貸出
+// days_overdueは返却期限からの経過日数で、1日未満の超過も1日として数える
+#[derive(Debug)]
+pub struct OverdueCheckout {
+    pub checkout: Checkout,
+    pub days_overdue: i64,
+}
+
+// 延滞中の貸出を借りているユーザーごとにまとめたもの
+#[derive(Debug)]
+pub struct UserOverdueCheckouts {
+    pub user: CheckoutUser,
+    pub checkouts: Vec<OverdueCheckout>,
+}
+
+// 貸出に関するルールをまとめた型
+// 返却期限は貸出日時にloan_period_daysを足したものとする
+// 延長はmax_renewals回まで可能で、1回の延長でloan_period_days分だけ返却期限が延びる
+// 延滞中の貸出は、延滞料金を免れられないよう延長できない
+// 予約の順番が回ってきた場合、hold_pickup_days日の間だけ予約者のために取り置きする
+// 同時に借りられる冊数はロールごとに上限を設定でき、Noneの場合は無制限とする
+#[derive(Debug, Clone, Copy, new)]
+pub struct LoanPolicy {
+    pub loan_period_days: i64,
+    pub max_renewals: i32,
+    pub hold_pickup_days: i64,
+    pub user_checkout_limit: Option<i64>,
+    pub admin_checkout_limit: Option<i64>,
+}
+
+impl LoanPolicy {
+    pub fn due_at(&self, checked_out_at: DateTime<Utc>) -> DateTime<Utc> {
+        checked_out_at + Duration::days(self.loan_period_days)
+    }
+
+    // 延長後の返却期限を算出する
+    pub fn renewed_due_at(&self, due_at: DateTime<Utc>) -> DateTime<Utc> {
+        due_at + Duration::days(self.loan_period_days)
+    }
+
+    pub fn can_renew(&self, renewal_count: i32) -> bool {
+        renewal_count < self.max_renewals
+    }
+
+    pub fn checkout_limit(&self, role: &Role) -> Option<i64> {
+        match role {
+            Role::Admin => self.admin_checkout_limit,
+            Role::User => self.user_checkout_limit,
+        }
+    }
+
+    pub fn pickup_expires_at(&self, ready_at: DateTime<Utc>) -> DateTime<Utc> {
+        ready_at + Duration::days(self.hold_pickup_days)
+    }
+}
@@ -0,0 +1,13 @@
+use crate::model::{
+    cover::CoverFormat,
+    id::{BookId, UserId},
+};
+
+// 書誌の表紙画像をアップロードする。すでにある場合は置き換える
+#[derive(Debug)]
+pub struct UploadCover {
+    pub book_id: BookId,
+    pub format: CoverFormat,
+    pub bytes: Vec<u8>,
+    pub requested_user: UserId,
+}
@@ -0,0 +1,48 @@
+use strum::{AsRefStr, EnumString};
+
+pub mod event;
+
+// アップロードできる表紙画像の最大サイズ(バイト)
+pub const MAX_COVER_SIZE: usize = 5 * 1024 * 1024;
+
+// 表紙画像として受け付ける画像の形式
+// 文字列としてはContent-Typeの値で表す
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, PartialEq, Eq)]
+pub enum CoverFormat {
+    #[strum(serialize = "image/jpeg")]
+    Jpeg,
+    #[strum(serialize = "image/png")]
+    Png,
+    #[strum(serialize = "image/webp")]
+    Webp,
+}
+
+// 表紙画像の大きさ
+// Original以外はアップロード時に生成するサムネイル
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, Default, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum CoverSize {
+    #[default]
+    Original,
+    Medium,
+    Small,
+}
+
+impl CoverSize {
+    pub const THUMBNAILS: [CoverSize; 2] = [CoverSize::Medium, CoverSize::Small];
+
+    // サムネイルの幅・高さの上限(ピクセル)。縦横比は保ったまま、この大きさに収まるよう縮小する
+    pub fn max_dimension(self) -> Option<u32> {
+        match self {
+            CoverSize::Original => None,
+            CoverSize::Medium => Some(480),
+            CoverSize::Small => Some(160),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CoverImage {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+
+use crate::model::{
+    fine::FineKind,
+    id::{CheckoutId, UserId},
+};
+
+#[derive(Debug)]
+pub struct CreateFine {
+    pub user_id: UserId,
+    pub checkout_id: Option<CheckoutId>,
+    pub kind: FineKind,
+    pub amount: i64,
+    pub note: String,
+    pub created_by: UserId,
+    pub created_at: DateTime<Utc>,
+}
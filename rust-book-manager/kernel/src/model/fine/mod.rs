@@ -0,0 +1,73 @@
+use crate::model::id::{CheckoutId, FineId, UserId};
+use chrono::{DateTime, Utc};
+use derive_new::new;
+use strum::{AsRefStr, EnumIter, EnumString};
+
+pub mod event;
+
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, EnumIter, PartialEq, Eq)]
+pub enum FineKind {
+    // 延滞による自動計上
+    Overdue,
+    // 紛失・破損による弁償金
+    Replacement,
+    // 管理者による請求の追加
+    Adjustment,
+    // 管理者による支払いの記録
+    Payment,
+    // 管理者による免除
+    Waiver,
+}
+
+#[derive(Debug)]
+pub struct Fine {
+    pub id: FineId,
+    pub user_id: UserId,
+    pub checkout_id: Option<CheckoutId>,
+    pub kind: FineKind,
+    // 請求はプラス、支払い・免除はマイナス
+    pub amount: i64,
+    pub note: String,
+    // システムによる自動計上の場合はNone
+    pub created_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+}
+
+// ユーザーの罰金台帳と未払い残高
+#[derive(Debug)]
+pub struct UserFines {
+    pub user_id: UserId,
+    pub balance: i64,
+    pub items: Vec<Fine>,
+}
+
+#[derive(Debug)]
+pub struct UserFineBalance {
+    pub user_id: UserId,
+    pub user_name: String,
+    pub balance: i64,
+}
+
+// 罰金に関するルールをまとめた型
+// 延滞料金は1日未満の超過も1日として数える
+#[derive(Debug, Clone, Copy, new)]
+pub struct FinePolicy {
+    pub fine_per_day: i64,
+    pub block_threshold: i64,
+}
+
+impl FinePolicy {
+    pub fn overdue_fine(&self, due_at: DateTime<Utc>, returned_at: DateTime<Utc>) -> i64 {
+        if returned_at <= due_at {
+            return 0;
+        }
+        const MILLIS_PER_DAY: i64 = 86_400_000;
+        let overdue_millis = (returned_at - due_at).num_milliseconds();
+        let days = (overdue_millis + MILLIS_PER_DAY - 1) / MILLIS_PER_DAY;
+        days * self.fine_per_day
+    }
+
+    pub fn blocks_checkout(&self, balance: i64) -> bool {
+        balance > self.block_threshold
+    }
+}
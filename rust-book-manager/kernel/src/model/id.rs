@@ -62,3 +62,7 @@ macro_rules! define_id {
 define_id!(UserId);
 define_id!(BookId);
 define_id!(CheckoutId);
+define_id!(ReservationId);
+define_id!(FineId);
+define_id!(BookCopyId);
+define_id!(TagId);
@@ -1,13 +1,58 @@
+use strum::{AsRefStr, EnumString};
+
 #[derive(Debug)]
 pub struct PaginatedList<T> {
-    pub total: i64,
+    // カーソル方式で取得した場合は件数を数えないため、Noneになる
+    pub total: Option<i64>,
     pub limit: i64,
     pub offset: i64,
     pub items: Vec<T>,
+    // カーソル方式で取得した場合に、次・前のページを取得するためのカーソル
+    // 該当するページがない場合や、オフセット方式で取得した場合はNoneになる
+    pub next_cursor: Option<PageCursor>,
+    pub prev_cursor: Option<PageCursor>,
 }
 
 impl<T> PaginatedList<T> {
     pub fn into_inner(self) -> Vec<T> {
         self.items
     }
-}
\ No newline at end of file
+}
+
+// カーソル方式のページネーションで、ページの位置を表す値
+// 中身の形式はリポジトリの実装が決めるため、呼び出し側では解釈せずにそのまま受け渡す
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageCursor(String);
+
+impl PageCursor {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<PageCursor> for String {
+    fn from(value: PageCursor) -> Self {
+        value.0
+    }
+}
+
+// カーソル方式で取得するページ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CursorPosition {
+    // 先頭のページ
+    First,
+    // 以前の一覧で返したnext_cursorまたはprev_cursorが指すページ
+    At(PageCursor),
+}
+
+// 一覧の並び順
+#[derive(Debug, Clone, Copy, EnumString, AsRefStr, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
@@ -0,0 +1,15 @@
+use crate::model::book::isbn::Isbn;
+
+// 外部の書誌データから取得した、ISBNに対応する書誌情報
+// 蔵書登録時の入力補助に使うため、データに含まれていない項目はNoneになる
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookMetadata {
+    pub isbn: Isbn,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub page_count: Option<i32>,
+    // ISO 639-2形式の言語コード(例: jpn, eng)
+    pub language: Option<String>,
+}
@@ -2,6 +2,12 @@ pub mod book;
 pub mod auth;
 pub mod user;
 pub mod checkout;
+pub mod reservation;
+pub mod fine;
 pub mod id;
 pub mod role;
-pub mod list;
\ No newline at end of file
+pub mod list;
+pub mod metadata;
+pub mod tag;
+pub mod cover;
+pub mod revision;
@@ -0,0 +1,16 @@
+use crate::model::id::{BookId, ReservationId, UserId};
+use chrono::{DateTime, Utc};
+
+pub mod event;
+
+#[derive(Debug)]
+pub struct Reservation {
+    pub id: ReservationId,
+    pub book_id: BookId,
+    pub reserved_by: UserId,
+    pub reserved_at: DateTime<Utc>,
+    // 蔵書の予約待ち行列における順番（1始まり）
+    pub position: i64,
+    // 順番が回ってきている場合、取り置きの期限が入る
+    pub pickup_expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,11 @@
+use crate::model::id::{BookId, UserId};
+
+// 書誌を指定した版の内容に戻す。戻した内容は新しい版として記録する
+#[derive(Debug)]
+pub struct RevertBook {
+    pub book_id: BookId,
+    pub revision: i32,
+    // 戻す前提とする書誌の版番号。書誌がこの版番号のままである場合のみ戻す
+    pub expected_version: i32,
+    pub requested_user: UserId,
+}
@@ -0,0 +1,98 @@
+use crate::model::{
+    book::isbn::Isbn,
+    id::{BookId, UserId},
+};
+use chrono::{DateTime, Utc};
+use strum::{AsRefStr, EnumString};
+
+pub mod event;
+
+// 書誌の版。書誌の登録時と更新時に、その時点の書誌情報を記録する
+// 版番号は書誌ごとに1から順に振る
+#[derive(Debug)]
+pub struct BookRevision {
+    pub book_id: BookId,
+    pub revision: i32,
+    pub snapshot: BookSnapshot,
+    // 1つ前の版から変更された項目。最初の版ではすべての項目になる
+    pub changed_fields: Vec<BookField>,
+    // 編集したユーザーが削除されている場合はNone
+    pub edited_by: Option<BookEditor>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug)]
+pub struct BookEditor {
+    pub id: UserId,
+    pub name: String,
+}
+
+// 版として記録する書誌情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookSnapshot {
+    pub title: String,
+    pub authors: Vec<String>,
+    pub isbn: Isbn,
+    pub description: String,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub language: Option<String>,
+    pub page_count: Option<i32>,
+    pub edition: Option<String>,
+    pub subjects: Vec<String>,
+}
+
+impl BookSnapshot {
+    // previousから変更された項目を返す。previousがない場合はすべての項目を返す
+    pub fn changed_fields(&self, previous: Option<&BookSnapshot>) -> Vec<BookField> {
+        let Some(previous) = previous else {
+            return BookField::ALL.to_vec();
+        };
+        BookField::ALL
+            .into_iter()
+            .filter(|field| match field {
+                BookField::Title => self.title != previous.title,
+                BookField::Authors => self.authors != previous.authors,
+                BookField::Isbn => self.isbn != previous.isbn,
+                BookField::Description => self.description != previous.description,
+                BookField::Publisher => self.publisher != previous.publisher,
+                BookField::PublishedYear => self.published_year != previous.published_year,
+                BookField::Language => self.language != previous.language,
+                BookField::PageCount => self.page_count != previous.page_count,
+                BookField::Edition => self.edition != previous.edition,
+                BookField::Subjects => self.subjects != previous.subjects,
+            })
+            .collect()
+    }
+}
+
+// 版で変更を記録する書誌の項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum BookField {
+    Title,
+    Authors,
+    Isbn,
+    Description,
+    Publisher,
+    PublishedYear,
+    Language,
+    PageCount,
+    Edition,
+    Subjects,
+}
+
+impl BookField {
+    pub const ALL: [BookField; 10] = [
+        Self::Title,
+        Self::Authors,
+        Self::Isbn,
+        Self::Description,
+        Self::Publisher,
+        Self::PublishedYear,
+        Self::Language,
+        Self::PageCount,
+        Self::Edition,
+        Self::Subjects,
+    ];
+}
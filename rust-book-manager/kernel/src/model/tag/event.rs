@@ -0,0 +1,15 @@
+use crate::model::id::BookId;
+
+// 書誌にタグを付ける。まだ存在しないタグ名であればタグを作成する
+#[derive(Debug)]
+pub struct AddBookTag {
+    pub book_id: BookId,
+    pub name: String,
+}
+
+// 書誌からタグを外す。どの書誌にも付いていないタグになった場合はタグも削除する
+#[derive(Debug)]
+pub struct RemoveBookTag {
+    pub book_id: BookId,
+    pub name: String,
+}
@@ -0,0 +1,13 @@
+use crate::model::id::TagId;
+
+pub mod event;
+
+// 書誌をトピックごとに分類するためのタグ
+// タグ名は大文字・小文字を区別せずに一意となる
+#[derive(Debug)]
+pub struct Tag {
+    pub id: TagId,
+    pub name: String,
+    // タグが付けられている書誌の数
+    pub book_count: i64,
+}
@@ -20,6 +20,16 @@ pub struct UpdateUserPassword {
     pub new_password: String,
 }
 
+// 更新後のプロフィール。変更しないフィールドも現在の値で埋めておく
+#[derive(Debug)]
+pub struct UpdateUserProfile {
+    pub user_id: UserId,
+    pub name: String,
+    pub email: String,
+    // メールアドレスを変更する場合に必要な現在のパスワード
+    pub current_password: Option<String>,
+}
+
 #[derive(Debug)]
 pub struct DeleteUser {
     pub user_id: UserId,
@@ -1,4 +1,4 @@
-use crate::model::{id::UserId, role::Role};
+use crate::model::{id::UserId, list::CursorPosition, role::Role};
 
 pub mod event;
 
@@ -10,6 +10,17 @@ pub struct User {
     pub role: Role,
 }
 
+// ユーザー一覧の取得条件
+// 一覧は登録日時の新しい順に並ぶ
+#[derive(Debug)]
+pub struct UserListOptions {
+    // Noneの場合は全件を取得する。カーソル方式では指定が必要
+    pub limit: Option<i64>,
+    pub offset: i64,
+    // 指定された場合はoffsetを使わず、カーソル方式で取得する
+    pub cursor: Option<CursorPosition>,
+}
+
 #[derive(Debug)]
 pub struct BookOwner {
     pub id: UserId,
@@ -1,21 +1,44 @@
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use shared::error::AppResult;
 
 use crate::model::{
     book::{
-        event::{CreateBook, DeleteBook, UpdateBook},
+        event::{
+            ArchiveBook, CreateBook, CreateBookCopy, DeleteBookCopy, RestoreBook, UpdateBook,
+        },
         Book, BookListOptions
     },
     id::{BookId, UserId},
     list::PaginatedList,
+    revision::{event::RevertBook, BookRevision},
 };
 
 #[mockall::automock]
 #[async_trait]
 pub trait BookRepository: Send + Sync {
     async fn create(&self, event: CreateBook, user_id: UserId) -> AppResult<()>;
+    // 複数の書誌を1つのトランザクションで登録する。1件でも失敗した場合はすべて登録しない
+    async fn create_many(&self, events: Vec<(CreateBook, UserId)>) -> AppResult<()>;
+    // 登録しようとしている蔵書と、ISBNが同じかタイトル・著者がほぼ同じ既存の書誌のIDを返す
+    async fn find_duplicates(&self, event: &CreateBook) -> AppResult<Vec<BookId>>;
+    // アーカイブした書誌は一覧に含めない
     async fn find_all(&self, options: BookListOptions) -> AppResult<PaginatedList<Book>>;
+    // アーカイブしていない全書誌を所有者と蔵書の貸出状況つきで順に返す。一度にすべてを読み込まず、少しずつ取得する
+    fn stream_all(&self) -> BoxStream<'static, AppResult<Book>>;
     async fn find_by_id(&self, book_id: BookId) -> AppResult<Option<Book>>;
+    // 登録時と更新時には、その時点の書誌情報を新しい版として記録する
+    // 書誌の版番号がexpected_versionと異なる場合や、アーカイブした書誌は更新しない
     async fn update(&self, event: UpdateBook) -> AppResult<()>;
-    async fn delete(&self, event: DeleteBook) -> AppResult<()>;
+    // 書誌の版を新しい順に返す
+    async fn find_revisions(&self, book_id: BookId) -> AppResult<Vec<BookRevision>>;
+    // 書誌の版番号がexpected_versionと異なる場合や、アーカイブした書誌は戻さない
+    // 書誌の所有者か管理者であることは呼び出し側で確認しておく。requested_userは版の編集者として記録するだけで、権限は確認しない
+    async fn revert(&self, event: RevertBook) -> AppResult<()>;
+    // 貸出中の蔵書がある書誌はアーカイブできない。アーカイブした書誌への予約は取り消す
+    async fn archive(&self, event: ArchiveBook) -> AppResult<()>;
+    async fn restore(&self, event: RestoreBook) -> AppResult<()>;
+    async fn create_copy(&self, event: CreateBookCopy) -> AppResult<()>;
+    // 貸出中・貸出履歴のある蔵書や、取り置き中の予約のために残しておく蔵書は削除できない
+    async fn delete_copy(&self, event: DeleteBookCopy) -> AppResult<()>;
 }
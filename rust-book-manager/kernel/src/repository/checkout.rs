@@ -1,12 +1,17 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use shared::error::AppResult;
 
 use crate::model::{
     checkout::{
-        event::{CreateCheckout, UpdateReturned},
-        Checkout,
+        event::{
+            CreateCheckout, ForceReturned, RenewCheckout, ReportDamaged, ReportLost, ReportRepaired,
+            UpdateReturned,
+        },
+        Checkout, CheckoutHistoryOptions, UserOverdueCheckouts,
     },
     id::{BookId, UserId},
+    list::PaginatedList,
 };
 
 #[mockall::automock]
@@ -18,12 +23,37 @@ pub trait CheckoutRepository: Send + Sync {
     // 返却操作を行う
     async fn update_returned(&self, event: UpdateReturned) -> AppResult<()>;
 
+    // 借りたユーザーに関わらず、管理者が返却操作を行う
+    async fn force_returned(&self, event: ForceReturned) -> AppResult<()>;
+
+    // 貸出の延長操作を行う。返却期限を過ぎた貸出は延長できない
+    async fn renew(&self, event: RenewCheckout) -> AppResult<()>;
+
+    // 貸出中の蔵書の紛失を報告する
+    async fn report_lost(&self, event: ReportLost) -> AppResult<()>;
+
+    // 返却済みの蔵書の破損を報告する。破損した蔵書は修理を報告するまで貸出できない
+    async fn report_damaged(&self, event: ReportDamaged) -> AppResult<()>;
+
+    // 破損した蔵書の修理を報告し、貸出可能な状態に戻す
+    async fn report_repaired(&self, event: ReportRepaired) -> AppResult<()>;
+
     // すべての未返却の貸出し情報を取得する
     async fn find_unreturned_all(&self) -> AppResult<Vec<Checkout>>;
 
+    // 指定日時の時点で返却期限を過ぎている貸出し情報を、ユーザーごとにまとめて取得する
+    async fn find_overdue_all(&self, now: DateTime<Utc>) -> AppResult<Vec<UserOverdueCheckouts>>;
+
     // ユーザーIDに紐づく未返却の貸出し情報を取得する
     async fn find_unreturned_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Checkout>>;
 
+    // ユーザーIDに紐づく貸出履歴（返却済みも含む）を、貸出日時の新しい順にページネーションして取得する
+    async fn find_history_by_user_id(
+        &self,
+        user_id: UserId,
+        options: CheckoutHistoryOptions,
+    ) -> AppResult<PaginatedList<Checkout>>;
+
     // 藏書の貸出履歴（返却済みも含む）を取得する
     async fn find_history_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Checkout>>;
 }
\ No newline at end of file
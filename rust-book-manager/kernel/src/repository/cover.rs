@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    cover::{event::UploadCover, CoverImage, CoverSize},
+    id::BookId,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait CoverRepository: Send + Sync {
+    // 表紙画像を保存し、サムネイルを生成する。書誌を登録したユーザーのみ行える
+    async fn upload(&self, event: UploadCover) -> AppResult<()>;
+
+    // 指定した大きさの表紙画像を取得する。表紙画像がない場合はNoneを返す
+    async fn find(&self, book_id: BookId, size: CoverSize) -> AppResult<Option<CoverImage>>;
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    fine::{event::CreateFine, UserFineBalance, UserFines},
+    id::UserId,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait FineRepository: Send + Sync {
+    // 管理者による請求・支払い・免除を台帳に記録する
+    async fn create(&self, event: CreateFine) -> AppResult<()>;
+
+    // ユーザーの罰金台帳と未払い残高を取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<UserFines>;
+
+    // 未払い残高のあるユーザーの一覧を取得する
+    async fn find_unpaid_balances(&self) -> AppResult<Vec<UserFineBalance>>;
+}
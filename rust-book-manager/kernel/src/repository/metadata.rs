@@ -0,0 +1,11 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{book::isbn::Isbn, metadata::BookMetadata};
+
+#[mockall::automock]
+#[async_trait]
+pub trait BookMetadataRepository: Send + Sync {
+    // ISBNに対応する書誌情報を取得する。見つからない場合はNoneを返す
+    async fn find_by_isbn(&self, isbn: &Isbn) -> AppResult<Option<BookMetadata>>;
+}
@@ -3,3 +3,8 @@ pub mod auth;
 pub mod health;
 pub mod user;
 pub mod checkout;
+pub mod reservation;
+pub mod fine;
+pub mod metadata;
+pub mod tag;
+pub mod cover;
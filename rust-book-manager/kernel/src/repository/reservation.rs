@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::{
+    id::{BookId, UserId},
+    reservation::{
+        event::{CreateReservation, DeleteReservation},
+        Reservation,
+    },
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait ReservationRepository: Send + Sync {
+    // 蔵書の予約を行う
+    async fn create(&self, event: CreateReservation) -> AppResult<()>;
+
+    // 予約を取り消す
+    async fn delete(&self, event: DeleteReservation) -> AppResult<()>;
+
+    // 蔵書に対する予約待ち行列を取得する
+    async fn find_by_book_id(&self, book_id: BookId) -> AppResult<Vec<Reservation>>;
+
+    // ユーザーIDに紐づく予約を取得する
+    async fn find_by_user_id(&self, user_id: UserId) -> AppResult<Vec<Reservation>>;
+}
@@ -0,0 +1,20 @@
+use async_trait::async_trait;
+use shared::error::AppResult;
+
+use crate::model::tag::{
+    event::{AddBookTag, RemoveBookTag},
+    Tag,
+};
+
+#[mockall::automock]
+#[async_trait]
+pub trait TagRepository: Send + Sync {
+    // タグの一覧を、付けられている書誌の数とあわせて取得する
+    async fn find_all(&self) -> AppResult<Vec<Tag>>;
+
+    // 書誌にタグを付ける。すでに付いている場合は何もしない
+    async fn add_to_book(&self, event: AddBookTag) -> AppResult<()>;
+
+    // 書誌からタグを外す
+    async fn remove_from_book(&self, event: RemoveBookTag) -> AppResult<()>;
+}
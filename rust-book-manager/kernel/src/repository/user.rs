@@ -3,9 +3,10 @@ use shared::error::AppResult;
 
 use crate::model:: {
     id::UserId,
+    list::PaginatedList,
     user::{
-        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserRole},
-        User,
+        event::{CreateUser, DeleteUser, UpdateUserPassword, UpdateUserProfile, UpdateUserRole},
+        User, UserListOptions,
     }
 };
 
@@ -13,9 +14,13 @@ use crate::model:: {
 #[async_trait]
 pub trait UserRepository: Send + Sync {
     async fn find_current_user(&self, current_user_id: UserId,) -> AppResult<Option<User>>;
-    async fn find_all(&self) -> AppResult<Vec<User>>;
+    // メールアドレスが一致するユーザーを取得する。大文字・小文字は区別しない
+    async fn find_by_email(&self, email: &str) -> AppResult<Option<User>>;
+    async fn find_all(&self, options: UserListOptions) -> AppResult<PaginatedList<User>>;
     async fn create(&self, event: CreateUser) -> AppResult<User>;
     async fn update_password(&self, event: UpdateUserPassword,) -> AppResult<()>;
     async fn update_role(&self, event: UpdateUserRole) -> AppResult<()>;
+    // 名前とメールアドレスを更新し、更新後のユーザーを返す
+    async fn update_profile(&self, event: UpdateUserProfile) -> AppResult<User>;
     async fn delete(&self, event: DeleteUser) -> AppResult<()>;
 }
\ No newline at end of file
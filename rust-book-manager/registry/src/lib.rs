@@ -3,20 +3,32 @@ use std::sync::Arc;
 use adapter::{
     database::ConnectionPool,
     redis::RedisClient,
+    storage::local::LocalFileStorage,
     repository::{
         book::BookRepositoryImpl,
         health::HealthCheckRepositoryImpl,
         auth::AuthRepositoryImpl,
         user::UserRepositoryImpl,
         checkout::CheckoutRepositoryImpl,
+        reservation::ReservationRepositoryImpl,
+        fine::FineRepositoryImpl,
+        metadata::BookMetadataRepositoryImpl,
+        tag::TagRepositoryImpl,
+        cover::CoverRepositoryImpl,
     },
 };
+use kernel::model::{checkout::LoanPolicy, fine::FinePolicy};
 use kernel::repository::{
     book::BookRepository,
     health::HealthCheckRepository,
     auth::AuthRepository,
     user::UserRepository,
     checkout::CheckoutRepository,
+    reservation::ReservationRepository,
+    fine::FineRepository,
+    metadata::BookMetadataRepository,
+    tag::TagRepository,
+    cover::CoverRepository,
 };
 use shared::config::AppConfig;
 
@@ -28,6 +40,11 @@ pub struct AppRegistryImpl {
     auth_repository: Arc<dyn AuthRepository>,
     user_repository: Arc<dyn UserRepository>,
     checkout_repository: Arc<dyn CheckoutRepository>,
+    reservation_repository: Arc<dyn ReservationRepository>,
+    fine_repository: Arc<dyn FineRepository>,
+    book_metadata_repository: Arc<dyn BookMetadataRepository>,
+    tag_repository: Arc<dyn TagRepository>,
+    cover_repository: Arc<dyn CoverRepository>,
 }
 
 impl AppRegistryImpl {
@@ -37,7 +54,24 @@ impl AppRegistryImpl {
         let book_repository = Arc::new(BookRepositoryImpl::new(pool.clone()));
         let auth_repository = Arc::new(AuthRepositoryImpl::new(pool.clone(), redis_client.clone(), app_config.auth.ttl));
         let user_repository = Arc::new(UserRepositoryImpl::new(pool.clone()));
-        let checkout_repository = Arc::new(CheckoutRepositoryImpl::new(pool.clone()));
+        let loan_policy = LoanPolicy::new(
+            app_config.checkout.loan_period_days,
+            app_config.checkout.max_renewals,
+            app_config.checkout.hold_pickup_days,
+            app_config.checkout.user_checkout_limit,
+            app_config.checkout.admin_checkout_limit,
+        );
+        let fine_policy = FinePolicy::new(
+            app_config.checkout.fine_per_day,
+            app_config.checkout.fine_block_threshold,
+        );
+        let checkout_repository = Arc::new(CheckoutRepositoryImpl::new(pool.clone(), loan_policy, fine_policy));
+        let reservation_repository = Arc::new(ReservationRepositoryImpl::new(pool.clone(), loan_policy));
+        let fine_repository = Arc::new(FineRepositoryImpl::new(pool.clone()));
+        let book_metadata_repository = Arc::new(BookMetadataRepositoryImpl::new(pool.clone()));
+        let tag_repository = Arc::new(TagRepositoryImpl::new(pool.clone()));
+        let cover_storage = Arc::new(LocalFileStorage::new(app_config.cover.storage_dir.clone()));
+        let cover_repository = Arc::new(CoverRepositoryImpl::new(pool.clone(), cover_storage));
 
         Self {
             health_check_repository,
@@ -45,6 +79,11 @@ impl AppRegistryImpl {
             auth_repository,
             user_repository,
             checkout_repository,
+            reservation_repository,
+            fine_repository,
+            book_metadata_repository,
+            tag_repository,
+            cover_repository,
         }
     }
 }
@@ -56,6 +95,11 @@ pub trait AppRegistryExt {
     fn auth_repository(&self) -> Arc<dyn AuthRepository>;
     fn checkout_repository(&self) -> Arc<dyn CheckoutRepository>;
     fn user_repository(&self) -> Arc<dyn UserRepository>;
+    fn reservation_repository(&self) -> Arc<dyn ReservationRepository>;
+    fn fine_repository(&self) -> Arc<dyn FineRepository>;
+    fn book_metadata_repository(&self) -> Arc<dyn BookMetadataRepository>;
+    fn tag_repository(&self) -> Arc<dyn TagRepository>;
+    fn cover_repository(&self) -> Arc<dyn CoverRepository>;
 }
 
 impl AppRegistryExt for AppRegistryImpl {
@@ -79,6 +123,26 @@ impl AppRegistryExt for AppRegistryImpl {
     fn checkout_repository(&self) -> Arc<dyn CheckoutRepository> {
         self.checkout_repository.clone()
     }
+
+    fn reservation_repository(&self) -> Arc<dyn ReservationRepository> {
+        self.reservation_repository.clone()
+    }
+
+    fn fine_repository(&self) -> Arc<dyn FineRepository> {
+        self.fine_repository.clone()
+    }
+
+    fn book_metadata_repository(&self) -> Arc<dyn BookMetadataRepository> {
+        self.book_metadata_repository.clone()
+    }
+
+    fn tag_repository(&self) -> Arc<dyn TagRepository> {
+        self.tag_repository.clone()
+    }
+
+    fn cover_repository(&self) -> Arc<dyn CoverRepository> {
+        self.cover_repository.clone()
+    }
 }
 
 pub type AppRegistry = Arc<dyn AppRegistryExt + Send + Sync + 'static>;
\ No newline at end of file
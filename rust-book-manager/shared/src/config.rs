@@ -1,9 +1,12 @@
 use anyhow::Result;
+use std::{path::PathBuf, str::FromStr};
 
 pub struct AppConfig {
     pub database: DatabaseConfig,
     pub redis: RedisConfig,
     pub auth: AuthConfig,
+    pub checkout: CheckoutConfig,
+    pub cover: CoverConfig,
 }
 
 impl AppConfig {
@@ -23,10 +26,24 @@ impl AppConfig {
         let auth = AuthConfig {
             ttl: std::env::var("AUTH_TOKEN_TTL")?.parse::<u64>()?,
         };
+        let checkout = CheckoutConfig {
+            loan_period_days: std::env::var("LOAN_PERIOD_DAYS")?.parse::<i64>()?,
+            max_renewals: std::env::var("MAX_RENEWALS")?.parse::<i32>()?,
+            hold_pickup_days: std::env::var("HOLD_PICKUP_DAYS")?.parse::<i64>()?,
+            fine_per_day: std::env::var("FINE_PER_DAY")?.parse::<i64>()?,
+            fine_block_threshold: std::env::var("FINE_BLOCK_THRESHOLD")?.parse::<i64>()?,
+            user_checkout_limit: optional_env_var("CHECKOUT_LIMIT_USER")?,
+            admin_checkout_limit: optional_env_var("CHECKOUT_LIMIT_ADMIN")?,
+        };
+        let cover = CoverConfig {
+            storage_dir: std::env::var("COVER_STORAGE_DIR")?.into(),
+        };
         Ok(Self {
             database,
             redis,
             auth,
+            checkout,
+            cover,
         })
     }
 }
@@ -46,4 +63,37 @@ pub struct RedisConfig {
 
 pub struct AuthConfig {
     pub ttl: u64,
-}
\ No newline at end of file
+}
+
+pub struct CheckoutConfig {
+    // 貸出日から返却期限までの日数
+    pub loan_period_days: i64,
+    // 1件の貸出に対して延長できる上限回数
+    pub max_renewals: i32,
+    // 予約の順番が回ってきてから取り置きしておく日数
+    pub hold_pickup_days: i64,
+    // 延滞1日あたりの延滞料金
+    pub fine_per_day: i64,
+    // 未払い残高がこの金額を超えると新たに借りられなくなる
+    pub fine_block_threshold: i64,
+    // ロールごとの同時に借りられる冊数の上限。Noneの場合は無制限
+    pub user_checkout_limit: Option<i64>,
+    pub admin_checkout_limit: Option<i64>,
+}
+
+pub struct CoverConfig {
+    // 表紙画像とサムネイルを保存するディレクトリ
+    pub storage_dir: PathBuf,
+}
+
+// 環境変数が未設定または空文字の場合はNoneを返す
+fn optional_env_var<T>(key: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    match std::env::var(key) {
+        Ok(v) if !v.is_empty() => Ok(Some(v.parse::<T>()?)),
+        _ => Ok(None),
+    }
+}
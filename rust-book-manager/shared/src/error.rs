@@ -8,6 +8,8 @@ pub enum AppError {
     #[error("{0}")]
     EntityNotFound(String),
     #[error("{0}")]
+    BadRequest(String),
+    #[error("{0}")]
     ValidationError(#[from] garde::Report),
     #[error("トランザクションを実行できませんでした。")]
     TransactionError(#[source] sqlx::Error),
@@ -29,6 +31,12 @@ pub enum AppError {
     ForbiddenOperation,
     #[error("{0}")]
     ConversionEntityError(String),
+    #[error("ファイルの保存・読み込みに失敗しました: {0}")]
+    StorageError(#[from] std::io::Error),
+    #[error("{0}")]
+    PreconditionFailed(String),
+    #[error("If-Matchヘッダーを指定してください")]
+    PreconditionRequired,
 }
 
 impl IntoResponse for AppError {
@@ -36,15 +44,20 @@ impl IntoResponse for AppError {
         let status_code = match self {
             AppError::UnprocessableEntity(_) => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::EntityNotFound(_) => StatusCode::NOT_FOUND,
-            AppError::ValidationError(_) | AppError::ConvertToUuidError(_) => StatusCode::BAD_REQUEST,
+            AppError::ValidationError(_)
+            | AppError::ConvertToUuidError(_)
+            | AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
             AppError::UnauthenticatedError | AppError::ForbiddenOperation => StatusCode::FORBIDDEN,
             AppError::UnauthorizedError => StatusCode::UNAUTHORIZED,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PreconditionRequired => StatusCode::PRECONDITION_REQUIRED,
             e @ (AppError::TransactionError(_)
             | AppError::SpecificOperationError(_)
             | AppError::NoRowsAffectedError(_)
             | AppError::KeyValuesStoreError(_)
             | AppError::BcryptError(_)
-            | AppError::ConversionEntityError(_)) => {
+            | AppError::ConversionEntityError(_)
+            | AppError::StorageError(_)) => {
                 tracing::error!(
                     errorr.cause_chain = ?e,
                     error.message = %e,
@@ -6,7 +6,10 @@ use std::{
 use adapter::{database::connect_database_with, redis::RedisClient};
 use anyhow::{Context, Result};
 use api::route::{auth, v1};
-use axum::{http::Method, Router};
+use axum::{
+    http::{header, Method},
+    Router,
+};
 use registry::AppRegistryImpl;
 use shared::{
     config::AppConfig,
@@ -127,9 +130,12 @@ fn cors() -> CorsLayer {
             Method::GET,
             Method::POST,
             Method::PUT,
+            Method::PATCH,
             Method::DELETE,
         ])
         .allow_origin(cors::Any)
+        // 楽観的ロックに使うETagをブラウザのクライアントからも読めるようにする
+        .expose_headers([header::ETAG])
 }
 
 async fn shutdown_signal() {
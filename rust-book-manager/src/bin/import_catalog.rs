@@ -0,0 +1,30 @@
+use std::path::PathBuf;
+
+use adapter::{catalog::CatalogImporter, database::connect_database_with};
+use anyhow::{bail, Result};
+use shared::config::AppConfig;
+
+// ISBNから書誌情報を引くための書誌データを取り込むコマンド
+// Open Libraryのダンプ(版・著者)やMARC21形式のファイルを引数に指定する
+// 例: cargo make import-catalog ol_dump_editions.txt ol_dump_authors.txt
+#[tokio::main]
+async fn main() -> Result<()> {
+    let paths = std::env::args_os().skip(1).map(PathBuf::from).collect::<Vec<_>>();
+    if paths.is_empty() {
+        bail!("取り込む書誌データのファイルを指定してください。");
+    }
+
+    let app_config = AppConfig::new()?;
+    let importer = CatalogImporter::new(connect_database_with(&app_config.database));
+    for path in paths {
+        let summary = importer.import(&path).await?;
+        println!(
+            "{}: 書誌 {}件, 著者 {}件を取り込みました",
+            path.display(),
+            summary.records,
+            summary.authors
+        );
+    }
+
+    Ok(())
+}